@@ -28,6 +28,7 @@ pub mod platform;
 
 pub mod types;
 
+use std::ffi::CStr;
 use std::mem;
 
 pub use types::*;
@@ -44,6 +45,32 @@ pub fn buffer_data<T>(target: BufferTarget, data: &[T], usage: BufferUsage) {
     }
 }
 
+/// Reads back the contents of the buffer bound to `target`, starting at the beginning of the
+/// buffer, into `data`.
+pub fn get_buffer_data<T>(target: BufferTarget, data: &mut [T]) {
+    unsafe {
+        get_buffer_sub_data(
+            target,
+            0,
+            (data.len() * mem::size_of::<T>()) as isize,
+            data.as_mut_ptr() as *mut _,
+        );
+    }
+}
+
+/// Returns the string describing `name` for the current context, or `None` if OpenGL returned
+/// a null pointer (e.g. because no context is current).
+pub fn get_string(name: StringName) -> Option<String> {
+    unsafe {
+        let ptr = get_string_raw(name);
+        if ptr.is_null() {
+            None
+        } else {
+            Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+        }
+    }
+}
+
 pub fn gen_buffer() -> Option<BufferName> {
     let mut buffer_name = BufferName::null();
     unsafe {
@@ -70,6 +97,65 @@ pub fn gen_vertex_array() -> Option<VertexArrayName> {
     }
 }
 
+pub fn gen_framebuffer() -> Option<FramebufferObject> {
+    let mut framebuffer = FramebufferObject::null();
+    unsafe {
+        gen_framebuffers(1, &mut framebuffer);
+    }
+
+    if framebuffer.is_null() {
+        None
+    } else {
+        Some(framebuffer)
+    }
+}
+
+pub fn gen_texture() -> Option<TextureObject> {
+    let mut texture_object = TextureObject::null();
+    unsafe {
+        gen_textures(1, &mut texture_object);
+    }
+
+    if texture_object.is_null() {
+        None
+    } else {
+        Some(texture_object)
+    }
+}
+
+/// Drains all pending OpenGL errors, returning them if any were set.
+///
+/// OpenGL may track several error flags at once, so `get_error` is called in a loop until it
+/// returns `ErrorCode::NoError`. The errors are returned in the order they were reported. Must be
+/// called with a context current.
+pub fn check_error() -> Result<(), Vec<ErrorCode>> {
+    let mut errors = Vec::new();
+    loop {
+        match unsafe { get_error() } {
+            ErrorCode::NoError => break,
+            error => errors.push(error),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Panics if any OpenGL errors are pending, clearing all error flags.
+///
+/// Only checks in debug builds, since querying the error state forces a sync with the driver. In
+/// release builds this does nothing.
+pub fn debug_check_error() {
+    if cfg!(debug_assertions) {
+        if let Err(errors) = check_error() {
+            panic!("OpenGL reported errors: {:?}", errors);
+        }
+    }
+}
+
 gl_proc!(glActiveTexture:
     /// Selects active texture unit.
     ///
@@ -226,6 +312,59 @@ gl_proc!(glBindBuffer:
     ///   `gen_buffers`.
     fn bind_buffer(target: BufferTarget, buffer: BufferName));
 
+gl_proc!(glBindBufferBase:
+    /// Binds a buffer object to an indexed buffer target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindBufferBase)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Binds the buffer object `buffer` to the binding point at index `index` of the array of
+    /// targets specified by `target`. Each `target` represents an indexed array of buffer binding
+    /// points, as well as a single general binding point that can be used by other buffer
+    /// manipulation functions such as `bind_buffer` or `map_buffer`. In addition to binding
+    /// `buffer` to the indexed buffer binding target, `bind_buffer_base` also binds `buffer` to the
+    /// generic buffer binding point specified by `target`.
+    ///
+    /// Calling `bind_buffer_base` is equivalent to calling `bind_buffer_range` with `offset` zero
+    /// and `size` equal to the size of the buffer.
+    ///
+    /// # Version Availability
+    ///
+    /// - The `AtomicCounter` target is available only if the GL version is 4.2 or greater.
+    /// - The `ShaderStorage` target is available only if the GL version is 4.3 or greater.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if `target` is not `BufferTarget::AtomicCounter`,
+    ///   `BufferTarget::TransformFeedback`, `BufferTarget::Uniform` or
+    ///   `BufferTarget::ShaderStorage`.
+    /// - `GL_INVALID_VALUE` is generated if `index` is greater than or equal to the number of
+    ///   target-specific indexed binding points.
+    /// - `GL_INVALID_VALUE` is generated if `buffer` does not have an associated data store, or if
+    ///   the size of that store is zero.
+    fn bind_buffer_base(target: BufferTarget, index: u32, buffer: BufferName));
+
+gl_proc!(glBindFramebuffer:
+    /// Binds a framebuffer to a framebuffer target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBindFramebuffer)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Binds the framebuffer object `framebuffer` to the framebuffer target `target`. If a
+    /// framebuffer object is bound to `DrawFramebuffer` or `ReadFramebuffer`, it becomes the
+    /// target for rendering or readback operations, respectively, until it is deleted or another
+    /// framebuffer is bound to the corresponding bind point. Calling `bind_framebuffer` with
+    /// `target` set to `Framebuffer` binds `framebuffer` to both the read and draw framebuffer
+    /// targets. Binding `FramebufferObject::null()` restores the default framebuffer.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if `framebuffer` is not zero or the name of a
+    ///   framebuffer previously returned from a call to `gen_framebuffers`.
+    fn bind_framebuffer(target: FramebufferTarget, framebuffer: FramebufferObject));
+
 gl_proc!(glBindTexture:
     /// Binds a named texture to a texturing target.
     ///
@@ -308,6 +447,21 @@ gl_proc!(glBindVertexArray:
     ///   object previously returned from a call to `gen_vertex_arrays`.
     fn bind_vertex_array(name: VertexArrayName));
 
+gl_proc!(glBlendEquation:
+    /// Specifies the equation used for both the RGB blend equation and the alpha blend equation.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBlendEquation)
+    ///
+    /// Core since version 1.4
+    ///
+    /// The blend equation determines how a new pixel (the source color) is combined with a pixel
+    /// already in the framebuffer (the destination color). `Add` adds the scaled source and
+    /// destination colors, `Subtract` subtracts the scaled destination from the scaled source, and
+    /// `ReverseSubtract` subtracts the scaled source from the scaled destination. `Min` and `Max`
+    /// take the component-wise minimum or maximum of the source and destination and ignore the
+    /// blend factors. The initial equation is `Add`.
+    fn blend_equation(mode: BlendEquation));
+
 gl_proc!(glBlendFunc:
     /// Specifies pixel arithmetic for both RGB and alpha components.
     ///
@@ -380,6 +534,27 @@ gl_proc!(glBlendFunc:
     ///   `GL_MAX_DUAL_SOURCE_DRAW_BUFFERS`, which may be lower than `GL_MAX_DRAW_BUFFERS`.
     fn blend_func(src_factor: SourceFactor, dest_factor: DestFactor));
 
+gl_proc!(glBlendFuncSeparate:
+    /// Specifies pixel arithmetic for RGB and alpha components separately.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glBlendFuncSeparate)
+    ///
+    /// Core since version 1.4
+    ///
+    /// Behaves like `blend_func`, except that `src_rgb` and `dest_rgb` scale the RGB components
+    /// of the source and destination colors while `src_alpha` and `dest_alpha` scale the alpha
+    /// components. See `blend_func` for a description of each factor. The initial values are
+    /// `One` for both source factors and `Zero` for both destination factors.
+    ///
+    /// Separate factors are needed to keep the alpha channel of a render target correct, e.g.
+    /// blending straight alpha with `(SourceAlpha, OneMinusSourceAlpha)` for the RGB components
+    /// and `(One, OneMinusSourceAlpha)` for alpha produces a premultiplied result.
+    fn blend_func_separate(
+        src_rgb: SourceFactor,
+        dest_rgb: DestFactor,
+        src_alpha: SourceFactor,
+        dest_alpha: DestFactor));
+
 gl_proc!(glBufferData:
     /// Creates and initializes a buffer object's data store.
     ///
@@ -435,6 +610,25 @@ gl_proc!(glBufferData:
     ///   specified size​.
     fn buffer_data_raw(target: BufferTarget, size: isize, data: *const (), usage: BufferUsage));
 
+gl_proc!(glCheckFramebufferStatus:
+    /// Checks the completeness status of a framebuffer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glCheckFramebufferStatus)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Returns the completeness status of the framebuffer object bound to `target`. If the
+    /// framebuffer is complete `FramebufferStatus::Complete` is returned, otherwise the status
+    /// identifies the first rule of framebuffer completeness that the framebuffer violates.
+    /// Drawing to or reading from an incomplete framebuffer generates
+    /// `GL_INVALID_FRAMEBUFFER_OPERATION`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if `target` is not `DrawFramebuffer`, `ReadFramebuffer`
+    ///   or `Framebuffer`.
+    fn check_framebuffer_status(target: FramebufferTarget) -> FramebufferStatus);
+
 gl_proc!(glClear:
     /// Clears buffers to preset values.
     ///
@@ -597,6 +791,24 @@ gl_proc!(glDebugMessageCallback:
         user_param: *mut ()
     ));
 
+gl_proc!(glDeleteFramebuffers:
+    /// Deletes framebuffer objects.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDeleteFramebuffers)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Deletes the `count` framebuffer objects whose names are stored in the array addressed by
+    /// `framebuffers`. The name zero is reserved by the GL and is silently ignored, should it
+    /// occur in `framebuffers`, as are other unused names. Once a framebuffer object is deleted,
+    /// its name is again unused and it has no attachments. If a framebuffer that is currently
+    /// bound is deleted, the binding reverts to the default framebuffer.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `count` is negative.
+    fn delete_framebuffers(count: i32, framebuffers: *const FramebufferObject));
+
 gl_proc!(glDeleteBuffers:
     /// Deletes named buffer objects.
     ///
@@ -738,6 +950,20 @@ gl_proc!(glDepthFunc:
     /// testing is disabled or if no depth buffer exists it is as if the depth test always passes.
     fn depth_func(func: Comparison));
 
+gl_proc!(glDepthMask:
+    /// Enables or disables writing into the depth buffer.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDepthMask)
+    ///
+    /// Core since version 1.0
+    ///
+    /// If `flag` is `False` depth buffer writing is disabled, otherwise it is enabled. Initially
+    /// depth buffer writing is enabled.
+    ///
+    /// The depth mask also applies to `clear`, so the depth buffer isn't cleared while depth
+    /// writes are disabled.
+    fn depth_mask(flag: Boolean));
+
 gl_proc!(glDetachShader:
     /// Detaches a shader object from a program object to which it is attached.
     ///
@@ -792,6 +1018,31 @@ gl_proc!(glDisableVertexAttribArray:
     /// - `GL_INVALID_OPERATION` is generated if no vertex array object is bound.
     fn disable_vertex_attrib_array(attrib: AttributeLocation));
 
+gl_proc!(glDispatchCompute:
+    /// Launches one or more compute work groups.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDispatchCompute)
+    ///
+    /// Core since version 4.3
+    ///
+    /// Launches one or more compute work groups. Each work group is processed by the active
+    /// program object for the compute shader stage. While the individual shader invocations within
+    /// a work group are executed as a unit, work groups are executed completely independently and
+    /// in unspecified order. `num_groups_x`, `num_groups_y` and `num_groups_z` specify the number
+    /// of local work groups that will be dispatched in the X, Y and Z dimensions, respectively.
+    ///
+    /// Writes made by the compute shader are not guaranteed to be visible to subsequent commands
+    /// until `memory_barrier` is called with the appropriate barrier bits.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if there is no active program for the compute shader
+    ///   stage.
+    /// - `GL_INVALID_VALUE` is generated if any of `num_groups_x`, `num_groups_y`, or
+    ///   `num_groups_z` is greater than or equal to the maximum work-group count for the
+    ///   corresponding dimension.
+    fn dispatch_compute(num_groups_x: u32, num_groups_y: u32, num_groups_z: u32));
+
 gl_proc!(glDrawArrays:
     /// Renders primitives from array data.
     ///
@@ -826,6 +1077,31 @@ gl_proc!(glDrawArrays:
     ///    object.
     fn draw_arrays(mode: DrawMode, first: i32, count: i32));
 
+gl_proc!(glDrawArraysInstanced:
+    /// Draws multiple instances of a range of elements.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDrawArraysInstanced)
+    ///
+    /// Core since version 3.1
+    ///
+    /// Behaves identically to `draw_arrays` except that `instance_count` instances of the range of
+    /// elements are executed and the value of the internal counter `instanceID` advances for each
+    /// iteration. `instanceID` is an internal 32-bit integer counter that may be read by a vertex
+    /// shader as `gl_InstanceID`.
+    ///
+    /// Vertex attributes with a non-zero divisor (see `vertex_attrib_divisor`) advance once every
+    /// `divisor` instances rather than once per vertex.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `count` or `instance_count` is negative.
+    /// - `GL_INVALID_OPERATION` is generated if a non-zero buffer object name is bound to an enabled
+    ///   array and the buffer object's data store is currently mapped.
+    /// - `GL_INVALID_OPERATION` is generated if a geometry shader is active and `mode` is
+    ///   incompatible with the input primitive type of the geometry shader in the currently
+    ///   installed program object.
+    fn draw_arrays_instanced(mode: DrawMode, first: i32, count: i32, instance_count: i32));
+
 gl_proc!(glDrawElements:
     /// Renders primitives from array data.
     ///
@@ -867,6 +1143,37 @@ gl_proc!(glDrawElements:
     ///   mapped.
     fn draw_elements(mode: DrawMode, count: i32, index_type: IndexType, offset: usize));
 
+gl_proc!(glDrawElementsInstanced:
+    /// Draws multiple instances of a set of elements.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glDrawElementsInstanced)
+    ///
+    /// Core since version 3.1
+    ///
+    /// Behaves identically to `draw_elements` except that `instance_count` instances of the set of
+    /// elements are executed and the value of the internal counter `instanceID` advances for each
+    /// iteration. `instanceID` is an internal 32-bit integer counter that may be read by a vertex
+    /// shader as `gl_InstanceID`.
+    ///
+    /// Vertex attributes with a non-zero divisor (see `vertex_attrib_divisor`) advance once every
+    /// `divisor` instances rather than once per vertex.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `count` or `instance_count` is negative.
+    /// - `GL_INVALID_OPERATION` is generated if a geometry shader is active and `mode` is
+    ///   incompatible with the input primitive type of the geometry shader in the currently
+    ///   installed program object.
+    /// - `GL_INVALID_OPERATION` is generated if a non-zero buffer object name is bound to an
+    ///   enabled array or the element array and the buffer object's data store is currently
+    ///   mapped.
+    fn draw_elements_instanced(
+        mode: DrawMode,
+        count: i32,
+        index_type: IndexType,
+        offset: usize,
+        instance_count: i32));
+
 gl_proc!(glEnable:
     /// Enables server-side GL capabilities.
     ///
@@ -914,6 +1221,31 @@ gl_proc!(glFlush:
     /// TODO: Add documentation.
     fn flush());
 
+gl_proc!(glFramebufferTexture2D:
+    /// Attaches a level of a texture object as a logical buffer of a framebuffer object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glFramebufferTexture)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Attaches the image `level` of `texture` to the attachment point `attachment` of the
+    /// framebuffer object bound to `target`. `texture_target` specifies the texture target of
+    /// `texture`, which for 2D textures is `Texture2dTarget::Texture2d`. If `texture` is
+    /// `TextureObject::null()` any image attached to `attachment` is detached.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if `target` is not one of the accepted tokens.
+    /// - `GL_INVALID_OPERATION` is generated if zero is bound to `target`.
+    /// - `GL_INVALID_OPERATION` is generated if `texture_target` and `texture` are not
+    ///   compatible.
+    fn framebuffer_texture_2d(
+        target: FramebufferTarget,
+        attachment: FramebufferAttachment,
+        texture_target: Texture2dTarget,
+        texture: TextureObject,
+        level: i32));
+
 gl_proc!(glFrontFace:
     /// Defines front- and back-facing polygons.
     ///
@@ -974,6 +1306,41 @@ gl_proc!(glGenTextures:
     /// unless they are first deleted with `delete_textures`.
     fn gen_textures(count: u32, textures: *mut TextureObject));
 
+gl_proc!(glGenFramebuffers:
+    /// Generates framebuffer object names.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGenFramebuffers)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Returns `count` framebuffer object names in `framebuffers`. There is no guarantee that the
+    /// names form a contiguous set of integers; however, it is guaranteed that none of the
+    /// returned names was in use immediately before the call to `gen_framebuffers`.
+    ///
+    /// Framebuffer object names returned by a call to `gen_framebuffers` are not returned by
+    /// subsequent calls, unless they are first deleted with `delete_framebuffers`.
+    fn gen_framebuffers(count: i32, framebuffers: *mut FramebufferObject));
+
+gl_proc!(glGenerateMipmap:
+    /// Generates mipmaps for a specified texture target.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGenerateMipmap)
+    ///
+    /// Core since version 3.0
+    ///
+    /// Generates mipmaps for the texture bound to `target` on the active texture unit. Mipmap
+    /// generation replaces texel array levels level base + 1 through q with arrays derived from
+    /// the level base array, regardless of their previous contents. All other mipmap arrays,
+    /// including the level base array, are left unchanged by this computation. The internal
+    /// formats of the derived mipmap arrays all match those of the level base array.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_ENUM` is generated if `target` is not one of the accepted texture targets.
+    /// - `GL_INVALID_OPERATION` is generated if `target` is `CubeMap` and the texture bound to
+    ///   `target` is not cube complete.
+    fn generate_mipmap(target: TextureBindTarget));
+
 gl_proc!(glGenQueries:
     /// Generates query object names.
     ///
@@ -1014,6 +1381,41 @@ gl_proc!(glGenVertexArrays:
     /// `GL_INVALID_VALUE` is generated if `num_arrays`​ is negative.
     fn gen_vertex_arrays(num_arrays: i32, arrays: *mut VertexArrayName));
 
+gl_proc!(glGetActiveUniform:
+    /// Returns information about an active uniform variable for the specified program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetActiveUniform)
+    ///
+    /// Core since version 2.0
+    ///
+    /// Returns information about the active uniform at `index` in `program`. The number of active
+    /// uniforms can be queried with `get_program_param` and `ProgramParam::ActiveUniforms`, and
+    /// indices range from 0 to that number minus 1. The name of the uniform is written to `name`
+    /// as a null terminated string of at most `buf_size` characters (including the null
+    /// terminator), and the number of characters written (excluding the null terminator) is
+    /// returned in `length`. The length of the longest uniform name can be queried with
+    /// `ProgramParam::ActiveUniformMaxLength`.
+    ///
+    /// `size` receives the number of elements if the uniform is an array, and 1 otherwise. The
+    /// name of an array uniform is reported with "[0]" appended. `uniform_type` receives the
+    /// `GLenum` describing the type of the uniform, e.g. `GL_FLOAT_VEC3` or `GL_SAMPLER_2D`.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `program` is not a value generated by OpenGL.
+    /// - `GL_INVALID_OPERATION` is generated if `program` is not a program object.
+    /// - `GL_INVALID_VALUE` is generated if `index` is greater than or equal to the number of
+    ///   active uniform variables in `program`.
+    /// - `GL_INVALID_VALUE` is generated if `buf_size` is less than 0.
+    fn get_active_uniform(
+        program: ProgramObject,
+        index: u32,
+        buf_size: i32,
+        length: *mut i32,
+        size: *mut i32,
+        uniform_type: *mut u32,
+        name: *mut u8));
+
 gl_proc!(glGetAttribLocation:
     /// Returns the location of an attribute variable.
     ///
@@ -1043,6 +1445,58 @@ gl_proc!(glGetAttribLocation:
     /// - `GL_INVALID_OPERATION` is generated if `program` has not been successfully linked.
     fn get_attrib_location(program: ProgramObject, name: *const u8) -> i32);
 
+gl_proc!(glGetBufferSubData:
+    /// Returns a subset of a buffer object's data store.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetBufferSubData)
+    ///
+    /// Core since version 1.5
+    ///
+    /// Returns some or all of the data contents of the data store of the buffer object currently
+    /// bound to `target`. Data starting at byte offset `offset` and extending for `size` bytes is
+    /// copied from the buffer object's data store to the memory pointed to by `data`. An error is
+    /// thrown if the buffer object is currently mapped, or if `offset` and `size` together define
+    /// a range beyond the bounds of the buffer object's data store.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `offset` or `size` is negative, or if together they
+    ///   define a region of memory that extends beyond the buffer object's allocated data store.
+    /// - `GL_INVALID_OPERATION` is generated if the reserved buffer object name 0 is bound to
+    ///   `target`.
+    /// - `GL_INVALID_OPERATION` is generated if the buffer object being queried is mapped.
+    fn get_buffer_sub_data(
+        target: BufferTarget,
+        offset: isize,
+        size: isize,
+        data: *mut ()));
+
+gl_proc!(glGetError:
+    /// Returns error information.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glGetError)
+    ///
+    /// Core since 1.0
+    ///
+    /// `get_error` returns the value of the error flag. Each detectable error is assigned a
+    /// numeric code and symbolic name. When an error occurs, the error flag is set to the
+    /// appropriate error code value. No other errors are recorded until `get_error` is called,
+    /// the error code is returned, and the flag is reset to `ErrorCode::NoError`. If a call to
+    /// `get_error` returns `ErrorCode::NoError`, there has been no detectable error since the last
+    /// call to `get_error`, or since the GL was initialized.
+    ///
+    /// To allow for distributed implementations, there may be several error flags. If any single
+    /// error flag has recorded an error, the value of that flag is returned and that flag is reset
+    /// to `ErrorCode::NoError` when `get_error` is called. If more than one flag has recorded an
+    /// error, `get_error` returns and clears an arbitrary error flag value. Thus, `get_error`
+    /// should always be called in a loop, until it returns `ErrorCode::NoError`, if all error
+    /// flags are to be reset.
+    ///
+    /// When an error flag is set, results of a GL operation are undefined only if
+    /// `ErrorCode::OutOfMemory` has occurred. In all other cases, the command generating the
+    /// error is ignored and has no effect on the GL state or frame buffer contents.
+    fn get_error() -> ErrorCode);
+
 gl_proc!(glGetInteger64v:
     /// Returns the value for simple state variables.
     ///
@@ -1068,12 +1522,22 @@ gl_proc!(glGetIntegerv:
     ///
     /// The following values for `name` are accepted:
     ///
+    /// - `Viewport` - `params` returns four values: the x and y window coordinates of the
+    ///   viewport, followed by its width and height. See `viewport`.
+    /// - `ScissorBox` - `params` returns four values: the x and y window coordinates of the
+    ///   scissor box, followed by its width and height. See `scissor`.
     /// - `MajorVersion` - `params` returns one value, the major version number of the OpenGL API
     ///   supported by the current context.
     /// - `MinorVersion` - `params` returns one value, the minor version number of the OpenGL API
     ///   supported by the current context.
     /// - GL_NUM_EXTENSIONS - `params` returns one value, the number of extensions supported by
     ///   the GL implementation for the current context. See `get_string`.
+    /// - `MaxTextureImageUnits` - `params` returns one value, the maximum supported texture image
+    ///   units that can be used to access texture maps from the fragment shader. The value must
+    ///   be at least 16.
+    /// - `MaxCombinedTextureImageUnits` - `params` returns one value, the maximum supported
+    ///   texture image units that can be used to access texture maps from the vertex shader and
+    ///   the fragment processor combined. The value must be at least 48.
     fn get_integers(name: IntegerName, params: *mut i32));
 
 gl_proc!(glGetProgramInfoLog:
@@ -1333,7 +1797,7 @@ gl_proc!(glGetString:
     /// Vendor-specific information may follow the version number. Its format depends on the
     /// implementation, but a space always separates the version number and the vendor-specific
     /// information.
-    fn get_string(name: StringName) -> *const i8);
+    fn get_string_raw(name: StringName) -> *const i8);
 
 gl_proc!(glUniform1f:
     /// Specify the value of a uniform variable for the current program object.
@@ -1464,6 +1928,54 @@ gl_proc!(glUniform1ui:
     ///   variable will not be changed.
     fn uniform_u32x1(location: UniformLocation, value: u32));
 
+gl_proc!(glUniform2ui:
+    /// Specify the value of a uniform variable for the current program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniform)
+    ///
+    /// Core since version 3.0
+    ///
+    /// The command `uniform_u32x2` changes the value of the uniform variable specified by `location​`
+    /// using the values passed as arguments. The number specified in the command should match the
+    /// number of components in the data type of the specified uniform variable (i.e. `uniform_u32x2`
+    /// should be used to set a `uvec2` variable).
+    ///
+    /// See `uniform_u32x1` for notes on the errors that can be generated when setting a uniform
+    /// variable.
+    fn uniform_u32x2(location: UniformLocation, x: u32, y: u32));
+
+gl_proc!(glUniform3ui:
+    /// Specify the value of a uniform variable for the current program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniform)
+    ///
+    /// Core since version 3.0
+    ///
+    /// The command `uniform_u32x3` changes the value of the uniform variable specified by `location​`
+    /// using the values passed as arguments. The number specified in the command should match the
+    /// number of components in the data type of the specified uniform variable (i.e. `uniform_u32x3`
+    /// should be used to set a `uvec3` variable).
+    ///
+    /// See `uniform_u32x1` for notes on the errors that can be generated when setting a uniform
+    /// variable.
+    fn uniform_u32x3(location: UniformLocation, x: u32, y: u32, z: u32));
+
+gl_proc!(glUniform4ui:
+    /// Specify the value of a uniform variable for the current program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniform)
+    ///
+    /// Core since version 3.0
+    ///
+    /// The command `uniform_u32x4` changes the value of the uniform variable specified by `location​`
+    /// using the values passed as arguments. The number specified in the command should match the
+    /// number of components in the data type of the specified uniform variable (i.e. `uniform_u32x4`
+    /// should be used to set a `uvec4` variable).
+    ///
+    /// See `uniform_u32x1` for notes on the errors that can be generated when setting a uniform
+    /// variable.
+    fn uniform_u32x4(location: UniformLocation, x: u32, y: u32, z: u32, w: u32));
+
 gl_proc!(glUniform2f:
     /// Specify the value of a uniform variable for the current program object.
     ///
@@ -1673,6 +2185,54 @@ gl_proc!(glUniform1iv:
     ///   variable will not be changed.
     fn uniform_i32x1v(location: UniformLocation, count: i32, data: *const i32));
 
+gl_proc!(glUniform2i:
+    /// Specify the value of a uniform variable for the current program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniform)
+    ///
+    /// Core since version 2.0
+    ///
+    /// The command `uniform_i32x2` changes the value of the uniform variable specified by `location​`
+    /// using the values passed as arguments. The number specified in the command should match the
+    /// number of components in the data type of the specified uniform variable (i.e. `uniform_i32x2`
+    /// should be used to set an `ivec2` variable).
+    ///
+    /// See `uniform_i32x1` for notes on the errors that can be generated when setting a uniform
+    /// variable.
+    fn uniform_i32x2(location: UniformLocation, x: i32, y: i32));
+
+gl_proc!(glUniform3i:
+    /// Specify the value of a uniform variable for the current program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniform)
+    ///
+    /// Core since version 2.0
+    ///
+    /// The command `uniform_i32x3` changes the value of the uniform variable specified by `location​`
+    /// using the values passed as arguments. The number specified in the command should match the
+    /// number of components in the data type of the specified uniform variable (i.e. `uniform_i32x3`
+    /// should be used to set an `ivec3` variable).
+    ///
+    /// See `uniform_i32x1` for notes on the errors that can be generated when setting a uniform
+    /// variable.
+    fn uniform_i32x3(location: UniformLocation, x: i32, y: i32, z: i32));
+
+gl_proc!(glUniform4i:
+    /// Specify the value of a uniform variable for the current program object.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glUniform)
+    ///
+    /// Core since version 2.0
+    ///
+    /// The command `uniform_i32x4` changes the value of the uniform variable specified by `location​`
+    /// using the values passed as arguments. The number specified in the command should match the
+    /// number of components in the data type of the specified uniform variable (i.e. `uniform_i32x4`
+    /// should be used to set an `ivec4` variable).
+    ///
+    /// See `uniform_i32x1` for notes on the errors that can be generated when setting a uniform
+    /// variable.
+    fn uniform_i32x4(location: UniformLocation, x: i32, y: i32, z: i32, w: i32));
+
 gl_proc!(glUniform3fv:
     /// Specify the value of a uniform variable for the current program object.
     ///
@@ -2035,6 +2595,31 @@ gl_proc!(glLinkProgram:
     ///   and transform feedback mode is active.
     fn link_program(program: ProgramObject));
 
+gl_proc!(glMemoryBarrier:
+    /// Defines a barrier ordering memory transactions.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glMemoryBarrier)
+    ///
+    /// Core since version 4.2
+    ///
+    /// Defines a barrier ordering the memory transactions issued prior to the command relative to
+    /// those issued after the barrier. For the purposes of this ordering, memory transactions
+    /// performed by shaders are considered to be issued by the rendering command that triggered
+    /// the execution of the shader. `barriers` is a bitfield indicating the set of operations that
+    /// are synchronized with shader stores; multiple masks can be combined with `|`.
+    ///
+    /// # Version Availability
+    ///
+    /// - `MemoryBarrierMask::ShaderStorage` is available only if the GL version is 4.3 or greater.
+    /// - `MemoryBarrierMask::ClientMappedBuffer` and `MemoryBarrierMask::QueryBuffer` are
+    ///   available only if the GL version is 4.4 or greater.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `barriers` is not the special value
+    ///   `MemoryBarrierMask::All` and has any bits set other than those listed above.
+    fn memory_barrier(barriers: MemoryBarrierMask));
+
 gl_proc!(glObjectLabel:
     /// Labels a named object for use in debug messages.
     ///
@@ -2081,6 +2666,26 @@ gl_proc!(glPolygonMode:
     /// `edge_flag`.
     fn polygon_mode(face: Face, mode: PolygonMode));
 
+gl_proc!(glPolygonOffset:
+    /// Sets the scale and units used to calculate depth values.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glPolygonOffset)
+    ///
+    /// Core since version 1.1
+    ///
+    /// When `ServerCapability::PolygonOffsetFill`, `ServerCapability::PolygonOffsetLine`, or
+    /// `ServerCapability::PolygonOffsetPoint` is enabled, each fragment's depth value will be
+    /// offset after it is interpolated from the depth values of the appropriate vertices. The
+    /// value of the offset is `factor * DZ + r * units`, where `DZ` is a measurement of the
+    /// change in depth relative to the screen area of the polygon, and `r` is the smallest value
+    /// that is guaranteed to produce a resolvable offset for a given implementation. The offset
+    /// is added before the depth test is performed and before the value is written into the depth
+    /// buffer.
+    ///
+    /// `polygon_offset` is useful for rendering hidden-line images, for applying decals to
+    /// surfaces, and for rendering solids with highlighted edges.
+    fn polygon_offset(factor: f32, units: f32));
+
 gl_proc!(glQueryCounter:
     /// Records the GL time into a query object after all previous commands have reached the GL
     /// server.
@@ -2104,6 +2709,30 @@ gl_proc!(glQueryCounter:
     ///   from a previous call to `gen_queries()`.
     fn query_counter(query: QueryObject, target: QueryCounterTarget));
 
+gl_proc!(glScissor:
+    /// Defines the scissor box.
+    ///
+    /// [Wiki Page](https://www.opengl.org/wiki/GLAPI/glScissor)
+    ///
+    /// Core since 1.0
+    ///
+    /// Defines a rectangle, called the scissor box, in window coordinates. The first two
+    /// arguments, `x` and `y`, specify the lower left corner of the box. `width` and `height`
+    /// specify the width and height of the box.
+    ///
+    /// To enable and disable the scissor test, call `enable` and `disable` with argument
+    /// `ServerCapability::ScissorTest`. The test is initially disabled. While the test is
+    /// enabled, only pixels that lie within the scissor box can be modified by drawing commands.
+    /// Window coordinates have integer values at the shared corners of frame buffer pixels.
+    ///
+    /// When the scissor test is disabled, it is as though the scissor box includes the entire
+    /// window.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if either width or height is negative.
+    fn scissor(x: i32, y: i32, width: i32, height: i32));
+
 gl_proc!(glShaderSource:
     /// Replaces the source code in a shader object.
     ///
@@ -2259,6 +2888,42 @@ gl_proc!(glTexParameteri:
         name: TextureParameterName,
         param: i32));
 
+gl_proc!(glTexSubImage2D:
+    /// Specifies a two-dimensional texture subimage.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glTexSubImage2D)
+    ///
+    /// Core since version 1.1
+    ///
+    /// Redefines a contiguous subregion of an existing two-dimensional texture image. The texels
+    /// referenced by `data` replace the portion of the existing texture array with x indices
+    /// `x_offset` and `x_offset + width - 1`, inclusive, and y indices `y_offset` and
+    /// `y_offset + height - 1`, inclusive. This region may not include any texels outside the
+    /// range of the texture array as it was originally specified. It is not an error to specify a
+    /// subtexture with zero width or height, but such a specification has no effect.
+    ///
+    /// Unlike `texture_image_2d`, `texture_sub_image_2d` doesn't reallocate the texture's storage,
+    /// so it can be used to upload a large texture incrementally.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_OPERATION` is generated if the texture array has not been defined by a
+    ///   previous `texture_image_2d` operation.
+    /// - `GL_INVALID_VALUE` is generated if `x_offset < 0`, `x_offset + width > w`,
+    ///   `y_offset < 0`, or `y_offset + height > h`, where `w` is the width and `h` is the height
+    ///   of the texture image being modified.
+    /// - `GL_INVALID_VALUE` is generated if `width` or `height` is less than 0.
+    fn texture_sub_image_2d(
+        target: Texture2dTarget,
+        level: i32,
+        x_offset: i32,
+        y_offset: i32,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        data_type: TextureDataType,
+        data: *const ()));
+
 gl_proc!(glUseProgram:
     /// Installs a program as part of the current rendering state.
     ///
@@ -2316,6 +2981,24 @@ gl_proc!(glUseProgram:
     /// - `GL_INVALID_OPERATION` is generated if transform feedback mode is active.
     fn use_program(program: ProgramObject));
 
+gl_proc!(glVertexAttribDivisor:
+    /// Modifies the rate at which generic vertex attributes advance during instanced rendering.
+    ///
+    /// [Wiki page](https://www.opengl.org/wiki/GLAPI/glVertexAttribDivisor)
+    ///
+    /// Core since version 3.3
+    ///
+    /// Modifies the rate at which generic vertex attributes advance when rendering multiple
+    /// instances of primitives in a single draw call. If `divisor` is zero, the attribute at slot
+    /// `attrib` advances once per vertex. If `divisor` is non-zero, the attribute advances once
+    /// per `divisor` instances of the set(s) of vertices being rendered.
+    ///
+    /// # Errors
+    ///
+    /// - `GL_INVALID_VALUE` is generated if `attrib` is greater than or equal to
+    ///   `GL_MAX_VERTEX_ATTRIBS`.
+    fn vertex_attrib_divisor(attrib: AttributeLocation, divisor: u32));
+
 gl_proc!(glVertexAttribPointer:
     /// Defines an array of generic vertex attribute data.
     ///
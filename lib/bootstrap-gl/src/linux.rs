@@ -1,39 +1,85 @@
-use std::ffi::CString;
 use std::mem;
 use std::ptr;
+use std::os::raw::{c_char, c_int, c_void};
 
-use gl;
-use super::x11::glx;
-// use super::x11::xlib;
+pub type DeviceContext = *mut c_void;
+pub type Context = *mut c_void;
 
-use window::Window;
+const RTLD_LAZY: c_int = 0x0001;
+const RTLD_GLOBAL: c_int = 0x0100;
 
-pub type Context = glx::GLXContext;
+#[link(name = "GL")]
+extern "C" {
+    fn glXGetProcAddress(proc_name: *const u8) -> Option<extern "system" fn()>;
+    fn glXGetProcAddressARB(proc_name: *const u8) -> Option<extern "system" fn()>;
+}
 
-pub unsafe fn init(_window: &Window) {
-    println!("gl::init() is not implemented on linux");
+#[link(name = "dl")]
+extern "C" {
+    fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
 }
 
-pub unsafe fn create_context(window: &Window) -> GLContext {
-    let context = unsafe {
-        let context = glx::glXCreateContext(window.display, window.visual_info, ptr::null_mut(), 1);
-        glx::glXMakeCurrent(window.display, window.window, context);
-        context
-    };
+static mut LIBGL: *mut c_void = 0 as *mut c_void;
+
+pub unsafe fn load_proc(proc_name: &str) -> Option<extern "system" fn()> {
+    let string = proc_name.as_bytes();
+    debug_assert!(
+        string[string.len() - 1] == 0,
+        "Proc name \"{}\" is not null terminated",
+        proc_name,
+    );
+
+    if let Some(ptr) = glXGetProcAddress(string.as_ptr()) {
+        return Some(ptr);
+    }
+
+    if let Some(ptr) = glXGetProcAddressARB(string.as_ptr()) {
+        return Some(ptr);
+    }
+
+    // Core 1.0 procs aren't always exposed through `glXGetProcAddress`, so fall back to looking
+    // them up in libGL directly.
+    if LIBGL.is_null() {
+        LIBGL = dlopen(b"libGL.so.1\0".as_ptr() as *const _, RTLD_LAZY | RTLD_GLOBAL);
+    }
+
+    if LIBGL.is_null() {
+        LIBGL = dlopen(b"libGL.so\0".as_ptr() as *const _, RTLD_LAZY | RTLD_GLOBAL);
+    }
+
+    if LIBGL.is_null() {
+        println!("pointer for {} was null, failed to open libGL.so", proc_name);
+        return None;
+    }
 
-    set_proc_loader();
+    let ptr = dlsym(LIBGL, string.as_ptr() as *const _);
+    if ptr.is_null() {
+        println!("pointer for {} was null", proc_name);
+        return None;
+    }
 
-    context
+    Some(mem::transmute(ptr))
 }
 
-pub unsafe fn set_proc_loader() {
-    // provide method for loading functions
-    gl::load_with(|s| {
-        let string = CString::new(s);
-        mem::transmute(glx::glXGetProcAddress(mem::transmute(string.unwrap().as_ptr())))
-    });
+// TODO: Context management isn't implemented on Linux yet. These stubs keep the same platform API
+// as the other platforms so that code using it still builds.
+
+pub unsafe fn init(_device_context: DeviceContext) {
+    println!("gl::init() is not implemented on linux");
 }
 
-pub unsafe fn swap_buffers(window: &Window) {
-    glx::glXSwapBuffers(window.display, window.window);
+pub unsafe fn create_context(_device_context: DeviceContext) -> Option<Context> {
+    println!("gl::create_context() is not implemented on linux");
+    None
 }
+
+pub unsafe fn destroy_context(_context: Context) {}
+
+pub unsafe fn swap_buffers(_context: Context) {}
+
+pub unsafe fn make_current(_context: Context) -> Context {
+    ptr::null_mut()
+}
+
+pub unsafe fn clear_current() {}
@@ -61,6 +61,20 @@ impl AttributeLocation {
     }
 }
 
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendEquation {
+    Add = 0x8006,
+    Subtract = 0x800A,
+    ReverseSubtract = 0x800B,
+    Min = 0x8007,
+    Max = 0x8008,
+}
+
+impl Default for BlendEquation {
+    fn default() -> BlendEquation { BlendEquation::Add }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BufferName(pub u32);
@@ -233,6 +247,7 @@ pub enum ErrorCode {
     StackOverflow = 0x0503,
     StackUnderflow = 0x0504,
     OutOfMemory = 0x0505,
+    InvalidFramebufferOperation = 0x0506,
 }
 
 #[repr(u32)]
@@ -247,6 +262,51 @@ impl Default for Face {
     fn default() -> Face { Face::Back }
 }
 
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramebufferAttachment {
+    Color0 = 0x8CE0,
+    Depth = 0x8D00,
+    Stencil = 0x8D20,
+    DepthStencil = 0x821A,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FramebufferObject(pub u32);
+
+impl FramebufferObject {
+    pub const fn null() -> FramebufferObject {
+        FramebufferObject(0)
+    }
+
+    pub fn is_null(self) -> bool {
+        self == FramebufferObject(0)
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramebufferStatus {
+    Complete = 0x8CD5,
+    Undefined = 0x8219,
+    IncompleteAttachment = 0x8CD6,
+    IncompleteMissingAttachment = 0x8CD7,
+    IncompleteDrawBuffer = 0x8CDB,
+    IncompleteReadBuffer = 0x8CDC,
+    Unsupported = 0x8CDD,
+    IncompleteMultisample = 0x8D56,
+    IncompleteLayerTargets = 0x8DA8,
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramebufferTarget {
+    Framebuffer = 0x8D40,
+    ReadFramebuffer = 0x8CA8,
+    DrawFramebuffer = 0x8CA9,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum GlType {
@@ -286,12 +346,52 @@ pub enum Integer64Name {
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IntegerName {
+    // Version 1.0
+    Viewport = 0x0BA2,
+    ScissorBox = 0x0C10,
+
+    // Version 1.1
+    TextureBinding2d = 0x8069,
+
+    // Version 2.0
+    MaxTextureImageUnits = 0x8872,
+    MaxCombinedTextureImageUnits = 0x8B4D,
+
     // Version 3.0
     MajorVersion = 0x821B,
     MinorVersion = 0x821C,
     NumExtensions = 0x821D,
 }
 
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryBarrierMask {
+    VertexAttribArray = 0x00000001,
+    ElementArray = 0x00000002,
+    Uniform = 0x00000004,
+    TextureFetch = 0x00000008,
+    ShaderImageAccess = 0x00000020,
+    Command = 0x00000040,
+    PixelBuffer = 0x00000080,
+    TextureUpdate = 0x00000100,
+    BufferUpdate = 0x00000200,
+    Framebuffer = 0x00000400,
+    TransformFeedback = 0x00000800,
+    AtomicCounter = 0x00001000,
+    ShaderStorage = 0x00002000,
+    ClientMappedBuffer = 0x00004000,
+    QueryBuffer = 0x00008000,
+    All = 0xFFFFFFFF,
+}
+
+impl BitOr for MemoryBarrierMask {
+    type Output = MemoryBarrierMask;
+
+    fn bitor(self, rhs: MemoryBarrierMask) -> MemoryBarrierMask {
+        unsafe { mem::transmute(self as u32 | rhs as u32) }
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PolygonMode {
@@ -388,6 +488,8 @@ pub enum ServerCapability {
     ColorMaterial = 0x0B57,
     Normalize = 0x0BA1,
     RescaleNormal = 0x803A,
+    PolygonOffsetPoint = 0x2A01,
+    PolygonOffsetLine = 0x2A02,
     PolygonOffsetFill = 0x8037,
     VertexArray = 0x8074,
     NormalArray = 0x8075,
@@ -542,6 +644,7 @@ pub enum TextureFormat {
     Rgba = 0x1908,
     Bgr = 0x80E0,
     Bgra = 0x80E1,
+    DepthComponent = 0x1902,
     // GL_COLOR_INDEX,
     // GL_RED,
     // GL_GREEN,
@@ -558,6 +661,7 @@ impl TextureFormat {
             TextureFormat::Rgba => 4,
             TextureFormat::Bgr => 3,
             TextureFormat::Bgra => 4,
+            TextureFormat::DepthComponent => 1,
         }
     }
 }
@@ -582,10 +686,10 @@ pub enum TextureInternalFormat {
     // GL_COMPRESSED_INTENSITY,
     // GL_COMPRESSED_RGB,
     // GL_COMPRESSED_RGBA,
-    // GL_DEPTH_COMPONENT,
-    // GL_DEPTH_COMPONENT16,
-    // GL_DEPTH_COMPONENT24,
-    // GL_DEPTH_COMPONENT32,
+    DepthComponent = 0x1902,
+    DepthComponent16 = 0x81A5,
+    DepthComponent24 = 0x81A6,
+    DepthComponent32 = 0x81A7,
     // GL_LUMINANCE,
     // GL_LUMINANCE4,
     // GL_LUMINANCE8,
@@ -659,21 +763,36 @@ pub enum TextureParameterName {
     // GL_TEXTURE_SWIZZLE_G,
     // GL_TEXTURE_SWIZZLE_B,
     // GL_TEXTURE_SWIZZLE_A,
-    // GL_TEXTURE_WRAP_S,
-    // GL_TEXTURE_WRAP_T,
-    // GL_TEXTURE_WRAP_R,
+    WrapS = 0x2802,
+    WrapT = 0x2803,
+    WrapR = 0x8072,
 }
 
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TextureParameterTarget {
     Texture2d = 0x0DE1,
+    CubeMap = 0x8513,
     // GL_TEXTURE_1D,
     // GL_TEXTURE_3D,
     // GL_TEXTURE_1D_ARRAY,
     // GL_TEXTURE_2D_ARRAY,
     // GL_TEXTURE_RECTANGLE,
-    // GL_TEXTURE_CUBE_MAP,
+}
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureWrapMode {
+    Repeat = 0x2901,
+    MirroredRepeat = 0x8370,
+    ClampToEdge = 0x812F,
+    ClampToBorder = 0x812D,
+}
+
+impl Into<i32> for TextureWrapMode {
+    fn into(self) -> i32 {
+        self as i32
+    }
 }
 
 #[repr(C)]
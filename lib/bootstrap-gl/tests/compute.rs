@@ -0,0 +1,90 @@
+// Creates a real window and OpenGL 4.3 context, so it only runs on Windows and only when asked
+// for with `cargo test -- --ignored`.
+#![cfg(target_os = "windows")]
+
+extern crate bootstrap_rs as bootstrap;
+extern crate bootstrap_gl as gl;
+
+use bootstrap::window::*;
+use gl::types::*;
+use std::ptr;
+
+static COMPUTE_SOURCE: &'static [u8] = b"
+#version 430
+
+layout(local_size_x = 1) in;
+
+layout(std430, binding = 0) buffer Output {
+    uint values[];
+};
+
+void main() {
+    uint index = gl_GlobalInvocationID.x;
+    values[index] = index * index;
+}
+\0";
+
+const NUM_VALUES: usize = 16;
+
+#[test]
+#[ignore]
+fn dispatch_compute_to_ssbo() {
+    let window = Window::new("bootstrap-gl - dispatch_compute_to_ssbo").unwrap();
+    let device_context = window.platform().device_context();
+    let context = unsafe {
+        let context = gl::create_context(device_context).unwrap();
+        gl::make_current(context);
+        context
+    };
+
+    // Compile and link the compute program.
+    let program = unsafe {
+        let shader = gl::create_shader(ShaderType::Compute);
+        let source_ptr = COMPUTE_SOURCE.as_ptr();
+        gl::shader_source(shader, 1, &source_ptr, ptr::null());
+        gl::compile_shader(shader);
+
+        let mut compile_status = 0;
+        gl::get_shader_param(shader, ShaderParam::CompileStatus, &mut compile_status);
+        assert_eq!(1, compile_status, "Compute shader failed to compile");
+
+        let program = gl::create_program();
+        gl::attach_shader(program, shader);
+        gl::link_program(program);
+
+        let mut link_status = 0;
+        gl::get_program_param(program, ProgramParam::LinkStatus, &mut link_status);
+        assert_eq!(1, link_status, "Compute program failed to link");
+
+        gl::delete_shader(shader);
+        program
+    };
+
+    // Create the SSBO the compute shader writes to, and bind it to binding point 0.
+    let buffer_name = gl::gen_buffer().unwrap();
+    unsafe {
+        gl::bind_buffer(BufferTarget::ShaderStorage, buffer_name);
+        gl::buffer_data(BufferTarget::ShaderStorage, &[0u32; NUM_VALUES][..], BufferUsage::DynamicRead);
+        gl::bind_buffer_base(BufferTarget::ShaderStorage, 0, buffer_name);
+    }
+
+    // Dispatch one work group per value and wait for the writes to become visible.
+    unsafe {
+        gl::use_program(program);
+        gl::dispatch_compute(NUM_VALUES as u32, 1, 1);
+        gl::memory_barrier(MemoryBarrierMask::ShaderStorage | MemoryBarrierMask::BufferUpdate);
+    }
+
+    let mut values = [0u32; NUM_VALUES];
+    gl::get_buffer_data(BufferTarget::ShaderStorage, &mut values);
+    assert_eq!(ErrorCode::NoError, unsafe { gl::get_error() });
+
+    for (index, &value) in values.iter().enumerate() {
+        assert_eq!((index * index) as u32, value);
+    }
+
+    unsafe {
+        gl::delete_program(program);
+        gl::destroy_context(context);
+    }
+}
@@ -0,0 +1,8 @@
+#![cfg(target_os = "linux")]
+
+extern crate bootstrap_gl as gl;
+
+#[test]
+fn load_clear() {
+    assert!(unsafe { gl::platform::load_proc("glClear\0") }.is_some());
+}
@@ -54,11 +54,16 @@
 
 use platform::PlatformId;
 use std::cell::Cell;
+use std::mem;
 
 #[cfg(target_os="windows")]
 #[path="platform\\windows.rs"]
 pub mod platform;
 
+#[cfg(any(target_os="linux", target_os="macos"))]
+#[path="platform/posix.rs"]
+pub mod platform;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FiberId(PlatformId);
 
@@ -79,8 +84,12 @@ unsafe impl Sync for FiberId {}
 /// Represents a fiber with its own stack and thread state.
 ///
 /// The fiber's lifetime is associated with any data it borrows when created.
-// TODO: What do we do about `Drop`? If you drop a `Fiber` it's basically leaking a sizeable chunk
-// of memory. Should we emit a warning or panic?
+///
+/// A `Fiber` handle always refers to a suspended fiber, and dropping the handle deletes the fiber
+/// and frees its stack. Any data still owned by the fiber's stack is leaked rather than dropped.
+/// Note that dropping the handle to a thread's initial fiber (i.e. the fiber created by `init()`)
+/// means the thread's original stack can never be resumed, so those handles should generally be
+/// resumed rather than dropped.
 #[derive(Debug)]
 pub struct Fiber(PlatformId);
 
@@ -130,6 +139,11 @@ impl Fiber {
     /// point another fiber may resume the current one, at which point `resume()` with return,
     /// yielding the fiber that was suspended.
     pub unsafe fn resume(self) -> Fiber {
+        // Once resumed the fiber is no longer owned by this handle, so we forget it to avoid
+        // deleting the fiber we're about to switch to.
+        let fiber = self.0;
+        mem::forget(self);
+
         // Initialize the current thread for fiber usage if we haven't done so already.
         if let None = CURRENT.with(|current| current.get()) {
             init();
@@ -138,14 +152,14 @@ impl Fiber {
         {
             let prev_handle = CURRENT.with(|current| {
                 let prev = current.get();
-                current.set(Some(self.0));
+                current.set(Some(fiber));
                 prev
             });
             PREV.with(|prev| prev.set(prev_handle));
         }
 
-        // Switch to `self`.
-        platform::resume(self.0);
+        // Switch to `fiber`.
+        platform::resume(fiber);
 
         // This is explicitly a different scope than before to avoid cross-contamination. We can't
         // make any assumptions about what was true before and after resuming another fiber, so we
@@ -161,6 +175,41 @@ impl Fiber {
     pub fn id(&self) -> FiberId {
         FiberId(self.0)
     }
+
+    /// Returns the size of the fiber's stack in bytes, as passed to `Fiber::new()`.
+    ///
+    /// Returns `None` if the fiber wasn't created with `Fiber::new()`, e.g. if it's the fiber that
+    /// `init()` created for a thread.
+    pub fn stack_size(&self) -> Option<usize> {
+        platform::stack_size(self.0)
+    }
+
+    /// Estimates the most stack space the fiber has used so far, in bytes.
+    ///
+    /// When the fiber first starts, the unused portion of its stack is filled with a sentinel
+    /// pattern. The high water mark is found by scanning for the deepest byte that has since been
+    /// overwritten, so it's only an estimate: it may over-report by up to a page, and a frame
+    /// that happens to write the sentinel value will be missed. Use it to get a rough sense of
+    /// how large fiber stacks need to be.
+    ///
+    /// Returns `None` if the fiber hasn't started running yet, if its stack was too small to
+    /// fill, or if the fiber wasn't created with `Fiber::new()`.
+    #[cfg(feature = "stack-stats")]
+    pub fn stack_high_water(&self) -> Option<usize> {
+        platform::stack_high_water(self.0)
+    }
+}
+
+impl Drop for Fiber {
+    fn drop(&mut self) {
+        debug_assert!(
+            CURRENT.with(|current| current.get()) != Some(self.0),
+            "Attempted to delete the currently running fiber {:?}",
+            self.0,
+        );
+
+        unsafe { platform::delete_fiber(self.0); }
+    }
 }
 
 // `Fiber` has pointers internally (at least on some platforms) so we need to manually implement
@@ -176,3 +225,138 @@ unsafe impl Sync for Fiber {}
 pub fn current() -> Option<FiberId> {
     CURRENT.with(|current| current.get()).map(|platform_fiber| FiberId(platform_fiber))
 }
+
+/// Suspends the current fiber and resumes the fiber that switched to it.
+///
+/// This behaves like calling `resume()` on the fiber that last resumed the current one, without
+/// needing that fiber's handle. Worker fibers can use this to hand control back to whatever
+/// scheduled them. As with `resume()`, `yield_to_prev()` returns once another fiber resumes the
+/// current one, yielding the fiber that was suspended.
+///
+/// # Panics
+///
+/// - If no fiber has switched to the current fiber on this thread, i.e. if called from the
+///   thread's initial fiber before it has ever been resumed.
+///
+/// # Unsafety
+///
+/// Has all of the same unsafety as `Fiber::resume()`: no `!Send` types may be alive and in scope.
+/// Additionally, the handle to the previous fiber returned by `resume()` (or passed to the fiber
+/// proc) refers to the same fiber that `yield_to_prev()` resumes. That handle must not be resumed
+/// after calling `yield_to_prev()`, since resuming a fiber that is already running is undefined
+/// behavior. It also must not be dropped, since that would delete the fiber out from under any
+/// thread running it, so use `mem::forget()` to discard it instead.
+pub unsafe fn yield_to_prev() -> Fiber {
+    let prev = PREV
+        .with(|prev| prev.get())
+        .expect("No previous fiber to yield to, the current fiber was never resumed");
+    Fiber(prev).resume()
+}
+
+/// A slot that a fiber can use to pass a value back to the fiber that resumes it.
+///
+/// Fiber procs can't return, so `FiberResult` provides a place for the proc to put its output
+/// before its final `resume()`. The resuming fiber creates the `FiberResult`, passes a
+/// `ResultSender` to the fiber proc (e.g. through the same queue that hands the fiber its work),
+/// and reads the value with `take()` once `resume()` returns.
+///
+/// # Examples
+///
+/// ```
+/// use fiber::{Fiber, FiberResult, ResultSender};
+/// use std::cell::Cell;
+///
+/// thread_local! {
+///     static SENDER: Cell<Option<ResultSender<u32>>> = Cell::new(None);
+/// }
+///
+/// fn fiber_proc(suspended: Fiber) -> ! {
+///     let sender = SENDER.with(|sender| sender.get()).unwrap();
+///     unsafe {
+///         sender.send(6 * 7);
+///         suspended.resume();
+///     }
+///
+///     panic!("Uh-oh, shouldn't have resumed this fiber again");
+/// }
+///
+/// let mut result = FiberResult::new();
+/// SENDER.with(|sender| sender.set(Some(result.sender())));
+///
+/// let fiber = Fiber::new(1024, fiber_proc);
+/// unsafe { fiber.resume(); }
+///
+/// assert_eq!(Some(42), result.take());
+/// ```
+#[derive(Debug)]
+pub struct FiberResult<T> {
+    slot: *mut Option<T>,
+}
+
+impl<T> FiberResult<T> {
+    /// Creates a new, empty result slot.
+    pub fn new() -> FiberResult<T> {
+        FiberResult {
+            slot: Box::into_raw(Box::new(None)),
+        }
+    }
+
+    /// Creates a sender that can be used to fill the slot.
+    ///
+    /// The sender doesn't borrow the `FiberResult`, so it's up to the caller to ensure that the
+    /// `FiberResult` outlives any use of the sender. See `ResultSender::send()` for details.
+    pub fn sender(&self) -> ResultSender<T> {
+        ResultSender {
+            slot: self.slot,
+        }
+    }
+
+    /// Takes the value out of the slot, leaving it empty.
+    ///
+    /// Returns `None` if the fiber hasn't sent a value yet.
+    pub fn take(&mut self) -> Option<T> {
+        unsafe { (*self.slot).take() }
+    }
+}
+
+impl<T> Drop for FiberResult<T> {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.slot)); }
+    }
+}
+
+// `FiberResult` owns its slot, so it's as thread-safe as the value it holds.
+unsafe impl<T: Send> Send for FiberResult<T> {}
+unsafe impl<T: Sync> Sync for FiberResult<T> {}
+
+/// The sending half of a `FiberResult`, used by a fiber proc to pass back its output.
+#[derive(Debug)]
+pub struct ResultSender<T> {
+    slot: *mut Option<T>,
+}
+
+impl<T> ResultSender<T> {
+    /// Puts `value` into the result slot, replacing any previous value.
+    ///
+    /// # Unsafety
+    ///
+    /// The `FiberResult` that created this sender must still be alive, and must not be accessed
+    /// while the value is being sent. In practice this means that the value should only be sent
+    /// while the fiber that owns the `FiberResult` is suspended, i.e. before the sending fiber
+    /// makes its final call to `resume()`.
+    pub unsafe fn send(self, value: T) {
+        *self.slot = Some(value);
+    }
+}
+
+impl<T> Clone for ResultSender<T> {
+    fn clone(&self) -> ResultSender<T> {
+        *self
+    }
+}
+
+impl<T> Copy for ResultSender<T> {}
+
+// `ResultSender` is meant to be handed to a fiber that may run on another thread, and sending a
+// value through it is already unsafe, so it's `Send` as long as the value is.
+unsafe impl<T: Send> Send for ResultSender<T> {}
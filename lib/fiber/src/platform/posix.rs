@@ -0,0 +1,269 @@
+//! Fiber support for Linux and macOS, built on top of the `ucontext` functions.
+//!
+//! Each fiber is represented by a heap-allocated `FiberContext` holding the saved register state
+//! and, for fibers created with `create_fiber()`, an `mmap`-ed stack with a guard page at the
+//! bottom.
+
+use ::{Fiber, CURRENT, PREV};
+use std::cmp;
+use std::mem;
+use std::os::raw::{c_int, c_long, c_void};
+#[cfg(target_os = "linux")]
+use std::os::raw::c_ulong;
+use std::ptr;
+
+pub type PlatformId = *mut c_void;
+
+/// The smallest stack that will be allocated for a fiber.
+///
+/// Windows rounds fiber stacks up to its 64 KiB allocation granularity, so we do the same to
+/// give fiber procs the same amount of room on all platforms.
+const MIN_STACK_SIZE: usize = 64 * 1024;
+
+pub fn init() -> PlatformId {
+    // The thread's initial fiber runs on the thread's own stack, so all we need is somewhere to
+    // save its context when another fiber is resumed.
+    let fiber = Box::new(FiberContext {
+        context: unsafe { mem::zeroed() },
+        stack: ptr::null_mut(),
+        mapping_size: 0,
+        func: None,
+        stack_size: None,
+        #[cfg(feature = "stack-stats")]
+        started: false,
+    });
+
+    Box::into_raw(fiber) as PlatformId
+}
+
+pub fn create_fiber(stack_size: usize, func: fn(Fiber) -> !) -> PlatformId
+{
+    unsafe {
+        let page_size = sysconf(SC_PAGESIZE) as usize;
+        let usable_size = round_up(cmp::max(stack_size, MIN_STACK_SIZE), page_size);
+        let mapping_size = usable_size + page_size;
+
+        let stack = mmap(
+            ptr::null_mut(),
+            mapping_size,
+            PROT_READ | PROT_WRITE,
+            MAP_PRIVATE | MAP_ANON,
+            -1,
+            0,
+        );
+
+        // TODO: Return an error result, rather than panicking.
+        if stack == MAP_FAILED {
+            panic!("ERROR: Failed to allocate fiber stack");
+        }
+
+        // Protect the lowest page so that a stack overflow faults instead of silently corrupting
+        // whatever memory is below the stack.
+        if mprotect(stack, page_size, PROT_NONE) != 0 {
+            munmap(stack, mapping_size);
+            panic!("ERROR: Failed to create fiber stack guard page");
+        }
+
+        let stack_bottom = (stack as *mut u8).offset(page_size as isize);
+
+        #[cfg(feature = "stack-stats")]
+        ptr::write_bytes(stack_bottom, STACK_SENTINEL, usable_size);
+
+        let mut fiber = Box::new(FiberContext {
+            context: mem::zeroed(),
+            stack: stack,
+            mapping_size: mapping_size,
+            func: Some(func),
+            stack_size: Some(stack_size),
+            #[cfg(feature = "stack-stats")]
+            started: false,
+        });
+
+        if getcontext(&mut fiber.context) != 0 {
+            munmap(stack, mapping_size);
+            panic!("ERROR: Failed to create fiber");
+        }
+
+        fiber.context.header.uc_link = ptr::null_mut();
+        fiber.context.header.uc_stack.ss_sp = stack_bottom as *mut c_void;
+        fiber.context.header.uc_stack.ss_size = usable_size;
+        fiber.context.header.uc_stack.ss_flags = 0;
+        makecontext(&mut fiber.context, fiber_proc, 0);
+
+        Box::into_raw(fiber) as PlatformId
+    }
+}
+
+/// Makes `fiber` active, saving the current context into the fiber that was running before.
+///
+/// Expects `PREV` to hold the fiber that is currently running, which `Fiber::resume()` takes
+/// care of before calling this.
+pub unsafe fn resume(fiber: PlatformId) {
+    let prev = PREV.with(|prev| prev.get().expect("PREV was None when resuming a fiber"));
+    let prev = prev as *mut FiberContext;
+    let fiber = fiber as *mut FiberContext;
+
+    if swapcontext(&mut (*prev).context, &(*fiber).context) != 0 {
+        panic!("ERROR: Failed to switch to fiber");
+    }
+}
+
+/// Deletes `fiber`, freeing its stack.
+///
+/// `fiber` must not be running on any thread.
+pub unsafe fn delete_fiber(fiber: PlatformId) {
+    let fiber = Box::from_raw(fiber as *mut FiberContext);
+    if !fiber.stack.is_null() {
+        munmap(fiber.stack, fiber.mapping_size);
+    }
+}
+
+/// Returns the stack size that `fiber` was created with.
+pub fn stack_size(fiber: PlatformId) -> Option<usize> {
+    unsafe { (*(fiber as *mut FiberContext)).stack_size }
+}
+
+/// Estimates the peak stack usage of `fiber` in bytes.
+///
+/// Scans up from the bottom of the stack for the first byte that no longer holds the sentinel
+/// value written by `create_fiber()`.
+#[cfg(feature = "stack-stats")]
+pub fn stack_high_water(fiber: PlatformId) -> Option<usize> {
+    let fiber = unsafe { &*(fiber as *mut FiberContext) };
+    if fiber.stack.is_null() || !fiber.started {
+        return None;
+    }
+
+    let stack_bottom = fiber.context.header.uc_stack.ss_sp as usize;
+    let stack_top = stack_bottom + fiber.context.header.uc_stack.ss_size;
+
+    let mut address = stack_bottom;
+    while address < stack_top && unsafe { ptr::read_volatile(address as *const u8) } == STACK_SENTINEL {
+        address += 1;
+    }
+
+    Some(stack_top - address)
+}
+
+/// Value written to the fiber's stack when collecting stack stats.
+#[cfg(feature = "stack-stats")]
+const STACK_SENTINEL: u8 = 0xFD;
+
+struct FiberContext {
+    context: UContext,
+
+    // Base of the stack mapping, including the guard page. Null for fibers created by `init()`.
+    stack: *mut c_void,
+    mapping_size: usize,
+
+    func: Option<fn(Fiber) -> !>,
+    stack_size: Option<usize>,
+
+    #[cfg(feature = "stack-stats")]
+    started: bool,
+}
+
+/// Entry point for fibers created with `create_fiber()`.
+///
+/// `makecontext()` can only portably pass `int` arguments, so rather than passing the fiber in
+/// we look it up in `CURRENT`, which `Fiber::resume()` sets before switching.
+extern "C" fn fiber_proc() {
+    let fiber = CURRENT.with(|current| current.get().expect("CURRENT was None in fiber_proc()"));
+    let fiber = fiber as *mut FiberContext;
+
+    #[cfg(feature = "stack-stats")]
+    unsafe { (*fiber).started = true; }
+
+    let func = unsafe { (*fiber).func.expect("Fiber created without a fiber proc") };
+    let prev_fiber = PREV.with(|prev| prev.get().expect("PREV was None in fiber_proc()"));
+
+    func(Fiber(prev_fiber));
+}
+
+fn round_up(value: usize, multiple: usize) -> usize {
+    (value + multiple - 1) / multiple * multiple
+}
+
+/// The leading fields of `ucontext_t`, the only ones we need to access directly.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[allow(dead_code)]
+struct UContextHeader {
+    uc_flags: c_ulong,
+    uc_link: *mut UContext,
+    uc_stack: StackT,
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct StackT {
+    ss_sp: *mut c_void,
+    ss_flags: c_int,
+    ss_size: usize,
+}
+
+/// The leading fields of `ucontext_t`, the only ones we need to access directly.
+#[cfg(target_os = "macos")]
+#[repr(C)]
+#[allow(dead_code)]
+struct UContextHeader {
+    uc_onstack: c_int,
+    uc_sigmask: u32,
+    uc_stack: StackT,
+    uc_link: *mut UContext,
+    uc_mcsize: usize,
+    uc_mcontext: *mut c_void,
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+struct StackT {
+    ss_sp: *mut c_void,
+    ss_size: usize,
+    ss_flags: c_int,
+}
+
+/// Storage for a `ucontext_t`.
+///
+/// The full layout of `ucontext_t` varies between platforms and libc versions, so we only
+/// declare the fields we use and pad out the rest generously.
+#[repr(C, align(16))]
+struct UContext {
+    header: UContextHeader,
+    _rest: [u8; 8192],
+}
+
+#[cfg(target_os = "linux")]
+const SC_PAGESIZE: c_int = 30;
+#[cfg(target_os = "macos")]
+const SC_PAGESIZE: c_int = 29;
+
+const PROT_NONE: c_int = 0;
+const PROT_READ: c_int = 1;
+const PROT_WRITE: c_int = 2;
+
+const MAP_PRIVATE: c_int = 0x0002;
+#[cfg(target_os = "linux")]
+const MAP_ANON: c_int = 0x0020;
+#[cfg(target_os = "macos")]
+const MAP_ANON: c_int = 0x1000;
+
+const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+extern "C" {
+    fn getcontext(ucp: *mut UContext) -> c_int;
+    fn makecontext(ucp: *mut UContext, func: extern "C" fn(), argc: c_int, ...);
+    fn swapcontext(oucp: *mut UContext, ucp: *const UContext) -> c_int;
+
+    fn sysconf(name: c_int) -> c_long;
+    fn mmap(
+        addr: *mut c_void,
+        len: usize,
+        prot: c_int,
+        flags: c_int,
+        fd: c_int,
+        offset: i64,
+    ) -> *mut c_void;
+    fn mprotect(addr: *mut c_void, len: usize, prot: c_int) -> c_int;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
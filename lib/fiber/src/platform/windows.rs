@@ -2,7 +2,8 @@ extern crate kernel32;
 extern crate winapi;
 
 use ::{Fiber, PREV};
-use std::mem;
+#[cfg(feature = "stack-stats")]
+use std::cmp;
 use std::ptr;
 use self::winapi::*;
 
@@ -20,16 +21,42 @@ pub fn init() -> PlatformId {
 
 pub fn create_fiber(stack_size: usize, func: fn(Fiber) -> !) -> PlatformId
 {
+    let data = Box::into_raw(Box::new(FiberData {
+        func: func,
+        stack_size: stack_size,
+        #[cfg(feature = "stack-stats")]
+        stack_top: 0,
+        #[cfg(feature = "stack-stats")]
+        stack_bottom: 0,
+        #[cfg(feature = "stack-stats")]
+        fill_end: 0,
+    }));
+
+    // When collecting stack stats we commit the whole stack up front so that it can be filled
+    // with the sentinel pattern without tripping the guard page.
+    #[cfg(feature = "stack-stats")]
+    let fiber = unsafe {
+        kernel32::CreateFiberEx(
+            stack_size as SIZE_T,
+            stack_size as SIZE_T,
+            0,
+            Some(fiber_proc),
+            data as LPVOID,
+        )
+    };
+
+    #[cfg(not(feature = "stack-stats"))]
     let fiber = unsafe {
         kernel32::CreateFiber(
             stack_size as u32,
             Some(fiber_proc),
-            func as LPVOID,
+            data as LPVOID,
         )
     };
 
     // TODO: Return an error result, rather than just logging a warning.
     if fiber.is_null() {
+        unsafe { drop(Box::from_raw(data)); }
         panic!("ERROR: Failed to create fiber");
     }
 
@@ -41,9 +68,114 @@ pub unsafe fn resume(fiber: PlatformId) {
     kernel32::SwitchToFiber(fiber);
 }
 
-/// `data` is secretly a pointer to a `Box<Box<FnBox()>>`.
+/// Deletes `fiber`, freeing its stack.
+///
+/// Deleting the currently running fiber terminates the calling thread, so callers must make sure
+/// `fiber` isn't running on any thread.
+pub unsafe fn delete_fiber(fiber: PlatformId) {
+    let data = fiber_data(fiber);
+    kernel32::DeleteFiber(fiber);
+
+    if !data.is_null() {
+        drop(Box::from_raw(data));
+    }
+}
+
+/// Returns the stack size that `fiber` was created with.
+pub fn stack_size(fiber: PlatformId) -> Option<usize> {
+    let data = unsafe { fiber_data(fiber) };
+    if data.is_null() {
+        None
+    } else {
+        Some(unsafe { (*data).stack_size })
+    }
+}
+
+/// Estimates the peak stack usage of `fiber` in bytes.
+///
+/// Scans up from the bottom of the stack for the first byte that no longer holds the sentinel
+/// value written by `fiber_proc()`.
+#[cfg(feature = "stack-stats")]
+pub fn stack_high_water(fiber: PlatformId) -> Option<usize> {
+    let data = unsafe { fiber_data(fiber) };
+    if data.is_null() {
+        return None;
+    }
+
+    let data = unsafe { &*data };
+    if data.stack_bottom >= data.fill_end {
+        // Either the fiber hasn't started yet or its stack was too small to fill.
+        return None;
+    }
+
+    let mut address = data.stack_bottom;
+    while address < data.fill_end && unsafe { ptr::read_volatile(address as *const u8) } == STACK_SENTINEL {
+        address += 1;
+    }
+
+    Some(data.stack_top - address)
+}
+
+/// Per-fiber bookkeeping, stored as the fiber data for fibers created with `create_fiber()`.
+struct FiberData {
+    func: fn(Fiber) -> !,
+    stack_size: usize,
+
+    // Bounds of the region of the stack filled with `STACK_SENTINEL`. These are only set once
+    // the fiber has started running.
+    #[cfg(feature = "stack-stats")]
+    stack_top: usize,
+    #[cfg(feature = "stack-stats")]
+    stack_bottom: usize,
+    #[cfg(feature = "stack-stats")]
+    fill_end: usize,
+}
+
+/// Value written to unused stack space when collecting stack stats.
+#[cfg(feature = "stack-stats")]
+const STACK_SENTINEL: u8 = 0xFD;
+
+/// Space left unfilled at the bottom of the stack to stay clear of the guard page.
+#[cfg(feature = "stack-stats")]
+const STACK_GUARD_SIZE: usize = 2 * 4096;
+
+/// Space left unfilled below `fiber_proc()`'s frame for the frames that are currently running.
+#[cfg(feature = "stack-stats")]
+const STACK_FILL_MARGIN: usize = 4096;
+
+/// Gets the fiber data for `fiber`.
+///
+/// The fiber data pointer is the first field of the fiber's OS structure, which is how
+/// `GetFiberData()` is implemented in winnt.h. This is null for fibers created with `init()`.
+unsafe fn fiber_data(fiber: PlatformId) -> *mut FiberData {
+    *(fiber as *const *mut FiberData)
+}
+
+/// Fills the unused part of the current fiber's stack with `STACK_SENTINEL`.
+#[cfg(feature = "stack-stats")]
+unsafe fn fill_stack(data: *mut FiberData, stack_top: usize) {
+    let stack_bottom = stack_top.saturating_sub((*data).stack_size) + STACK_GUARD_SIZE;
+    let fill_end = cmp::max(stack_bottom, stack_top.saturating_sub(STACK_FILL_MARGIN));
+
+    let mut address = stack_bottom;
+    while address < fill_end {
+        ptr::write_volatile(address as *mut u8, STACK_SENTINEL);
+        address += 1;
+    }
+
+    (*data).stack_top = stack_top;
+    (*data).stack_bottom = stack_bottom;
+    (*data).fill_end = fill_end;
+}
+
+/// `data` is secretly a pointer to the fiber's `FiberData`.
 unsafe extern "system" fn fiber_proc(data: LPVOID) {
-    let func: fn(Fiber) -> ! = mem::transmute(data);
+    let data = data as *mut FiberData;
+
+    #[cfg(feature = "stack-stats")]
+    fill_stack(data, &data as *const _ as usize);
+
+    let func = (*data).func;
     let prev_fiber = PREV.with(|prev| prev.get().expect("PREV was None in fiber_proc()"));
 
     func(Fiber(prev_fiber));
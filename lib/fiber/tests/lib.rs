@@ -17,3 +17,89 @@ fn basic_usage() {
     let prev = unsafe { fiber.resume() };
     assert_eq!(fiber_id, prev.id());
 }
+
+#[test]
+fn yield_to_prev() {
+    // The suspended fiber's handle goes unused, `yield_to_prev()` finds the fiber on its own.
+    fn fiber_proc(_suspended: Fiber) -> ! {
+        unsafe { fiber::yield_to_prev(); }
+
+        panic!("Uh-oh, shouldn't have resumed this fiber again");
+    }
+
+    let main_fiber = fiber::init();
+    let fiber = Fiber::new(1024, fiber_proc);
+    let fiber_id = fiber.id();
+
+    let prev = unsafe { fiber.resume() };
+    assert_eq!(fiber_id, prev.id());
+    assert_eq!(Some(main_fiber), fiber::current());
+}
+
+#[test]
+fn fiber_result() {
+    use fiber::{FiberResult, ResultSender};
+    use std::cell::Cell;
+
+    thread_local! {
+        static SENDER: Cell<Option<ResultSender<String>>> = Cell::new(None);
+    }
+
+    fn fiber_proc(suspended: Fiber) -> ! {
+        let sender = SENDER.with(|sender| sender.get()).unwrap();
+        unsafe {
+            sender.send("Hello from a fiber".into());
+            suspended.resume();
+        }
+
+        panic!("Uh-oh, shouldn't have resumed this fiber again");
+    }
+
+    let mut result = FiberResult::new();
+    assert_eq!(None, result.take());
+    SENDER.with(|sender| sender.set(Some(result.sender())));
+
+    let fiber = Fiber::new(1024, fiber_proc);
+    unsafe { fiber.resume(); }
+
+    assert_eq!(Some("Hello from a fiber".into()), result.take());
+    assert_eq!(None, result.take());
+}
+
+#[test]
+fn stack_size() {
+    fn fiber_proc(suspended: Fiber) -> ! {
+        unsafe { suspended.resume(); }
+
+        panic!("Uh-oh, shouldn't have resumed this fiber again");
+    }
+
+    let fiber = Fiber::new(64 * 1024, fiber_proc);
+    assert_eq!(Some(64 * 1024), fiber.stack_size());
+}
+
+#[test]
+#[cfg(feature = "stack-stats")]
+fn stack_high_water() {
+    fn fiber_proc(suspended: Fiber) -> ! {
+        // Use up some stack space so that there's something to measure. Reading the buffer
+        // through volatile reads stops it from being optimized away.
+        let buffer = [1u8; 16 * 1024];
+        let sum: usize = buffer
+            .iter()
+            .map(|byte| unsafe { ::std::ptr::read_volatile(byte) } as usize)
+            .sum();
+        assert_eq!(16 * 1024, sum);
+        unsafe { suspended.resume(); }
+
+        panic!("Uh-oh, shouldn't have resumed this fiber again");
+    }
+
+    let fiber = Fiber::new(256 * 1024, fiber_proc);
+    assert_eq!(None, fiber.stack_high_water());
+
+    let fiber = unsafe { fiber.resume() };
+    let high_water = fiber.stack_high_water().expect("Fiber should have stack stats after running");
+    assert!(high_water >= 16 * 1024, "High water mark {} was less than the buffer size", high_water);
+    assert!(high_water < 256 * 1024, "High water mark {} was more than the stack size", high_water);
+}
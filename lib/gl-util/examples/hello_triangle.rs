@@ -4,6 +4,7 @@ extern crate gl_util as gl;
 use bootstrap::window::*;
 use gl::*;
 use gl::context::Context;
+use gl::shader::*;
 
 static VERTEX_POSITIONS: [f32; 9] = [
     -1.0, -1.0, 0.0,
@@ -11,6 +12,26 @@ static VERTEX_POSITIONS: [f32; 9] = [
      0.0,  1.0, 0.0,
 ];
 
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+layout(location = 0) in vec4 position;
+
+void main() {
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+out vec4 fragment_color;
+
+void main() {
+    fragment_color = vec4(1, 1, 1, 1);
+}
+"#;
+
 fn main() {
     // Open a window to be used as a target for rendering.
     let mut window = Window::new("Hello, Triangle!").unwrap();
@@ -20,16 +41,23 @@ fn main() {
     let mut context = Context::from_window(&window).unwrap();
 
     // Create the vertex array object, which groups all buffers for a mesh into a single object.
-    let mut vertex_array = VertexArray::new(&context, &VERTEX_POSITIONS[..]);
+    let mut vertex_array = VertexArray::new(&context, &VERTEX_POSITIONS[..]).unwrap();
     vertex_array.set_attrib(
         AttributeLocation::from_index(0),
         AttribLayout { elements: 3, offset: 0, stride: 0 },
     );
 
+    // Compile a minimal shader program that passes the vertex positions through and draws
+    // everything white. A program is required for every draw.
+    let vert_shader = Shader::new(&context, VERT_SOURCE, ShaderType::Vertex).unwrap();
+    let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
+    let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
+
     // `DrawBuilder` is used to specify all of the various configuration options when drawing. In
     // this case we're using `vertex_buffer` in triangles mode, and we're sending its "position"
-    // attribute to attribute location 0, which is the default for `glPosition`.
+    // attribute to attribute location 0, which is where the vertex shader reads `position`.
     let mut draw_builder = DrawBuilder::new(&mut context, &vertex_array, DrawMode::Triangles);
+    draw_builder.program(&program);
 
     'outer: loop {
         while let Some(message) = window.next_message() {
@@ -42,7 +70,7 @@ fn main() {
         // We use the context to clear the render target and swap buffers. `DrawBuilder` can be
         // used multiple to avoid having to re-configure the build for each draw.
         context.clear();
-        draw_builder.draw();
+        draw_builder.draw().unwrap();
         context.swap_buffers();
     }
 }
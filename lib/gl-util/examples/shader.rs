@@ -76,7 +76,7 @@ fn main() {
     let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
     let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
 
-    let mut vertex_array = VertexArray::with_index_buffer(&context, &*vertex_data, &*indices);
+    let mut vertex_array = VertexArray::with_index_buffer(&context, &*vertex_data, &*indices).unwrap();
     vertex_array.set_attrib(
         AttributeLocation::from_index(0),
         AttribLayout { elements: 4, stride: 7, offset: 0 },
@@ -107,7 +107,7 @@ fn main() {
         }
 
         context.clear();
-        draw_builder.draw();
+        draw_builder.draw().unwrap();
         context.swap_buffers();
     }
 }
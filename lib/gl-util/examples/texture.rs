@@ -63,7 +63,7 @@ fn main() {
     let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
     let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
 
-    let mut vertex_array = VertexArray::new(&context, &VERTEX_DATA[..]);
+    let mut vertex_array = VertexArray::new(&context, &VERTEX_DATA[..]).unwrap();
     vertex_array.set_attrib(
         AttributeLocation::from_index(0),
         AttribLayout { elements: 3, offset: 0, stride: 5 },
@@ -104,7 +104,7 @@ fn main() {
         }
 
         context.clear();
-        draw_builder.draw();
+        draw_builder.draw().unwrap();
         context.swap_buffers();
     }
 }
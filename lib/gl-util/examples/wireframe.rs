@@ -5,8 +5,29 @@ extern crate parse_obj;
 use bootstrap::window::*;
 use gl::*;
 use gl::context::Context;
+use gl::shader::*;
 use parse_obj::Obj;
 
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+layout(location = 0) in vec4 position;
+
+void main() {
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+out vec4 fragment_color;
+
+void main() {
+    fragment_color = vec4(1, 1, 1, 1);
+}
+"#;
+
 fn main() {
     // Load mesh file and normalize indices for OpenGL.
     let obj = Obj::from_file("examples/epps_head.obj").unwrap();
@@ -20,12 +41,16 @@ fn main() {
     let mut window = Window::new("gl-util - wireframe example").unwrap();
     let context = Context::from_window(&window).unwrap();
 
-    let mut vertex_array = VertexArray::with_index_buffer(&context, obj.raw_positions(), &*raw_indices);
+    let mut vertex_array = VertexArray::with_index_buffer(&context, obj.raw_positions(), &*raw_indices).unwrap();
     vertex_array.set_attrib(
         AttributeLocation::from_index(0),
         AttribLayout { elements: 4, offset: 0, stride: 0 },
     );
 
+    let vert_shader = Shader::new(&context, VERT_SOURCE, ShaderType::Vertex).unwrap();
+    let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
+    let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
+
     'outer: loop {
         while let Some(message) = window.next_message() {
             match message {
@@ -37,7 +62,9 @@ fn main() {
         context.clear();
         DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
             .polygon_mode(PolygonMode::Line)
-            .draw();
+            .program(&program)
+            .draw()
+            .unwrap();
         context.swap_buffers();
     }
 }
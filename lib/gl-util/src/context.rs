@@ -2,9 +2,9 @@ use bootstrap::window::Window;
 use gl;
 use gl::*;
 use std::cell::RefCell;
-use std::ffi::CStr;
 use std::ptr;
 use std::rc::Rc;
+use texture::{self, TextureUnit};
 
 #[derive(Debug)]
 pub struct Context {
@@ -48,6 +48,10 @@ impl Context {
                 message);
         }
 
+        let mut max_texture_units = 0;
+        let mut viewport = [0; 4];
+        let mut scissor_box = [0; 4];
+
         unsafe {
             let context =
                 gl::create_context(device_context)
@@ -59,10 +63,10 @@ impl Context {
                 gl::enable(ServerCapability::DebugOutput);
                 gl::debug_message_callback(Some(debug_callback), ptr::null_mut());
 
-                let vendor = CStr::from_ptr(gl::get_string(StringName::Vendor)).to_str().unwrap();
-                let renderer = CStr::from_ptr(gl::get_string(StringName::Renderer)).to_str().unwrap();
-                let version = CStr::from_ptr(gl::get_string(StringName::Version)).to_str().unwrap();
-                let glsl_version = CStr::from_ptr(gl::get_string(StringName::ShadingLanguageVersion)).to_str().unwrap();
+                let vendor = gl::get_string(StringName::Vendor).unwrap_or_default();
+                let renderer = gl::get_string(StringName::Renderer).unwrap_or_default();
+                let version = gl::get_string(StringName::Version).unwrap_or_default();
+                let glsl_version = gl::get_string(StringName::ShadingLanguageVersion).unwrap_or_default();
 
                 println!("OpenGL Information:");
                 println!("\tvendor: {}", vendor);
@@ -75,6 +79,10 @@ impl Context {
                 gl::gen_vertex_arrays::load();
                 gl::enable(ServerCapability::FramebufferSrgb);
                 gl::enable(ServerCapability::Blend);
+
+                gl::get_integers(IntegerName::MaxTextureImageUnits, &mut max_texture_units);
+                gl::get_integers(IntegerName::Viewport, viewport.as_mut_ptr());
+                gl::get_integers(IntegerName::ScissorBox, scissor_box.as_mut_ptr());
             }
 
             let inner = Rc::new(RefCell::new(ContextInner {
@@ -84,15 +92,30 @@ impl Context {
                 server_cull_enabled: false,
                 server_depth_test_enabled: false,
                 server_blend_enabled: true,
+                server_polygon_offset_enabled: false,
+                server_scissor_enabled: false,
 
                 bound_vertex_array: None,
+                bound_framebuffer: FramebufferObject::null(),
                 front_polygon_mode: PolygonMode::default(),
                 back_polygon_mode: PolygonMode::default(),
                 program: None,
                 cull_mode: Face::default(),
                 winding_order: WindingOrder::default(),
                 depth_test: Comparison::Less,
+                depth_write: true,
                 blend: Default::default(),
+                blend_equation: BlendEquation::default(),
+                polygon_offset: (0.0, 0.0),
+                clear_color: [0.0, 0.0, 0.0, 0.0],
+                viewport: Rect::from_array(viewport),
+                scissor_box: Rect::from_array(scissor_box),
+
+                active_texture: TextureUnit::default(),
+                bound_textures: vec![
+                    (TextureBindTarget::Texture2d, TextureObject::null());
+                    max_texture_units as usize
+                ],
             }));
 
             Ok(Context {
@@ -105,14 +128,74 @@ impl Context {
     /// TODO: Take clear mask (and values) as parameters.
     pub fn clear(&self) {
         let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().depth_write(true);
         unsafe { gl::clear(ClearBufferMask::Color | ClearBufferMask::Depth); }
     }
 
+    /// Sets the color that `clear()` clears the color buffer to.
+    ///
+    /// The color is given as `[red, green, blue, alpha]`. The initial clear color is
+    /// `[0.0, 0.0, 0.0, 0.0]`.
+    pub fn set_clear_color(&self, color: [f32; 4]) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().set_clear_color(color);
+    }
+
+    /// Gets the color that `clear()` clears the color buffer to.
+    pub fn clear_color(&self) -> [f32; 4] {
+        self.inner.borrow().clear_color
+    }
+
+    /// Clears the depth buffer without clearing the color buffer.
+    ///
+    /// Like `clear()`, only the region inside the current scissor box is cleared.
+    pub fn clear_depth(&self) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().depth_write(true);
+        unsafe { gl::clear(ClearBufferMask::Depth); }
+    }
+
     pub fn swap_buffers(&self) {
         let _guard = ::context::ContextGuard::new(self.raw);
         unsafe { gl::platform::swap_buffers(self.raw); }
     }
 
+    /// Gets the number of texture units that can be used in a single draw call.
+    pub fn max_texture_units(&self) -> usize {
+        self.inner.borrow().max_texture_units()
+    }
+
+    /// Sets the viewport, in window coordinates, that draws are mapped to.
+    ///
+    /// The context tracks the current viewport, so setting the viewport it already has doesn't
+    /// make any OpenGL calls. This makes it safe for multiple subsystems to set the viewport they
+    /// need before drawing.
+    pub fn set_viewport(&self, viewport: Rect) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().set_viewport(viewport);
+    }
+
+    /// Gets the current viewport.
+    ///
+    /// Initially the viewport covers the whole window the context was created for.
+    pub fn viewport(&self) -> Rect {
+        self.inner.borrow().viewport
+    }
+
+    /// Sets the scissor box, in window coordinates, that draws are clipped to.
+    ///
+    /// `None` disables the scissor test, allowing draws to affect the whole window. As with
+    /// `set_viewport()`, OpenGL is only called if the scissor state actually changes.
+    pub fn set_scissor(&self, scissor: Option<Rect>) {
+        let _guard = ::context::ContextGuard::new(self.raw);
+        self.inner.borrow_mut().set_scissor(scissor);
+    }
+
+    /// Gets the current scissor box, or `None` if the scissor test is disabled.
+    pub fn scissor(&self) -> Option<Rect> {
+        self.inner.borrow().scissor()
+    }
+
     pub(crate) fn raw(&self) -> gl::Context {
         self.raw
     }
@@ -130,15 +213,31 @@ pub(crate) struct ContextInner {
     server_cull_enabled: bool,
     server_depth_test_enabled: bool,
     server_blend_enabled: bool,
+    server_polygon_offset_enabled: bool,
+    server_scissor_enabled: bool,
 
     bound_vertex_array: Option<VertexArrayName>,
+    bound_framebuffer: FramebufferObject,
     front_polygon_mode: PolygonMode,
     back_polygon_mode: PolygonMode,
     program: Option<ProgramObject>,
     cull_mode: Face,
     winding_order: WindingOrder,
     depth_test: Comparison,
-    blend: (SourceFactor, DestFactor),
+    depth_write: bool,
+    blend: (SourceFactor, DestFactor, SourceFactor, DestFactor),
+    blend_equation: BlendEquation,
+    polygon_offset: (f32, f32),
+    clear_color: [f32; 4],
+    viewport: Rect,
+    scissor_box: Rect,
+
+    active_texture: TextureUnit,
+
+    /// The texture currently bound to each texture unit, along with the target it's bound to.
+    ///
+    /// The length of `bound_textures` is the number of texture units supported by the context.
+    bound_textures: Vec<(TextureBindTarget, TextureObject)>,
 }
 
 impl ContextInner {
@@ -146,6 +245,71 @@ impl ContextInner {
         self.raw
     }
 
+    pub(crate) fn max_texture_units(&self) -> usize {
+        self.bound_textures.len()
+    }
+
+    pub(crate) fn active_texture(&self) -> TextureUnit {
+        self.active_texture
+    }
+
+    /// Binds the 2D texture `texture` to the specified texture unit.
+    ///
+    /// Does nothing if `texture` is already bound to `unit`, which avoids redundant state changes
+    /// when consecutive draws use the same textures.
+    ///
+    /// # Panics
+    ///
+    /// - If `unit` is not a valid texture unit for the context.
+    pub(crate) fn bind_texture(&mut self, unit: TextureUnit, texture: TextureObject) {
+        self.bind_texture_target(unit, TextureBindTarget::Texture2d, texture);
+    }
+
+    /// Binds `texture` to `target` on the specified texture unit.
+    ///
+    /// Only the most recent binding for each unit is tracked, so binding a texture to a different
+    /// target on the same unit always calls OpenGL.
+    ///
+    /// # Panics
+    ///
+    /// - If `unit` is not a valid texture unit for the context.
+    pub(crate) fn bind_texture_target(
+        &mut self,
+        unit: TextureUnit,
+        target: TextureBindTarget,
+        texture: TextureObject,
+    ) {
+        let index = unit.index() as usize;
+        assert!(
+            index < self.bound_textures.len(),
+            "Texture unit {} is out of range, context only has {} texture units",
+            index,
+            self.bound_textures.len(),
+        );
+
+        if self.bound_textures[index] != (target, texture) {
+            if self.active_texture != unit {
+                unsafe { texture::set_active_texture(unit); }
+                self.active_texture = unit;
+            }
+
+            unsafe { gl::bind_texture(target, texture); }
+            self.bound_textures[index] = (target, texture);
+        }
+    }
+
+    /// Clears any bindings for `texture`.
+    ///
+    /// Should be called when a texture object is deleted, since OpenGL implicitly unbinds deleted
+    /// textures.
+    pub(crate) fn forget_texture(&mut self, texture: TextureObject) {
+        for &mut (_, ref mut bound) in &mut self.bound_textures {
+            if *bound == texture {
+                *bound = TextureObject::null();
+            }
+        }
+    }
+
     pub(crate) fn bind_vertex_array(&mut self, vertex_array_name: VertexArrayName) {
         if Some(vertex_array_name) != self.bound_vertex_array {
             unsafe { gl::bind_vertex_array(vertex_array_name); }
@@ -160,6 +324,29 @@ impl ContextInner {
         }
     }
 
+    /// Binds `framebuffer` as the target for draws, `FramebufferObject::null()` binds the default
+    /// framebuffer.
+    pub(crate) fn bind_framebuffer(&mut self, framebuffer: FramebufferObject) {
+        if framebuffer != self.bound_framebuffer {
+            unsafe { gl::bind_framebuffer(FramebufferTarget::Framebuffer, framebuffer); }
+            self.bound_framebuffer = framebuffer;
+        }
+    }
+
+    /// Clears the binding for `framebuffer` if it's bound.
+    ///
+    /// Should be called when a framebuffer object is deleted, since OpenGL reverts to the default
+    /// framebuffer when the bound framebuffer is deleted.
+    pub(crate) fn forget_framebuffer(&mut self, framebuffer: FramebufferObject) {
+        if framebuffer == self.bound_framebuffer {
+            self.bound_framebuffer = FramebufferObject::null();
+        }
+    }
+
+    pub(crate) fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
     pub(crate) fn polygon_mode(&mut self, mode: PolygonMode) {
         if mode != self.front_polygon_mode || mode != self.back_polygon_mode {
             unsafe { gl::polygon_mode(Face::FrontAndBack, mode); }
@@ -199,6 +386,23 @@ impl ContextInner {
         }
     }
 
+    /// Enables or disables polygon offset for both filled polygons and lines.
+    pub(crate) fn enable_server_polygon_offset(&mut self, enabled: bool) {
+        if enabled != self.server_polygon_offset_enabled {
+            match enabled {
+                true => unsafe {
+                    gl::enable(ServerCapability::PolygonOffsetFill);
+                    gl::enable(ServerCapability::PolygonOffsetLine);
+                },
+                false => unsafe {
+                    gl::disable(ServerCapability::PolygonOffsetFill);
+                    gl::disable(ServerCapability::PolygonOffsetLine);
+                },
+            }
+            self.server_polygon_offset_enabled = enabled;
+        }
+    }
+
     pub(crate) fn cull_mode(&mut self, face: Face) {
         if self.cull_mode != face {
             unsafe { gl::cull_face(face); }
@@ -220,10 +424,78 @@ impl ContextInner {
         }
     }
 
-    pub(crate) fn blend(&mut self, source_factor: SourceFactor, dest_factor: DestFactor) {
-        if (source_factor, dest_factor) != self.blend {
-            unsafe { gl::blend_func(source_factor, dest_factor); }
-            self.blend = (source_factor, dest_factor);
+    pub(crate) fn depth_write(&mut self, enabled: bool) {
+        if enabled != self.depth_write {
+            unsafe { gl::depth_mask(enabled.into()); }
+            self.depth_write = enabled;
+        }
+    }
+
+    pub(crate) fn polygon_offset(&mut self, factor: f32, units: f32) {
+        if (factor, units) != self.polygon_offset {
+            unsafe { gl::polygon_offset(factor, units); }
+            self.polygon_offset = (factor, units);
+        }
+    }
+
+    pub(crate) fn set_clear_color(&mut self, color: [f32; 4]) {
+        if color != self.clear_color {
+            unsafe { gl::clear_color(color[0], color[1], color[2], color[3]); }
+            self.clear_color = color;
+        }
+    }
+
+    pub(crate) fn set_viewport(&mut self, viewport: Rect) {
+        if viewport != self.viewport {
+            unsafe { gl::viewport(viewport.x, viewport.y, viewport.width, viewport.height); }
+            self.viewport = viewport;
+        }
+    }
+
+    pub(crate) fn set_scissor(&mut self, scissor: Option<Rect>) {
+        if let Some(scissor_box) = scissor {
+            if scissor_box != self.scissor_box {
+                unsafe { gl::scissor(scissor_box.x, scissor_box.y, scissor_box.width, scissor_box.height); }
+                self.scissor_box = scissor_box;
+            }
+        }
+
+        let enabled = scissor.is_some();
+        if enabled != self.server_scissor_enabled {
+            match enabled {
+                true => unsafe { gl::enable(ServerCapability::ScissorTest); },
+                false => unsafe { gl::disable(ServerCapability::ScissorTest); },
+            }
+            self.server_scissor_enabled = enabled;
+        }
+    }
+
+    pub(crate) fn scissor(&self) -> Option<Rect> {
+        if self.server_scissor_enabled {
+            Some(self.scissor_box)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn blend(
+        &mut self,
+        source_rgb: SourceFactor,
+        dest_rgb: DestFactor,
+        source_alpha: SourceFactor,
+        dest_alpha: DestFactor,
+    ) {
+        let blend = (source_rgb, dest_rgb, source_alpha, dest_alpha);
+        if blend != self.blend {
+            unsafe { gl::blend_func_separate(source_rgb, dest_rgb, source_alpha, dest_alpha); }
+            self.blend = blend;
+        }
+    }
+
+    pub(crate) fn blend_equation(&mut self, equation: BlendEquation) {
+        if equation != self.blend_equation {
+            unsafe { gl::blend_equation(equation); }
+            self.blend_equation = equation;
         }
     }
 }
@@ -252,6 +524,32 @@ pub enum Error {
     UnableToCreateRenderContext,
 }
 
+/// A rectangle in window coordinates, used for the viewport and scissor box.
+///
+/// `x` and `y` are the lower left corner of the rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    fn from_array(values: [i32; 4]) -> Rect {
+        Rect::new(values[0], values[1], values[2], values[3])
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct ContextGuard(gl::Context);
 
@@ -268,3 +566,200 @@ impl Drop for ContextGuard {
         unsafe { gl::make_current(self.0); }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {AttribLayout, AttributeLocation, DrawBuilder, DrawError, DrawMode, VertexArray};
+    use bootstrap::window::Window;
+    use gl::{self, IntegerName, TextureBindTarget, TextureObject};
+    use shader::{Program, Shader, ShaderType};
+    use super::*;
+    use texture::{Texture2d, TextureFormat, TextureInternalFormat};
+
+    static VERTEX_POSITIONS: [f32; 9] = [
+        -1.0, -1.0, 0.0,
+         1.0, -1.0, 0.0,
+         0.0,  1.0, 0.0,
+    ];
+
+    static VERT_SOURCE: &'static str = r#"
+        #version 330 core
+
+        layout(location = 0) in vec4 position;
+
+        void main() {
+            gl_Position = position;
+        }
+    "#;
+
+    static THREE_TEXTURE_FRAG_SOURCE: &'static str = r#"
+        #version 330 core
+
+        uniform sampler2D texture_a;
+        uniform sampler2D texture_b;
+        uniform sampler2D texture_c;
+
+        out vec4 fragment_color;
+
+        void main() {
+            vec2 uv = vec2(0.5, 0.5);
+            fragment_color = texture(texture_a, uv) + texture(texture_b, uv) + texture(texture_c, uv);
+        }
+    "#;
+
+    /// Reads the viewport directly from OpenGL, bypassing the context's cached state.
+    fn gl_viewport(context: &Context) -> Rect {
+        let _guard = ContextGuard::new(context.raw());
+        let mut viewport = [0; 4];
+        unsafe { gl::get_integers(IntegerName::Viewport, viewport.as_mut_ptr()); }
+        Rect::from_array(viewport)
+    }
+
+    /// Reads the 2D texture bound to `unit` directly from OpenGL, bypassing the context's cached
+    /// state.
+    fn gl_texture_binding(context: &Context, unit: TextureUnit) -> TextureObject {
+        let _guard = ContextGuard::new(context.raw());
+        let active_texture = context.inner().borrow().active_texture();
+
+        let mut binding = 0;
+        unsafe {
+            texture::set_active_texture(unit);
+            gl::get_integers(IntegerName::TextureBinding2d, &mut binding);
+            texture::set_active_texture(active_texture);
+        }
+
+        TextureObject(binding as u32)
+    }
+
+    fn white_texture(context: &Context) -> Texture2d {
+        Texture2d::new(
+            context,
+            TextureFormat::Rgba,
+            TextureInternalFormat::Rgba,
+            1,
+            1,
+            &[255u8, 255, 255, 255])
+            .unwrap()
+    }
+
+    fn three_texture_program(context: &Context) -> Program {
+        let vert_shader = Shader::new(context, VERT_SOURCE, ShaderType::Vertex).unwrap();
+        let frag_shader = Shader::new(context, THREE_TEXTURE_FRAG_SOURCE, ShaderType::Fragment).unwrap();
+        Program::new(context, &[vert_shader, frag_shader]).unwrap()
+    }
+
+    fn triangle(context: &Context) -> VertexArray {
+        let mut vertex_array = VertexArray::new(context, &VERTEX_POSITIONS[..]).unwrap();
+        vertex_array.set_attrib(
+            AttributeLocation::from_index(0),
+            AttribLayout { elements: 3, offset: 0, stride: 0 },
+        );
+        vertex_array
+    }
+
+    #[test]
+    fn draw_with_three_textures() {
+        let window = Window::new("gl-util - draw_with_three_textures").unwrap();
+        let context = Context::from_window(&window).unwrap();
+        let vertex_array = triangle(&context);
+        let program = three_texture_program(&context);
+        let textures = [white_texture(&context), white_texture(&context), white_texture(&context)];
+
+        let mut draw_builder = DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles);
+        draw_builder
+            .program(&program)
+            .uniform("texture_a", &textures[0])
+            .uniform("texture_b", &textures[1])
+            .uniform("texture_c", &textures[2]);
+        draw_builder.draw().unwrap();
+
+        // Each texture is bound to its own unit, starting at unit 0.
+        let bound: Vec<TextureObject> = (0..3)
+            .map(|index| context.inner().borrow().bound_textures[index].1)
+            .collect();
+        for texture in &textures {
+            assert!(bound.contains(&texture.inner()));
+        }
+        for (index, &texture) in bound.iter().enumerate() {
+            assert_eq!(texture, gl_texture_binding(&context, TextureUnit::new(index as u32)));
+        }
+
+        // Unbind unit 0 behind the context's back. The textures are still bound as far as the
+        // binding cache knows, so drawing with them again shouldn't touch the bindings.
+        {
+            let _guard = ContextGuard::new(context.raw());
+            let active_texture = context.inner().borrow().active_texture();
+            unsafe {
+                texture::set_active_texture(TextureUnit::new(0));
+                gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
+                texture::set_active_texture(active_texture);
+            }
+        }
+        draw_builder.draw().unwrap();
+        assert_eq!(TextureObject::null(), gl_texture_binding(&context, TextureUnit::new(0)));
+    }
+
+    #[test]
+    fn draw_with_too_many_textures() {
+        let window = Window::new("gl-util - draw_with_too_many_textures").unwrap();
+        let context = Context::from_window(&window).unwrap();
+        let vertex_array = triangle(&context);
+        let program = three_texture_program(&context);
+        let textures = [white_texture(&context), white_texture(&context), white_texture(&context)];
+
+        // Pretend the context only has two texture units.
+        context.inner().borrow_mut().bound_textures.truncate(2);
+
+        let result = DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+            .program(&program)
+            .uniform("texture_a", &textures[0])
+            .uniform("texture_b", &textures[1])
+            .uniform("texture_c", &textures[2])
+            .draw();
+        assert_eq!(Err(DrawError::TooManyTextures { textures: 3, max_texture_units: 2 }), result);
+    }
+
+    #[test]
+    fn viewport_round_trip() {
+        let window = Window::new("gl-util - viewport_round_trip").unwrap();
+        let context = Context::from_window(&window).unwrap();
+
+        // The initial viewport matches the actual GL state.
+        assert_eq!(gl_viewport(&context), context.viewport());
+
+        let viewport = Rect::new(10, 20, 100, 50);
+        context.set_viewport(viewport);
+        assert_eq!(viewport, context.viewport());
+        assert_eq!(viewport, gl_viewport(&context));
+
+        // Change the GL viewport behind the context's back. Setting the cached viewport again is
+        // redundant, so it shouldn't touch GL and the out-of-band viewport remains.
+        {
+            let _guard = ContextGuard::new(context.raw());
+            unsafe { gl::viewport(0, 0, 1, 1); }
+        }
+        context.set_viewport(viewport);
+        assert_eq!(Rect::new(0, 0, 1, 1), gl_viewport(&context));
+
+        // A different viewport is always applied.
+        let other = Rect::new(0, 0, 64, 64);
+        context.set_viewport(other);
+        assert_eq!(other, context.viewport());
+        assert_eq!(other, gl_viewport(&context));
+    }
+
+    #[test]
+    fn scissor_round_trip() {
+        let window = Window::new("gl-util - scissor_round_trip").unwrap();
+        let context = Context::from_window(&window).unwrap();
+
+        assert_eq!(None, context.scissor());
+
+        let scissor = Rect::new(5, 5, 20, 20);
+        context.set_scissor(Some(scissor));
+        assert_eq!(Some(scissor), context.scissor());
+
+        context.set_scissor(None);
+        assert_eq!(None, context.scissor());
+    }
+}
@@ -0,0 +1,155 @@
+use context::{Context, ContextInner, Rect};
+use gl::{self, ClearBufferMask};
+use std::cell::RefCell;
+use std::rc::Rc;
+use texture::{self, Texture2d, TextureFormat, TextureInternalFormat};
+
+pub use gl::{FramebufferAttachment, FramebufferObject, FramebufferStatus, FramebufferTarget};
+
+/// An off-screen render target with color and depth textures.
+///
+/// Draws are directed to a framebuffer with `DrawBuilder::target()`. Once the scene has been
+/// drawn the color texture can be sampled like any other texture, e.g. to apply post-processing
+/// in a second pass, and the depth texture can be used for shadow mapping.
+#[derive(Debug)]
+pub struct Framebuffer {
+    framebuffer_object: FramebufferObject,
+    color: Texture2d,
+    depth: Texture2d,
+
+    context: Rc<RefCell<ContextInner>>,
+}
+
+impl Framebuffer {
+    /// Creates a new framebuffer with `width` by `height` color and depth attachments.
+    ///
+    /// The color attachment is an RGBA texture with 8 bits per channel, and the depth attachment
+    /// is a 24 bit depth texture. The contents of both are undefined until the framebuffer is
+    /// cleared or drawn to.
+    pub fn new(context: &Context, width: usize, height: usize) -> Result<Framebuffer, Error> {
+        let color = Texture2d::uninitialized::<u8>(
+            context,
+            TextureFormat::Rgba,
+            TextureInternalFormat::Rgba,
+            width,
+            height,
+        )?;
+        let depth = Texture2d::uninitialized::<f32>(
+            context,
+            TextureFormat::DepthComponent,
+            TextureInternalFormat::DepthComponent24,
+            width,
+            height,
+        )?;
+
+        let context_inner = context.inner();
+        let mut context = context_inner.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let framebuffer_object = gl::gen_framebuffer().ok_or(Error::FailedToGenerateFramebuffer)?;
+
+        context.bind_framebuffer(framebuffer_object);
+        let status = unsafe {
+            gl::framebuffer_texture_2d(
+                FramebufferTarget::Framebuffer,
+                FramebufferAttachment::Color0,
+                texture::Texture2dTarget::Texture2d,
+                color.inner(),
+                0);
+            gl::framebuffer_texture_2d(
+                FramebufferTarget::Framebuffer,
+                FramebufferAttachment::Depth,
+                texture::Texture2dTarget::Texture2d,
+                depth.inner(),
+                0);
+
+            gl::check_framebuffer_status(FramebufferTarget::Framebuffer)
+        };
+        context.bind_framebuffer(FramebufferObject::null());
+
+        if status != FramebufferStatus::Complete {
+            unsafe { gl::delete_framebuffers(1, &framebuffer_object); }
+            return Err(Error::Incomplete(status));
+        }
+
+        Ok(Framebuffer {
+            framebuffer_object: framebuffer_object,
+            color: color,
+            depth: depth,
+
+            context: context_inner.clone(),
+        })
+    }
+
+    /// Clears the color and depth attachments of the framebuffer.
+    ///
+    /// The color attachment is cleared to the context's clear color. As with `Context::clear()`,
+    /// only the region inside the current scissor box is cleared.
+    pub fn clear(&self) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        context.bind_framebuffer(self.framebuffer_object);
+        context.depth_write(true);
+        unsafe { gl::clear(ClearBufferMask::Color | ClearBufferMask::Depth); }
+        context.bind_framebuffer(FramebufferObject::null());
+    }
+
+    /// Gets the texture that color output is written to.
+    pub fn color(&self) -> &Texture2d {
+        &self.color
+    }
+
+    /// Gets the texture that depth values are written to.
+    pub fn depth(&self) -> &Texture2d {
+        &self.depth
+    }
+
+    /// Returns the width of the framebuffer in pixels.
+    pub fn width(&self) -> usize {
+        self.color.width()
+    }
+
+    /// Returns the height of the framebuffer in pixels.
+    pub fn height(&self) -> usize {
+        self.color.height()
+    }
+
+    /// Returns a viewport covering the whole framebuffer.
+    pub(crate) fn viewport(&self) -> Rect {
+        Rect::new(0, 0, self.width() as i32, self.height() as i32)
+    }
+
+    /// Returns the OpenGL primitive managed by this object.
+    pub(crate) fn inner(&self) -> FramebufferObject {
+        self.framebuffer_object
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+        unsafe { gl::delete_framebuffers(1, &self.framebuffer_object); }
+
+        context.forget_framebuffer(self.framebuffer_object);
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Indicates that OpenGL failed to generate a framebuffer object.
+    FailedToGenerateFramebuffer,
+
+    /// Indicates that one of the framebuffer's attachments couldn't be created.
+    Texture(texture::Error),
+
+    /// Indicates that the framebuffer wasn't complete after its attachments were attached.
+    Incomplete(FramebufferStatus),
+}
+
+impl From<texture::Error> for Error {
+    fn from(from: texture::Error) -> Error {
+        Error::Texture(from)
+    }
+}
@@ -12,20 +12,23 @@
 extern crate bootstrap_rs as bootstrap;
 extern crate bootstrap_gl as gl;
 
-use context::{Context, ContextInner};
+use context::{Context, ContextInner, Rect};
+use framebuffer::Framebuffer;
 use gl::*;
 use shader::Program;
 use std::mem;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use texture::Texture2d;
+use texture::{Texture2d, TextureCubeMap, TextureUnit};
 
 pub use gl::{
     AttributeLocation,
+    BlendEquation,
     Comparison,
     DestFactor,
     DrawMode,
+    ErrorCode,
     Face,
     PolygonMode,
     ShaderType,
@@ -34,6 +37,7 @@ pub use gl::{
 };
 
 pub mod context;
+pub mod framebuffer;
 pub mod shader;
 pub mod texture;
 
@@ -71,6 +75,9 @@ pub struct VertexArray {
     vertex_buffer_name: BufferName,
     index_buffer: Option<IndexBuffer>,
 
+    /// Buffers holding per-instance attribute data, see `set_instance_attrib()`.
+    instance_buffer_names: Vec<BufferName>,
+
     /// The number of primitive elments in the buffer.
     ///
     /// Does not reflect the number of vertices in the buffer.
@@ -86,17 +93,36 @@ pub struct VertexArray {
 
 impl VertexArray {
     /// Creates a new VAO and vertex buffer, filling the buffer with the provided data.
-    // TODO: Is this operation fallible? If so it should return a `Result<T>`.
-    pub fn new(context: &Context, vertex_data: &[f32]) -> VertexArray {
+    ///
+    /// # Errors
+    ///
+    /// - `VertexArrayError::FailedToGenerateVertexArray` or
+    ///   `VertexArrayError::FailedToGenerateBuffer` if OpenGL couldn't create the objects.
+    /// - `VertexArrayError::Gl` if OpenGL reported an error while filling the buffer, e.g.
+    ///   `ErrorCode::OutOfMemory`.
+    pub fn new(context: &Context, vertex_data: &[f32]) -> ::std::result::Result<VertexArray, VertexArrayError> {
         let context_inner = context.inner();
 
         let (vertex_buffer_name, vertex_array_name) = unsafe {
             let mut context = context_inner.borrow_mut();
             let _guard = ::context::ContextGuard::new(context.raw());
 
+            // Clear any errors left over from earlier operations so that they aren't reported as
+            // coming from this one.
+            take_gl_error();
+
             // Create the VAO and VBO.
-            let vertex_array = gl::gen_vertex_array().expect("Failed to create vertex array object");
-            let buffer_name = gl::gen_buffer().expect("Failed to create buffer object");
+            let vertex_array = match gl::gen_vertex_array() {
+                Some(vertex_array) => vertex_array,
+                None => return Err(VertexArrayError::FailedToGenerateVertexArray),
+            };
+            let buffer_name = match gl::gen_buffer() {
+                Some(buffer_name) => buffer_name,
+                None => {
+                    gl::delete_vertex_arrays(1, &vertex_array);
+                    return Err(VertexArrayError::FailedToGenerateBuffer);
+                },
+            };
 
             // Bind the VAO to the context, then bind the buffer to the VAO.
             context.bind_vertex_array(vertex_array);
@@ -109,46 +135,96 @@ impl VertexArray {
                 BufferUsage::StaticDraw,
             );
 
+            if let Some(error) = take_gl_error() {
+                gl::delete_buffers(1, &buffer_name);
+                gl::delete_vertex_arrays(1, &vertex_array);
+                context.unbind_vertex_array(vertex_array);
+                return Err(VertexArrayError::Gl(error));
+            }
+
             (buffer_name, vertex_array)
         };
 
-        VertexArray {
+        Ok(VertexArray {
             vertex_array_name: vertex_array_name,
             vertex_buffer_name: vertex_buffer_name,
             index_buffer: None,
+            instance_buffer_names: Vec::new(),
 
             vertex_primitive_len: vertex_data.len(),
             elements_per_vertex: 0,
 
             context: context_inner,
-        }
+        })
     }
 
     /// Creates a new VAO with the provided vertex and index data.
-    pub fn with_index_buffer(context: &Context, vertex_data: &[f32], index_data: &[u32]) -> VertexArray {
-        let mut vertex_array = VertexArray::new(context, vertex_data);
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same cases as `new()`, and also if the index buffer couldn't be created.
+    pub fn with_index_buffer(
+        context: &Context,
+        vertex_data: &[f32],
+        index_data: &[u32],
+    ) -> ::std::result::Result<VertexArray, VertexArrayError> {
+        VertexArray::with_typed_index_buffer(context, vertex_data, index_data, IndexType::UnsignedInt)
+    }
+
+    /// Creates a new VAO with the provided vertex data and 16 bit index data.
+    ///
+    /// Prefer this over `with_index_buffer()` for meshes with fewer than 65536 vertices, since the
+    /// index buffer takes half as much memory.
+    pub fn with_index_buffer_u16(
+        context: &Context,
+        vertex_data: &[f32],
+        index_data: &[u16],
+    ) -> ::std::result::Result<VertexArray, VertexArrayError> {
+        VertexArray::with_typed_index_buffer(context, vertex_data, index_data, IndexType::UnsignedShort)
+    }
+
+    fn with_typed_index_buffer<T>(
+        context: &Context,
+        vertex_data: &[f32],
+        index_data: &[T],
+        index_type: IndexType,
+    ) -> ::std::result::Result<VertexArray, VertexArrayError> {
+        let mut vertex_array = VertexArray::new(context, vertex_data)?;
 
+        // The vertex array is dropped if creating the index buffer fails, so the context must not
+        // be borrowed when returning an error.
         let index_buffer_name = unsafe {
             let context = vertex_array.context.borrow_mut();
             let _guard = ::context::ContextGuard::new(context.raw());
 
-            let buffer_name = gl::gen_buffer().expect("Failed to generate buffer object");
-            gl::bind_buffer(BufferTarget::ElementArray, buffer_name);
-            gl::buffer_data(
-                BufferTarget::ElementArray,
-                index_data,
-                BufferUsage::StaticDraw,
-            );
-
-            buffer_name
-        };
+            match gl::gen_buffer() {
+                Some(buffer_name) => {
+                    gl::bind_buffer(BufferTarget::ElementArray, buffer_name);
+                    gl::buffer_data(
+                        BufferTarget::ElementArray,
+                        index_data,
+                        BufferUsage::StaticDraw,
+                    );
+
+                    match take_gl_error() {
+                        Some(error) => {
+                            gl::delete_buffers(1, &buffer_name);
+                            Err(VertexArrayError::Gl(error))
+                        },
+                        None => Ok(buffer_name),
+                    }
+                },
+                None => Err(VertexArrayError::FailedToGenerateBuffer),
+            }
+        }?;
 
         vertex_array.index_buffer = Some(IndexBuffer {
             name: index_buffer_name,
             primitive_len: index_data.len(),
+            index_type: index_type,
         });
 
-        vertex_array
+        Ok(vertex_array)
     }
 
     /// Declares a vetex attribute within the vertex buffer.
@@ -156,6 +232,26 @@ impl VertexArray {
         &mut self,
         attrib_location: AttributeLocation,
         layout: AttribLayout,
+    ) {
+        self.set_attrib_typed(attrib_location, layout, GlType::Float, false);
+    }
+
+    /// Declares a vertex attribute whose elements are of type `attrib_type`.
+    ///
+    /// The `stride` and `offset` of `layout` are measured in elements of `attrib_type` rather than
+    /// in `f32`s, so an attrib of four `UnsignedByte` elements packed after three `f32` positions
+    /// has an offset of 12 and a stride of 16. The packed data still lives in the `f32` vertex
+    /// buffer, e.g. four `u8` color channels share a single `f32` slot (see `f32::from_bits()`).
+    ///
+    /// If `normalized` is `true` integer elements are mapped to the range [0, 1] (or [-1, 1] for
+    /// signed types) when read by the shader, otherwise they are converted to floats directly.
+    /// `normalized` has no effect for floating point types.
+    pub fn set_attrib_typed(
+        &mut self,
+        attrib_location: AttributeLocation,
+        layout: AttribLayout,
+        attrib_type: GlType,
+        normalized: bool,
     ) {
         assert!(
             layout.elements <= 4,
@@ -165,24 +261,80 @@ impl VertexArray {
         // TODO: Verify validity of layout?
         // TODO: Verify that `attrib_location` is valid? How would we even do that?
 
-        // Update the total number of elements per vertex.
-        self.elements_per_vertex += layout.elements;
+        // Update the total number of elements per vertex, counted in `f32` slots so that the
+        // number of vertices in the buffer can still be determined from its length.
+        let type_size = gl_type_size(attrib_type);
+        let attrib_size = layout.elements * type_size;
+        self.elements_per_vertex +=
+            (attrib_size + mem::size_of::<f32>() - 1) / mem::size_of::<f32>();
 
         unsafe {
             let mut context = self.context.borrow_mut();
             let _guard = ::context::ContextGuard::new(context.raw());
             context.bind_vertex_array(self.vertex_array_name);
 
+            gl::enable_vertex_attrib_array(attrib_location);
+            gl::vertex_attrib_pointer(
+                attrib_location,
+                layout.elements as i32,
+                attrib_type,
+                normalized.into(),
+                (layout.stride * type_size) as i32,
+                layout.offset * type_size,
+            );
+        }
+    }
+
+    /// Declares a per-instance vertex attribute backed by its own buffer.
+    ///
+    /// `instance_data` is uploaded to a new buffer owned by the vertex array, and the attribute
+    /// advances once per instance instead of once per vertex (i.e. it has a divisor of 1). This
+    /// only has an effect when drawing with `DrawBuilder::instanced()`, and the buffer should hold
+    /// data for at least as many instances as are drawn. Per-instance attributes don't count
+    /// towards the number of elements per vertex.
+    pub fn set_instance_attrib(
+        &mut self,
+        attrib_location: AttributeLocation,
+        layout: AttribLayout,
+        instance_data: &[f32],
+    ) {
+        assert!(
+            layout.elements <= 4,
+            "Layout elements must not be more than 4 (was actually {})",
+            layout.elements,
+        );
+
+        let buffer_name = unsafe {
+            let mut context = self.context.borrow_mut();
+            let _guard = ::context::ContextGuard::new(context.raw());
+            context.bind_vertex_array(self.vertex_array_name);
+
+            let buffer_name = gl::gen_buffer().expect("Failed to generate buffer object");
+            gl::bind_buffer(BufferTarget::Array, buffer_name);
+            gl::buffer_data(
+                BufferTarget::Array,
+                instance_data,
+                BufferUsage::StaticDraw,
+            );
+
             gl::enable_vertex_attrib_array(attrib_location);
             gl::vertex_attrib_pointer(
                 attrib_location,
                 layout.elements as i32,
                 GlType::Float,
                 False,
-                (layout.stride * mem::size_of::<f32>()) as i32, // TODO: Correctly handle non-f32
-                layout.offset * mem::size_of::<f32>(), // attrib data types.
+                (layout.stride * mem::size_of::<f32>()) as i32,
+                layout.offset * mem::size_of::<f32>(),
             );
-        }
+            gl::vertex_attrib_divisor(attrib_location, 1);
+
+            // Restore the vertex buffer so that later calls to `set_attrib()` refer to it.
+            gl::bind_buffer(BufferTarget::Array, self.vertex_buffer_name);
+
+            buffer_name
+        };
+
+        self.instance_buffer_names.push(buffer_name);
     }
 }
 
@@ -194,6 +346,12 @@ impl Drop for VertexArray {
         unsafe {
             gl::delete_vertex_arrays(1, &mut self.vertex_array_name);
             gl::delete_buffers(2, buffers.as_ptr());
+            if self.instance_buffer_names.len() > 0 {
+                gl::delete_buffers(
+                    self.instance_buffer_names.len() as i32,
+                    self.instance_buffer_names.as_ptr(),
+                );
+            }
         }
         context.unbind_vertex_array(self.vertex_array_name);
     }
@@ -209,6 +367,9 @@ struct IndexBuffer {
     /// This does not reflect number of primitive shapes described by the index buffer, e.g. an
     /// index length of 3 may only describe a single triangle.
     primitive_len: usize,
+
+    /// The type of the indices in the buffer.
+    index_type: IndexType,
 }
 
 /// A configuration object for specifying all of the various configurable options for a draw call.
@@ -220,8 +381,14 @@ pub struct DrawBuilder<'a> {
     program: Option<&'a Program>,
     cull: Option<Face>,
     depth_test: Option<Comparison>,
+    depth_write: bool,
+    polygon_offset: Option<(f32, f32)>,
     winding_order: WindingOrder,
-    blend: (SourceFactor, DestFactor),
+    blend: (SourceFactor, DestFactor, SourceFactor, DestFactor),
+    blend_equation: BlendEquation,
+    instance_count: Option<usize>,
+    target: Option<&'a Framebuffer>,
+    scissor: Option<Rect>,
     uniforms: HashMap<UniformLocation, UniformValue<'a>>,
 
     context: Rc<RefCell<ContextInner>>,
@@ -238,8 +405,14 @@ impl<'a> DrawBuilder<'a> {
             program: None,
             cull: None,
             depth_test: None,
+            depth_write: true,
+            polygon_offset: None,
             winding_order: WindingOrder::default(),
             blend: Default::default(),
+            blend_equation: BlendEquation::default(),
+            instance_count: None,
+            target: None,
+            scissor: None,
             uniforms: HashMap::new(),
 
             context: context.inner(),
@@ -270,17 +443,101 @@ impl<'a> DrawBuilder<'a> {
         self
     }
 
+    /// Enables or disables writing to the depth buffer.
+    ///
+    /// Depth writes are enabled by default. Transparent geometry is usually drawn with depth
+    /// writes disabled so that it doesn't occlude other transparent geometry drawn after it, while
+    /// still being depth tested against opaque geometry. Depth writes only happen when depth
+    /// testing is enabled.
+    pub fn depth_write(&mut self, enabled: bool) -> &mut DrawBuilder<'a> {
+        self.depth_write = enabled;
+        self
+    }
+
+    /// Offsets the depth values of the drawn polygons.
+    ///
+    /// The offset applied to each fragment is `factor * DZ + units * r`, where `DZ` is the slope
+    /// of the polygon's depth relative to the screen and `r` is the smallest resolvable depth
+    /// difference. Negative values move the polygons toward the camera.
+    ///
+    /// This is primarily useful for drawing a wireframe over a solid mesh without z-fighting. Draw
+    /// the solid mesh normally, then draw the wireframe with `polygon_mode(PolygonMode::Line)` and
+    /// a small negative offset such as `polygon_offset(-1.0, -1.0)`. Values between `-1.0` and
+    /// `-2.0` for both `factor` and `units` work well in most scenes; larger values may cause the
+    /// wireframe to show through geometry in front of it.
+    pub fn polygon_offset(&mut self, factor: f32, units: f32) -> &mut DrawBuilder<'a> {
+        self.polygon_offset = Some((factor, units));
+        self
+    }
+
     pub fn winding(&mut self, winding_order: WindingOrder) -> &mut DrawBuilder<'a> {
         self.winding_order = winding_order;
         self
     }
 
+    /// Sets the blend factors for all color channels.
+    ///
+    /// Equivalent to `blend_separate()` with the same factors for RGB and alpha.
     pub fn blend(
         &mut self,
         source_factor: SourceFactor,
         dest_factor: DestFactor
     ) -> &mut DrawBuilder<'a> {
-        self.blend = (source_factor, dest_factor);
+        self.blend_separate(source_factor, dest_factor, source_factor, dest_factor)
+    }
+
+    /// Sets separate blend factors for the RGB and alpha channels.
+    ///
+    /// Compositing premultiplied alpha, for example, uses `(One, OneMinusSourceAlpha)` for both,
+    /// while keeping a correct alpha channel when blending straight alpha into a framebuffer uses
+    /// `(SourceAlpha, OneMinusSourceAlpha)` for RGB and `(One, OneMinusSourceAlpha)` for alpha.
+    pub fn blend_separate(
+        &mut self,
+        source_rgb: SourceFactor,
+        dest_rgb: DestFactor,
+        source_alpha: SourceFactor,
+        dest_alpha: DestFactor,
+    ) -> &mut DrawBuilder<'a> {
+        self.blend = (source_rgb, dest_rgb, source_alpha, dest_alpha);
+        self
+    }
+
+    /// Sets the equation used to combine the blended source and destination colors.
+    ///
+    /// Defaults to `BlendEquation::Add`.
+    pub fn blend_equation(&mut self, equation: BlendEquation) -> &mut DrawBuilder<'a> {
+        self.blend_equation = equation;
+        self
+    }
+
+    /// Draws `count` instances of the vertex array in a single draw call.
+    ///
+    /// Attributes declared with `VertexArray::set_instance_attrib()` advance once per instance,
+    /// and the vertex shader can read the index of the current instance from `gl_InstanceID`.
+    /// Draws are not instanced by default.
+    pub fn instanced(&mut self, count: usize) -> &mut DrawBuilder<'a> {
+        self.instance_count = Some(count);
+        self
+    }
+
+    /// Draws to `framebuffer` instead of the default framebuffer.
+    ///
+    /// The framebuffer is only bound for the duration of the draw, and the viewport is set to
+    /// cover the whole framebuffer. Afterwards the default framebuffer and the previous viewport
+    /// are restored, so later draws without a target still go to the window.
+    pub fn target(&mut self, framebuffer: &'a Framebuffer) -> &mut DrawBuilder<'a> {
+        self.target = Some(framebuffer);
+        self
+    }
+
+    /// Clips the draw to a rectangle, in window coordinates.
+    ///
+    /// `x` and `y` are the lower left corner of the rectangle. The scissor box only applies for
+    /// the duration of the draw, afterwards the context's scissor state (see
+    /// `Context::set_scissor()`) is restored. Draws without a scissor box use the context's
+    /// scissor state, which by default disables the scissor test.
+    pub fn scissor(&mut self, x: i32, y: i32, width: i32, height: i32) -> &mut DrawBuilder<'a> {
+        self.scissor = Some(Rect::new(x, y, width, height));
         self
     }
 
@@ -317,10 +574,46 @@ impl<'a> DrawBuilder<'a> {
         self
     }
 
-    pub fn draw(&mut self) {
+    /// Performs the draw call using the current configuration.
+    ///
+    /// # Errors
+    ///
+    /// - `DrawError::NoProgram` if no shader program has been set using `program()`. Nothing is
+    ///   drawn in this case.
+    /// - `DrawError::TooManyTextures` if more textures have been set as uniforms than there are
+    ///   texture units available. Nothing is drawn in this case.
+    /// - `DrawError::Gl` if OpenGL reported an error during the draw. OpenGL errors are only
+    ///   checked in debug builds, since querying the error state forces a sync with the driver.
+    pub fn draw(&mut self) -> ::std::result::Result<(), DrawError> {
         let mut context = self.context.borrow_mut();
         let _guard = ::context::ContextGuard::new(context.raw());
 
+        if self.program.is_none() {
+            return Err(DrawError::NoProgram);
+        }
+
+        // Clear any errors left over from earlier operations so that they aren't reported as
+        // coming from this draw.
+        if cfg!(debug_assertions) {
+            take_gl_error();
+        }
+
+        // Make sure there are enough texture units for all of the textures used by the draw.
+        let texture_count = self
+            .uniforms
+            .values()
+            .filter(|uniform| match **uniform {
+                UniformValue::Texture(_) | UniformValue::TextureCubeMap(_) => true,
+                _ => false,
+            })
+            .count();
+        if texture_count > context.max_texture_units() {
+            return Err(DrawError::TooManyTextures {
+                textures: texture_count,
+                max_texture_units: context.max_texture_units(),
+            });
+        }
+
         context.polygon_mode(self.polygon_mode.unwrap_or_default());
         context.use_program(self.program.map(Program::inner));
 
@@ -335,17 +628,37 @@ impl<'a> DrawBuilder<'a> {
         if let Some(depth_test) = self.depth_test {
             context.enable_server_depth_test(true);
             context.depth_test(depth_test);
+            context.depth_write(self.depth_write);
         } else {
             context.enable_server_depth_test(false);
         }
 
-        let (source_factor, dest_factor) = self.blend;
-        context.blend(source_factor, dest_factor);
+        if let Some((factor, units)) = self.polygon_offset {
+            context.enable_server_polygon_offset(true);
+            context.polygon_offset(factor, units);
+        } else {
+            context.enable_server_polygon_offset(false);
+        }
+
+        let (source_rgb, dest_rgb, source_alpha, dest_alpha) = self.blend;
+        context.blend(source_rgb, dest_rgb, source_alpha, dest_alpha);
+        context.blend_equation(self.blend_equation);
+
+        let window_viewport = context.viewport();
+        if let Some(framebuffer) = self.target {
+            context.bind_framebuffer(framebuffer.inner());
+            context.set_viewport(framebuffer.viewport());
+        }
 
-        let mut active_texture = 0;
+        let context_scissor = context.scissor();
+        if self.scissor.is_some() {
+            context.set_scissor(self.scissor);
+        }
+
+        let mut next_texture_unit = 0;
         // Apply uniforms.
         for (&location, uniform) in &self.uniforms {
-            self.apply(uniform, location, &mut active_texture);
+            self.apply(&mut *context, uniform, location, &mut next_texture_unit);
         }
 
         unsafe {
@@ -354,25 +667,64 @@ impl<'a> DrawBuilder<'a> {
             // first.
             context.bind_vertex_array(self.vertex_array.vertex_array_name);
 
-            if let Some(indices) = self.vertex_array.index_buffer.as_ref() {
-                gl::draw_elements(
+            match (self.vertex_array.index_buffer.as_ref(), self.instance_count) {
+                (Some(indices), None) => gl::draw_elements(
                     self.draw_mode,
                     indices.primitive_len as i32,
-                    IndexType::UnsignedInt,
+                    indices.index_type,
                     0,
-                );
-            } else {
-                let vertex_len = self.vertex_array.vertex_primitive_len / self.vertex_array.elements_per_vertex;
-                gl::draw_arrays(
+                ),
+                (Some(indices), Some(instance_count)) => gl::draw_elements_instanced(
                     self.draw_mode,
+                    indices.primitive_len as i32,
+                    indices.index_type,
                     0,
-                    vertex_len as i32,
-                );
+                    instance_count as i32,
+                ),
+                (None, instance_count) => {
+                    let vertex_len = self.vertex_array.vertex_primitive_len / self.vertex_array.elements_per_vertex;
+                    match instance_count {
+                        None => gl::draw_arrays(
+                            self.draw_mode,
+                            0,
+                            vertex_len as i32,
+                        ),
+                        Some(instance_count) => gl::draw_arrays_instanced(
+                            self.draw_mode,
+                            0,
+                            vertex_len as i32,
+                            instance_count as i32,
+                        ),
+                    }
+                },
             }
         }
+
+        if self.target.is_some() {
+            context.bind_framebuffer(FramebufferObject::null());
+            context.set_viewport(window_viewport);
+        }
+
+        if self.scissor.is_some() {
+            context.set_scissor(context_scissor);
+        }
+
+        if cfg!(debug_assertions) {
+            if let Some(error) = take_gl_error() {
+                return Err(DrawError::Gl(error));
+            }
+        }
+
+        Ok(())
     }
 
-    fn apply(&self, uniform: &UniformValue, location: UniformLocation, active_texture: &mut i32) {
+    fn apply(
+        &self,
+        context: &mut ContextInner,
+        uniform: &UniformValue,
+        location: UniformLocation,
+        next_texture_unit: &mut u32,
+    ) {
         match *uniform {
             UniformValue::F32(value) => unsafe {
                 gl::uniform_f32x1(location, value);
@@ -401,9 +753,27 @@ impl<'a> DrawBuilder<'a> {
             UniformValue::I32x1v(value) => unsafe {
                 gl::uniform_i32x1v(location, value.len() as i32, value.as_ptr());
             },
+            UniformValue::I32x2((x, y)) => unsafe {
+                gl::uniform_i32x2(location, x, y);
+            },
+            UniformValue::I32x3((x, y, z)) => unsafe {
+                gl::uniform_i32x3(location, x, y, z);
+            },
+            UniformValue::I32x4((x, y, z, w)) => unsafe {
+                gl::uniform_i32x4(location, x, y, z, w);
+            },
             UniformValue::U32(value) => unsafe {
                 gl::uniform_u32x1(location, value);
             },
+            UniformValue::U32x2((x, y)) => unsafe {
+                gl::uniform_u32x2(location, x, y);
+            },
+            UniformValue::U32x3((x, y, z)) => unsafe {
+                gl::uniform_u32x3(location, x, y, z);
+            },
+            UniformValue::U32x4((x, y, z, w)) => unsafe {
+                gl::uniform_u32x4(location, x, y, z, w);
+            },
             UniformValue::Matrix(ref matrix) => match matrix.data.len() {
                 16 => unsafe {
                     gl::uniform_matrix_f32x4v(
@@ -421,19 +791,152 @@ impl<'a> DrawBuilder<'a> {
                 },
                 _ => panic!("Unsupported matrix data length: {}", matrix.data.len()),
             },
-            UniformValue::Texture(texture) => {
-                unsafe {
-                    texture::set_active_texture(*active_texture as u32);
-                    gl::bind_texture(TextureBindTarget::Texture2d, texture.inner());
-                    gl::uniform_i32x1(location, *active_texture);
+            UniformValue::MatrixArray(ref matrices) => {
+                match matrices.matrix_len() {
+                    16 => unsafe {
+                        gl::uniform_matrix_f32x4v(
+                            location,
+                            matrices.count as i32,
+                            matrices.transpose.into(),
+                            matrices.data.as_ptr())
+                    },
+                    9 => unsafe {
+                        gl::uniform_matrix_f32x3v(
+                            location,
+                            matrices.count as i32,
+                            matrices.transpose.into(),
+                            matrices.data.as_ptr())
+                    },
+                    len => panic!("Unsupported matrix data length: {}", len),
                 }
+            },
+            UniformValue::Texture(texture) => {
+                let unit = TextureUnit::new(*next_texture_unit);
+                context.bind_texture(unit, texture.inner());
+                unsafe { gl::uniform_i32x1(location, unit.index() as i32); }
+
+                *next_texture_unit += 1;
+            }
+            UniformValue::TextureCubeMap(texture) => {
+                let unit = TextureUnit::new(*next_texture_unit);
+                context.bind_texture_target(unit, TextureBindTarget::CubeMap, texture.inner());
+                unsafe { gl::uniform_i32x1(location, unit.index() as i32); }
 
-                *active_texture += 1;
+                *next_texture_unit += 1;
             }
         }
     }
 }
 
+/// Represents an error that prevented a draw call from being made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawError {
+    /// Indicates that no shader program was set for the draw.
+    NoProgram,
+
+    /// Indicates that OpenGL generated an error while performing the draw.
+    ///
+    /// See the documentation for `gl::draw_arrays()` and `gl::draw_elements()` (or their
+    /// `_instanced` variants) for the conditions that generate each error code.
+    Gl(ErrorCode),
+
+    /// Indicates that the draw used more textures than the context has texture units.
+    TooManyTextures {
+        textures: usize,
+        max_texture_units: usize,
+    },
+}
+
+/// Represents an error that prevented a vertex array from being created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexArrayError {
+    /// Indicates that OpenGL failed to generate a vertex array object.
+    FailedToGenerateVertexArray,
+
+    /// Indicates that OpenGL failed to generate a buffer object.
+    FailedToGenerateBuffer,
+
+    /// Indicates that OpenGL generated an error while filling the buffers, most likely
+    /// `ErrorCode::OutOfMemory`.
+    Gl(ErrorCode),
+}
+
+/// The error type for all fallible `gl-util` operations.
+///
+/// Each module has its own error type describing the ways its operations can fail, `Error` wraps
+/// all of them so that code using several parts of `gl-util` can propagate errors with `?`.
+#[derive(Debug)]
+pub enum Error {
+    Context(context::Error),
+    Framebuffer(framebuffer::Error),
+    Shader(shader::ShaderError),
+    Program(shader::ProgramError),
+    Texture(texture::Error),
+    VertexArray(VertexArrayError),
+    Draw(DrawError),
+}
+
+impl From<context::Error> for Error {
+    fn from(from: context::Error) -> Error {
+        Error::Context(from)
+    }
+}
+
+impl From<framebuffer::Error> for Error {
+    fn from(from: framebuffer::Error) -> Error {
+        Error::Framebuffer(from)
+    }
+}
+
+impl From<shader::ShaderError> for Error {
+    fn from(from: shader::ShaderError) -> Error {
+        Error::Shader(from)
+    }
+}
+
+impl From<shader::ProgramError> for Error {
+    fn from(from: shader::ProgramError) -> Error {
+        Error::Program(from)
+    }
+}
+
+impl From<texture::Error> for Error {
+    fn from(from: texture::Error) -> Error {
+        Error::Texture(from)
+    }
+}
+
+impl From<VertexArrayError> for Error {
+    fn from(from: VertexArrayError) -> Error {
+        Error::VertexArray(from)
+    }
+}
+
+impl From<DrawError> for Error {
+    fn from(from: DrawError) -> Error {
+        Error::Draw(from)
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Returns the size in bytes of a single element of `gl_type`.
+fn gl_type_size(gl_type: GlType) -> usize {
+    match gl_type {
+        GlType::Byte | GlType::UnsignedByte => 1,
+        GlType::Short | GlType::UnsignedShort | GlType::HalfFloat => 2,
+        GlType::Float | GlType::Fixed | GlType::Int | GlType::UnsignedInt => 4,
+        GlType::Double => 8,
+    }
+}
+
+/// Returns the first pending OpenGL error, clearing all error flags.
+///
+/// Must be called with a context current.
+fn take_gl_error() -> Option<ErrorCode> {
+    gl::check_error().err().map(|errors| errors[0])
+}
+
 /// Represents a value for a uniform variable in a shader program.
 #[derive(Debug)]
 pub enum UniformValue<'a> {
@@ -446,9 +949,17 @@ pub enum UniformValue<'a> {
     F32x4v(&'a [[f32; 4]]),
     I32(i32),
     I32x1v(&'a [i32]),
+    I32x2((i32, i32)),
+    I32x3((i32, i32, i32)),
+    I32x4((i32, i32, i32, i32)),
     U32(u32),
+    U32x2((u32, u32)),
+    U32x3((u32, u32, u32)),
+    U32x4((u32, u32, u32, u32)),
     Matrix(GlMatrix<'a>),
+    MatrixArray(GlMatrixArray<'a>),
     Texture(&'a Texture2d),
+    TextureCubeMap(&'a TextureCubeMap),
 }
 
 impl<'a> From<f32> for UniformValue<'a> {
@@ -529,26 +1040,162 @@ impl<'a> From<&'a [i32]> for UniformValue<'a> {
     }
 }
 
+impl<'a> From<(i32, i32)> for UniformValue<'a> {
+    fn from(from: (i32, i32)) -> UniformValue<'a> {
+        UniformValue::I32x2(from)
+    }
+}
+
+impl<'a> From<(i32, i32, i32)> for UniformValue<'a> {
+    fn from(from: (i32, i32, i32)) -> UniformValue<'a> {
+        UniformValue::I32x3(from)
+    }
+}
+
+impl<'a> From<(i32, i32, i32, i32)> for UniformValue<'a> {
+    fn from(from: (i32, i32, i32, i32)) -> UniformValue<'a> {
+        UniformValue::I32x4(from)
+    }
+}
+
 impl<'a> From<u32> for UniformValue<'a> {
     fn from(from: u32) -> UniformValue<'a> {
         UniformValue::U32(from)
     }
 }
 
+impl<'a> From<(u32, u32)> for UniformValue<'a> {
+    fn from(from: (u32, u32)) -> UniformValue<'a> {
+        UniformValue::U32x2(from)
+    }
+}
+
+impl<'a> From<(u32, u32, u32)> for UniformValue<'a> {
+    fn from(from: (u32, u32, u32)) -> UniformValue<'a> {
+        UniformValue::U32x3(from)
+    }
+}
+
+impl<'a> From<(u32, u32, u32, u32)> for UniformValue<'a> {
+    fn from(from: (u32, u32, u32, u32)) -> UniformValue<'a> {
+        UniformValue::U32x4(from)
+    }
+}
+
 impl<'a> From<GlMatrix<'a>> for UniformValue<'a> {
     fn from(matrix: GlMatrix<'a>) -> UniformValue<'a> {
         UniformValue::Matrix(matrix)
     }
 }
 
+impl<'a> From<GlMatrixArray<'a>> for UniformValue<'a> {
+    fn from(matrices: GlMatrixArray<'a>) -> UniformValue<'a> {
+        UniformValue::MatrixArray(matrices)
+    }
+}
+
 impl<'a> From<&'a Texture2d> for UniformValue<'a> {
     fn from(from: &'a Texture2d) -> UniformValue<'a> {
         UniformValue::Texture(from)
     }
 }
 
+impl<'a> From<&'a TextureCubeMap> for UniformValue<'a> {
+    fn from(from: &'a TextureCubeMap) -> UniformValue<'a> {
+        UniformValue::TextureCubeMap(from)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GlMatrix<'a> {
     pub data: &'a [f32],
     pub transpose: bool,
 }
+
+/// An array of matrices to be sent to a `mat4[]` or `mat3[]` uniform.
+///
+/// `data` contains the elements of all `count` matrices packed together, so its length must be
+/// either `count * 16` or `count * 9`.
+#[derive(Debug, Clone)]
+pub struct GlMatrixArray<'a> {
+    pub data: &'a [f32],
+    pub count: usize,
+    pub transpose: bool,
+}
+
+impl<'a> GlMatrixArray<'a> {
+    /// Creates an array of `mat4` matrices from a slice of 4x4 matrices.
+    pub fn from_mat4s(matrices: &'a [[f32; 16]], transpose: bool) -> GlMatrixArray<'a> {
+        // `[[f32; 16]]` has the same layout as the matrices' elements packed together.
+        let data = unsafe {
+            ::std::slice::from_raw_parts(matrices.as_ptr() as *const f32, matrices.len() * 16)
+        };
+
+        GlMatrixArray {
+            data: data,
+            count: matrices.len(),
+            transpose: transpose,
+        }
+    }
+
+    /// Gets the number of elements in each matrix, 16 for `mat4` or 9 for `mat3`.
+    ///
+    /// # Panics
+    ///
+    /// - If the length of `data` isn't a multiple of `count`.
+    pub fn matrix_len(&self) -> usize {
+        assert!(
+            self.count > 0 && self.data.len() % self.count == 0,
+            "Matrix array data length {} is not a multiple of the matrix count {}",
+            self.data.len(),
+            self.count,
+        );
+
+        self.data.len() / self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlMatrixArray;
+
+    fn translation(x: f32) -> [f32; 16] {
+        [
+            1.0, 0.0, 0.0, x,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ]
+    }
+
+    #[test]
+    fn mat4_array_layout() {
+        let matrices = [translation(1.0), translation(2.0), translation(3.0)];
+        let array = GlMatrixArray::from_mat4s(&matrices, true);
+
+        assert_eq!(3, array.count);
+        assert_eq!(48, array.data.len());
+        assert_eq!(16, array.matrix_len());
+        assert!(array.transpose);
+
+        // Matrices are packed one after another in order.
+        for (index, matrix) in matrices.iter().enumerate() {
+            assert_eq!(&matrix[..], &array.data[index * 16..(index + 1) * 16]);
+        }
+    }
+
+    #[test]
+    fn mat3_array_len() {
+        let data = [0.0; 27];
+        let array = GlMatrixArray { data: &data, count: 3, transpose: false };
+        assert_eq!(9, array.matrix_len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn matrix_array_bad_len() {
+        let data = [0.0; 20];
+        let array = GlMatrixArray { data: &data, count: 3, transpose: false };
+        array.matrix_len();
+    }
+}
@@ -1,6 +1,7 @@
 use context::Context;
 use gl;
 use gl::*;
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::mem;
 
@@ -130,6 +131,12 @@ enum ShaderCompileStatus {
 pub struct Program {
     program_object: ProgramObject,
 
+    /// The locations of the program's active uniforms, keyed by name.
+    ///
+    /// Built once the program is linked so that looking up uniforms while drawing doesn't have
+    /// to query OpenGL.
+    uniforms: HashMap<String, UniformLocation>,
+
     pub(crate) context: ::gl::Context,
 }
 
@@ -141,8 +148,9 @@ impl Program {
         let _guard = ::context::ContextGuard::new(context);
 
         // Create shader program.
-        let program = Program {
+        let mut program = Program {
             program_object: unsafe { gl::create_program() },
+            uniforms: HashMap::new(),
 
             context: context,
         };
@@ -166,7 +174,10 @@ impl Program {
         // Check for errors.
         let link_status = link_status(program.inner());
         match link_status {
-            ProgramLinkStatus::Success => Ok(program),
+            ProgramLinkStatus::Success => {
+                program.uniforms = active_uniforms(program.inner());
+                Ok(program)
+            },
             ProgramLinkStatus::Failure => {
                 let log = program_log(program.inner());
                 Err(ProgramError::LinkError(log))
@@ -191,22 +202,21 @@ impl Program {
         }
     }
 
+    /// Gets the location of an active uniform in the program.
+    ///
+    /// Returns `None` if the program has no active uniform named `name`. Array elements can be
+    /// looked up by index (e.g. `"lights[2]"`), and the array's name on its own refers to the
+    /// first element.
     pub(crate) fn get_uniform_location(&self, name: &str) -> Option<UniformLocation> {
-        let _guard = ::context::ContextGuard::new(self.context);
-
-        let mut null_terminated = String::from(name);
-        null_terminated.push('\0');
-
-        let raw_location = unsafe {
-            gl::get_uniform_location(self.inner(), null_terminated.as_ptr())
-        };
+        self.uniforms.get(name).cloned()
+    }
 
-        // Check for errors.
-        if raw_location == -1 {
-            None
-        } else {
-            Some(UniformLocation::from_index(raw_location as u32))
-        }
+    /// Returns `true` if the program has an active uniform named `name`.
+    ///
+    /// Uniforms that are declared but never used by the shader are usually removed by the
+    /// shader compiler, in which case they aren't active.
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.uniforms.contains_key(name)
     }
 
     pub(crate) fn inner(&self) -> ProgramObject {
@@ -252,6 +262,81 @@ fn link_status(program_object: ProgramObject) -> ProgramLinkStatus {
     }
 }
 
+/// Finds the locations of all active uniforms in a linked program.
+///
+/// Each element of an array uniform gets its own entry, and the array's name without an index
+/// maps to the first element. Uniforms in uniform blocks don't have locations and are skipped.
+fn active_uniforms(program_object: ProgramObject) -> HashMap<String, UniformLocation> {
+    let mut uniforms = HashMap::new();
+
+    let mut count = 0;
+    let mut max_length = 0;
+    unsafe {
+        gl::get_program_param(program_object, ProgramParam::ActiveUniforms, &mut count);
+        gl::get_program_param(program_object, ProgramParam::ActiveUniformMaxLength, &mut max_length);
+    }
+
+    let mut name_buffer = vec![0u8; max_length as usize];
+    for index in 0..count as u32 {
+        let mut length = 0;
+        let mut size = 0;
+        let mut uniform_type = 0;
+        unsafe {
+            gl::get_active_uniform(
+                program_object,
+                index,
+                max_length,
+                &mut length,
+                &mut size,
+                &mut uniform_type,
+                name_buffer.as_mut_ptr());
+        }
+
+        let name = String::from_utf8_lossy(&name_buffer[..length as usize]).into_owned();
+
+        // Array uniforms are reported by the name of their first element, e.g. "lights[0]".
+        let is_array = name.ends_with("[0]");
+        let base_name = if is_array {
+            name[..name.len() - 3].to_owned()
+        } else {
+            name
+        };
+
+        if is_array {
+            for element in 0..size {
+                let element_name = format!("{}[{}]", base_name, element);
+                if let Some(location) = uniform_location(program_object, &element_name) {
+                    if element == 0 {
+                        uniforms.insert(base_name.clone(), location);
+                    }
+                    uniforms.insert(element_name, location);
+                }
+            }
+        } else if let Some(location) = uniform_location(program_object, &base_name) {
+            uniforms.insert(base_name, location);
+        }
+    }
+
+    uniforms
+}
+
+/// Queries OpenGL for the location of a uniform.
+fn uniform_location(program_object: ProgramObject, name: &str) -> Option<UniformLocation> {
+    let mut null_terminated = String::from(name);
+    null_terminated.push('\0');
+
+    let raw_location = unsafe {
+        gl::get_uniform_location(program_object, null_terminated.as_ptr())
+    };
+
+    // Check for errors.
+    if raw_location == -1 {
+        None
+    } else {
+        Some(UniformLocation::from_index(raw_location as u32))
+    }
+}
+
 fn program_log(program_object: ProgramObject) -> String {
     // Get the length of the info log.
     let mut info_log_length = 0;
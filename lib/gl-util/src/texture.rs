@@ -1,15 +1,55 @@
-use context::Context;
+use context::{Context, ContextInner};
 use gl;
+use std::cell::RefCell;
+use std::ptr;
+use std::rc::Rc;
 
 pub use gl::{
     TextureObject, TextureFilterFunction, TextureFormat, TextureBindTarget, Texture2dTarget,
-    TextureInternalFormat, TextureDataType, TextureParameterName, TextureParameterTarget};
+    TextureInternalFormat, TextureDataType, TextureParameterName, TextureParameterTarget,
+    TextureWrapMode};
+
+/// Describes how a texture is sampled.
+///
+/// The default uses linear filtering and repeats the texture in both directions. Pixel art
+/// generally wants `Nearest` for both filters so that texels stay crisp. The mipmap filters
+/// (e.g. `LinearMipmapLinear`) may only be used for `min_filter`, and only once mipmaps have
+/// been generated with `Texture2d::generate_mipmaps()`, otherwise the texture samples as black.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureParams {
+    /// The filter used when the texture is drawn smaller than its native size.
+    pub min_filter: TextureFilterFunction,
+
+    /// The filter used when the texture is drawn larger than its native size.
+    ///
+    /// Must be either `Nearest` or `Linear`.
+    pub mag_filter: TextureFilterFunction,
+
+    /// How texture coordinates outside of [0, 1] are handled horizontally.
+    pub wrap_s: TextureWrapMode,
+
+    /// How texture coordinates outside of [0, 1] are handled vertically.
+    pub wrap_t: TextureWrapMode,
+}
+
+impl Default for TextureParams {
+    fn default() -> TextureParams {
+        TextureParams {
+            min_filter: TextureFilterFunction::Linear,
+            mag_filter: TextureFilterFunction::Linear,
+            wrap_s: TextureWrapMode::Repeat,
+            wrap_t: TextureWrapMode::Repeat,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Texture2d {
     texture_object: TextureObject,
+    width: usize,
+    height: usize,
 
-    context: ::gl::Context,
+    context: Rc<RefCell<ContextInner>>,
 }
 
 impl Texture2d {
@@ -26,9 +66,6 @@ impl Texture2d {
         height: usize,
         data: &[T],
     ) -> Result<Texture2d, Error> {
-        let context = context.raw();
-        let _guard = ::context::ContextGuard::new(context);
-
         let expected_pixels = width * height * data_format.elements() / T::ELEMENTS;
         assert!(
             expected_pixels == data.len(),
@@ -38,16 +75,164 @@ impl Texture2d {
             expected_pixels,
             data.len());
 
-        let mut texture_object = TextureObject::null();
-        unsafe { gl::gen_textures(1, &mut texture_object); }
+        Texture2d::create(
+            context,
+            data_format,
+            internal_format,
+            width,
+            height,
+            T::DATA_TYPE,
+            data.as_ptr() as *const ())
+    }
+
+    /// Constructs a new `Texture2d` with storage for a `width` by `height` image, but without
+    /// any data.
+    ///
+    /// The contents of the texture are undefined until they are written with `write_rows()`.
+    /// This allows large textures to be uploaded a few rows at a time rather than all at once.
+    pub fn uninitialized<T: TextureData>(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Texture2d, Error> {
+        Texture2d::create(
+            context,
+            data_format,
+            internal_format,
+            width,
+            height,
+            T::DATA_TYPE,
+            ptr::null())
+    }
+
+    pub fn empty(context: &Context) -> Texture2d {
+        Texture2d {
+            texture_object: TextureObject::null(),
+            width: 0,
+            height: 0,
 
-        // Check if the texture object was successfully created.
-        if texture_object.is_null() {
-            return Err(Error::FailedToGenerateTexture);
+            context: context.inner(),
         }
+    }
+
+    /// Writes rows of pixel data to the texture, starting at row `first_row`.
+    ///
+    /// `data` must contain whole rows of pixels in the specified format.
+    ///
+    /// # Panics
+    ///
+    /// - If `data` doesn't contain a whole number of rows.
+    /// - If the rows in `data` would extend past the last row of the texture.
+    pub fn write_rows<T: TextureData>(
+        &mut self,
+        data_format: TextureFormat,
+        first_row: usize,
+        data: &[T],
+    ) {
+        let row_len = self.width * data_format.elements() / T::ELEMENTS;
+        assert!(
+            row_len > 0 && data.len() % row_len == 0,
+            "Texture data must contain whole rows, row length: {}, data length: {}",
+            row_len,
+            data.len());
+
+        let rows = data.len() / row_len;
+        assert!(
+            first_row + rows <= self.height,
+            "Rows extend past the end of the texture, first row: {}, rows: {}, height: {}",
+            first_row,
+            rows,
+            self.height);
+
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let active_texture = context.active_texture();
+        context.bind_texture(active_texture, self.texture_object);
+
+        unsafe {
+            gl::texture_sub_image_2d(
+                Texture2dTarget::Texture2d,
+                0,
+                0,
+                first_row as i32,
+                self.width as i32,
+                rows as i32,
+                data_format,
+                T::DATA_TYPE,
+                data.as_ptr() as *const ());
+        }
+
+        context.bind_texture(active_texture, TextureObject::null());
+    }
+
+    /// Sets how the texture is filtered and wrapped when sampled.
+    ///
+    /// Textures are created with `TextureParams::default()`.
+    pub fn set_params(&mut self, params: TextureParams) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let active_texture = context.active_texture();
+        context.bind_texture(active_texture, self.texture_object);
+        unsafe { apply_params(TextureParameterTarget::Texture2d, params); }
+        context.bind_texture(active_texture, TextureObject::null());
+    }
+
+    /// Generates mipmaps from the current contents of the texture.
+    ///
+    /// Needs to be called again if the texture's contents change, e.g. after `write_rows()`.
+    pub fn generate_mipmaps(&mut self) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let active_texture = context.active_texture();
+        context.bind_texture(active_texture, self.texture_object);
+        unsafe { gl::generate_mipmap(TextureBindTarget::Texture2d); }
+        context.bind_texture(active_texture, TextureObject::null());
+    }
+
+    /// Returns the width of the texture in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the texture in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the OpenGL primitive managed by this object.
+    pub(crate) fn inner(&self) -> TextureObject {
+        self.texture_object
+    }
+
+    fn create(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        width: usize,
+        height: usize,
+        data_type: TextureDataType,
+        data: *const (),
+    ) -> Result<Texture2d, Error> {
+        let context_inner = context.inner();
+        let mut context = context_inner.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let texture_object = match gl::gen_texture() {
+            Some(texture_object) => texture_object,
+            None => return Err(Error::FailedToGenerateTexture),
+        };
+
+        // Bind the texture to the active unit through the context so that the context's record of
+        // the bound textures stays accurate.
+        let active_texture = context.active_texture();
+        context.bind_texture(active_texture, texture_object);
 
         unsafe {
-            gl::bind_texture(TextureBindTarget::Texture2d, texture_object);
             gl::texture_image_2d(
                 Texture2dTarget::Texture2d,
                 0,
@@ -56,33 +241,126 @@ impl Texture2d {
                 height as i32,
                 0,
                 data_format,
-                T::DATA_TYPE,
-                data.as_ptr() as *const ());
+                data_type,
+                data);
 
-            gl::texture_parameter_i32(
-                TextureParameterTarget::Texture2d,
-                TextureParameterName::MinFilter,
-                TextureFilterFunction::Nearest.into());
-            gl::texture_parameter_i32(
-                TextureParameterTarget::Texture2d,
-                TextureParameterName::MagFilter,
-                TextureFilterFunction::Nearest.into());
-            gl::bind_texture(TextureBindTarget::Texture2d, TextureObject::null());
+            apply_params(TextureParameterTarget::Texture2d, TextureParams::default());
         }
 
+        context.bind_texture(active_texture, TextureObject::null());
+
         Ok(Texture2d {
             texture_object: texture_object,
+            width: width,
+            height: height,
 
-            context: context,
+            context: context_inner.clone(),
         })
     }
+}
 
-    pub fn empty(context: &Context) -> Texture2d {
-        Texture2d {
-            texture_object: TextureObject::null(),
+impl Drop for Texture2d {
+    fn drop(&mut self) {
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+        unsafe { gl::delete_textures(1, &mut self.inner()); }
 
-            context: context.raw(),
+        // Deleting the texture implicitly unbinds it, and the name may be reused by a new texture,
+        // so the context needs to forget about any bindings for it.
+        context.forget_texture(self.texture_object);
+    }
+}
+
+/// A cube map texture, made up of six square 2D faces.
+///
+/// Cube maps are sampled in shaders with a `samplerCube` using a direction vector rather than
+/// texture coordinates, which makes them useful for skyboxes and environment maps.
+#[derive(Debug)]
+pub struct TextureCubeMap {
+    texture_object: TextureObject,
+    size: usize,
+
+    context: Rc<RefCell<ContextInner>>,
+}
+
+impl TextureCubeMap {
+    /// Constructs a new `TextureCubeMap` from the data for each of its faces.
+    ///
+    /// Each face is `size` pixels wide and tall. The faces are given in the order positive X,
+    /// negative X, positive Y, negative Y, positive Z, negative Z.
+    ///
+    /// # Panics
+    ///
+    /// - If any face doesn't contain `size * size` pixels.
+    pub fn new<T: TextureData>(
+        context: &Context,
+        data_format: TextureFormat,
+        internal_format: TextureInternalFormat,
+        size: usize,
+        faces: [&[T]; 6],
+    ) -> Result<TextureCubeMap, Error> {
+        const FACE_TARGETS: [Texture2dTarget; 6] = [
+            Texture2dTarget::CubeMapPositiveX,
+            Texture2dTarget::CubeMapNegativeX,
+            Texture2dTarget::CubeMapPositiveY,
+            Texture2dTarget::CubeMapNegativeY,
+            Texture2dTarget::CubeMapPositiveZ,
+            Texture2dTarget::CubeMapNegativeZ,
+        ];
+
+        let expected_pixels = size * size * data_format.elements() / T::ELEMENTS;
+        for (face_target, face) in FACE_TARGETS.iter().zip(faces.iter()) {
+            assert!(
+                expected_pixels == face.len(),
+                "Wrong number of pixels in cube map face {:?}, size: {}, expected pixels: {}, actual pixels: {}",
+                face_target,
+                size,
+                expected_pixels,
+                face.len());
         }
+
+        let context_inner = context.inner();
+        let mut context = context_inner.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
+
+        let texture_object = match gl::gen_texture() {
+            Some(texture_object) => texture_object,
+            None => return Err(Error::FailedToGenerateTexture),
+        };
+
+        let active_texture = context.active_texture();
+        context.bind_texture_target(active_texture, TextureBindTarget::CubeMap, texture_object);
+
+        unsafe {
+            for (&face_target, face) in FACE_TARGETS.iter().zip(faces.iter()) {
+                gl::texture_image_2d(
+                    face_target,
+                    0,
+                    internal_format,
+                    size as i32,
+                    size as i32,
+                    0,
+                    data_format,
+                    T::DATA_TYPE,
+                    face.as_ptr() as *const ());
+            }
+
+            apply_params(TextureParameterTarget::CubeMap, TextureParams::default());
+        }
+
+        context.bind_texture_target(active_texture, TextureBindTarget::CubeMap, TextureObject::null());
+
+        Ok(TextureCubeMap {
+            texture_object: texture_object,
+            size: size,
+
+            context: context_inner.clone(),
+        })
+    }
+
+    /// Returns the width and height of each face in pixels.
+    pub fn size(&self) -> usize {
+        self.size
     }
 
     /// Returns the OpenGL primitive managed by this object.
@@ -91,13 +369,24 @@ impl Texture2d {
     }
 }
 
-impl Drop for Texture2d {
+impl Drop for TextureCubeMap {
     fn drop(&mut self) {
-        let _guard = ::context::ContextGuard::new(self.context);
+        let mut context = self.context.borrow_mut();
+        let _guard = ::context::ContextGuard::new(context.raw());
         unsafe { gl::delete_textures(1, &mut self.inner()); }
+
+        context.forget_texture(self.texture_object);
     }
 }
 
+/// Applies `params` to the texture bound to `target` on the active texture unit.
+unsafe fn apply_params(target: TextureParameterTarget, params: TextureParams) {
+    gl::texture_parameter_i32(target, TextureParameterName::MinFilter, params.min_filter.into());
+    gl::texture_parameter_i32(target, TextureParameterName::MagFilter, params.mag_filter.into());
+    gl::texture_parameter_i32(target, TextureParameterName::WrapS, params.wrap_s.into());
+    gl::texture_parameter_i32(target, TextureParameterName::WrapT, params.wrap_t.into());
+}
+
 pub trait TextureData {
     const DATA_TYPE: TextureDataType;
     const ELEMENTS: usize;
@@ -128,11 +417,35 @@ pub enum Error {
     FailedToGenerateTexture,
 }
 
-pub unsafe fn set_active_texture(index: u32) {
-    const TEXTURE_ID_BASE: u32 = 0x84C0;
+/// Identifies one of the texture units available in a context.
+///
+/// Each texture sampled by a draw call must be bound to its own texture unit. The number of
+/// texture units is implementation dependent, use `Context::max_texture_units()` to determine
+/// how many are available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureUnit(u32);
+
+impl TextureUnit {
+    /// Creates a `TextureUnit` for the unit with the specified index.
+    pub fn new(index: u32) -> TextureUnit {
+        TextureUnit(index)
+    }
+
+    /// Gets the index of the texture unit.
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
 
-    // TODO: Check that texture index is supported.
+impl Default for TextureUnit {
+    fn default() -> TextureUnit {
+        TextureUnit(0)
+    }
+}
+
+pub unsafe fn set_active_texture(unit: TextureUnit) {
+    const TEXTURE_ID_BASE: u32 = 0x84C0;
 
-    let texture_id = TEXTURE_ID_BASE + index;
+    let texture_id = TEXTURE_ID_BASE + unit.index();
     gl::active_texture(texture_id);
 }
@@ -0,0 +1,117 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate gl_util as gl;
+
+use bootstrap::window::Window;
+use gl::*;
+use gl::context::{Context, Rect};
+use gl::shader::*;
+
+static VERTEX_POSITIONS: [f32; 9] = [
+    -1.0, -1.0, 0.0,
+     1.0, -1.0, 0.0,
+     0.0,  1.0, 0.0,
+];
+
+static VERT_SOURCE: &'static str = r#"
+#version 330 core
+
+layout(location = 0) in vec4 position;
+
+void main() {
+    gl_Position = position;
+}
+"#;
+
+static FRAG_SOURCE: &'static str = r#"
+#version 330 core
+
+out vec4 fragment_color;
+
+void main() {
+    fragment_color = vec4(1, 1, 1, 1);
+}
+"#;
+
+#[test]
+fn draw_without_program() {
+    let window = Window::new("gl-util - draw_without_program").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    let mut vertex_array = VertexArray::new(&context, &VERTEX_POSITIONS[..]).unwrap();
+    vertex_array.set_attrib(
+        AttributeLocation::from_index(0),
+        AttribLayout { elements: 3, offset: 0, stride: 0 },
+    );
+
+    let result = DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles).draw();
+    assert_eq!(Err(DrawError::NoProgram), result);
+}
+
+#[test]
+fn draw_with_polygon_offset() {
+    let window = Window::new("gl-util - draw_with_polygon_offset").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    let mut vertex_array = VertexArray::new(&context, &VERTEX_POSITIONS[..]).unwrap();
+    vertex_array.set_attrib(
+        AttributeLocation::from_index(0),
+        AttribLayout { elements: 3, offset: 0, stride: 0 },
+    );
+
+    let vert_shader = Shader::new(&context, VERT_SOURCE, ShaderType::Vertex).unwrap();
+    let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
+    let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
+
+    // Draw the solid triangle, then draw its wireframe offset toward the camera.
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .depth_test(Comparison::LessThanOrEqual)
+        .draw()
+        .unwrap();
+
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .depth_test(Comparison::LessThanOrEqual)
+        .polygon_mode(PolygonMode::Line)
+        .polygon_offset(-1.0, -1.0)
+        .draw()
+        .unwrap();
+
+    // Drawing again without an offset disables it.
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .draw()
+        .unwrap();
+}
+
+#[test]
+fn draw_with_scissor_restores_context_scissor() {
+    let window = Window::new("gl-util - draw_with_scissor_restores_context_scissor").unwrap();
+    let context = Context::from_window(&window).unwrap();
+
+    let mut vertex_array = VertexArray::new(&context, &VERTEX_POSITIONS[..]).unwrap();
+    vertex_array.set_attrib(
+        AttributeLocation::from_index(0),
+        AttribLayout { elements: 3, offset: 0, stride: 0 },
+    );
+
+    let vert_shader = Shader::new(&context, VERT_SOURCE, ShaderType::Vertex).unwrap();
+    let frag_shader = Shader::new(&context, FRAG_SOURCE, ShaderType::Fragment).unwrap();
+    let program = Program::new(&context, &[vert_shader, frag_shader]).unwrap();
+
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .scissor(10, 10, 20, 20)
+        .draw()
+        .unwrap();
+    assert_eq!(None, context.scissor());
+
+    let context_scissor = Rect::new(0, 0, 50, 50);
+    context.set_scissor(Some(context_scissor));
+    DrawBuilder::new(&context, &vertex_array, DrawMode::Triangles)
+        .program(&program)
+        .scissor(10, 10, 20, 20)
+        .draw()
+        .unwrap();
+    assert_eq!(Some(context_scissor), context.scissor());
+}
@@ -2,9 +2,7 @@ use std::convert::AsRef;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
-use std::mem;
 use std::path::Path;
-use std::slice;
 
 #[derive(Debug, Clone)]
 pub struct Bitmap {
@@ -32,16 +30,13 @@ impl Bitmap {
     /// Parses a byte array representing a bitmap file.
     pub fn from_bytes(bytes: &[u8]) -> Result<Bitmap, Error> {
         // Extract the headers to get information about the bitmap.
-        let file_header = {
-            let ptr = bytes.as_ptr() as *const FileHeader;
-            unsafe { &*ptr }
-        };
+        let mut reader = ByteReader::new(bytes);
+        let file_header = FileHeader::read(&mut reader)?;
+        if file_header.file_type != BITMAP_FILE_TYPE {
+            return Err(Error::NotABitmap);
+        }
 
-        let info_header = {
-            let offset = mem::size_of::<FileHeader>() as isize;
-            let ptr = unsafe { bytes.as_ptr().offset(offset) } as *const InfoHeader;
-            unsafe { &*ptr }
-        };
+        let info_header = &InfoHeader::read(&mut reader)?;
 
         // // Extract the color masks.
         // let color_masks = {
@@ -50,37 +45,163 @@ impl Bitmap {
         //     unsafe { slice::from_raw_parts(ptr as *const RgbQuad, 5) }
         // };
 
-        // Extract color data.
-        let image_data = {
-            let offset = file_header.data_offset as isize;
-            let ptr = unsafe { bytes.as_ptr().offset(offset) };
-            let byte_count = info_header.image_size as usize;
-            unsafe { slice::from_raw_parts(ptr, byte_count) }
-        };
+        if info_header.width < 0 {
+            return Err(Error::InvalidData);
+        }
+
+        // A negative height means the rows are stored top-down. We always return the rows
+        // bottom-up, so for top-down bitmaps the rows are read in reverse order.
+        let top_down = info_header.height < 0;
+        let width = info_header.width as usize;
+        let height = (info_header.height as i64).abs() as usize;
+        let row_index = |y: usize| if top_down { height - 1 - y } else { y };
 
         // Parse the raw data into a BitmapData structure.
         let data = match info_header.compression {
+            Compression::Rgb if info_header.bit_count <= 8 => {
+                let palette_offset = FILE_HEADER_SIZE + info_header.size as usize;
+                let palette = read_palette(bytes, palette_offset, info_header)?;
+
+                let bit_count = info_header.bit_count as usize;
+                if ![1, 2, 4, 8].contains(&bit_count) {
+                    return Err(Error::InvalidData);
+                }
+
+                // Each row is padded to a multiple of 4 bytes. `image_size` may be 0 for
+                // uncompressed bitmaps, so calculate the size from the dimensions instead.
+                let row_size = (width * bit_count + 31) / 32 * 4;
+                let data_start = file_header.data_offset as usize;
+                let image_data = bytes
+                    .get(data_start..data_start + row_size * height)
+                    .ok_or(Error::InvalidData)?;
+
+                let mut data = Vec::with_capacity(width * height);
+                for y in 0..height {
+                    let row_start = row_index(y) * row_size;
+                    let row = &image_data[row_start..row_start + row_size];
+                    for x in 0..width {
+                        // Pixels are packed starting with the most significant bits of each byte.
+                        let bit_offset = x * bit_count;
+                        let shift = 8 - bit_count - bit_offset % 8;
+                        let index = (row[bit_offset / 8] >> shift) & ((1 << bit_count) - 1) as u8;
+
+                        let color = palette.get(index as usize).ok_or(Error::InvalidData)?;
+                        data.push((color.blue, color.green, color.red));
+                    }
+                }
+
+                BitmapData::Bgr(data)
+            },
+            Compression::Rgb if info_header.bit_count == 16 => {
+                // Uncompressed 16 bit bitmaps use 5 bits for each channel, with the top bit unused.
+                let masks = [
+                    ChannelMask::new(0x7C00),
+                    ChannelMask::new(0x03E0),
+                    ChannelMask::new(0x001F),
+                    ChannelMask::new(0),
+                ];
+                let data_start = file_header.data_offset as usize;
+                decode_masked(bytes, data_start, width, height, top_down, 16, masks)?
+            },
+            Compression::Rgb if info_header.bit_count == 32 => {
+                // Uncompressed 32 bit bitmaps store BGR with the high byte unused, so they're
+                // treated as fully opaque.
+                let masks = [
+                    ChannelMask::new(0x00FF0000),
+                    ChannelMask::new(0x0000FF00),
+                    ChannelMask::new(0x000000FF),
+                    ChannelMask::new(0),
+                ];
+                let data_start = file_header.data_offset as usize;
+                decode_masked(bytes, data_start, width, height, top_down, 32, masks)?
+            },
             Compression::Rgb => {
-                assert!(image_data.len() % 3 == 0, "Rgb image data must have a byte count multiple of 3");
-
-                // Convert slice.
-                let ptr = image_data.as_ptr() as *const (u8, u8, u8);
-                let len = image_data.len() / 3;
-                let data = unsafe { slice::from_raw_parts(ptr, len) };
-
-                BitmapData::Bgr(data.into())
+                if info_header.bit_count != 24 {
+                    return Err(Error::InvalidData);
+                }
+
+                // Each row is padded to a multiple of 4 bytes.
+                let row_size = (width * 3 + 3) / 4 * 4;
+                let data_start = file_header.data_offset as usize;
+                let image_data = bytes
+                    .get(data_start..data_start + row_size * height)
+                    .ok_or(Error::InvalidData)?;
+
+                let mut data = Vec::with_capacity(width * height);
+                for y in 0..height {
+                    let row_start = row_index(y) * row_size;
+                    let row = &image_data[row_start..row_start + width * 3];
+                    data.extend(row.chunks(3).map(|pixel| (pixel[0], pixel[1], pixel[2])));
+                }
+
+                BitmapData::Bgr(data)
+            },
+            Compression::Rle8 | Compression::Rle4 => {
+                // The bit count determines the size of the color table, so it has to match the
+                // compression before the table can be read.
+                let expected_bit_count = if info_header.compression == Compression::Rle8 { 8 } else { 4 };
+                if info_header.bit_count != expected_bit_count {
+                    return Err(Error::InvalidData);
+                }
+
+                let palette_offset = FILE_HEADER_SIZE + info_header.size as usize;
+                let palette = read_palette(bytes, palette_offset, info_header)?;
+
+                let data_start = file_header.data_offset as usize;
+                let data_end = data_start + info_header.image_size as usize;
+                let image_data = bytes.get(data_start..data_end).ok_or(Error::CorruptRleStream)?;
+
+                // Compressed bitmaps are always bottom-up.
+                if top_down {
+                    return Err(Error::CorruptRleStream);
+                }
+
+                let indices = decode_rle(image_data, info_header.compression, width, height)?;
+
+                let mut data = Vec::with_capacity(indices.len());
+                for index in indices {
+                    let color = palette.get(index as usize).ok_or(Error::CorruptRleStream)?;
+                    data.push((color.blue, color.green, color.red));
+                }
+
+                BitmapData::Bgr(data)
+            },
+            Compression::Bitfields => {
+                let bit_count = info_header.bit_count as usize;
+                if bit_count != 16 && bit_count != 32 {
+                    return Err(Error::InvalidData);
+                }
+
+                // The color masks immediately follow the 40 byte info header. Newer header
+                // versions include them as part of the header, along with an alpha mask.
+                let red = ChannelMask::new(reader.read_u32()?);
+                let green = ChannelMask::new(reader.read_u32()?);
+                let blue = ChannelMask::new(reader.read_u32()?);
+                let alpha = if info_header.size >= 56 {
+                    ChannelMask::new(reader.read_u32()?)
+                } else {
+                    ChannelMask::new(0)
+                };
+
+                let masks = [red.mask, green.mask, blue.mask, alpha.mask];
+                for (index, first) in masks.iter().enumerate() {
+                    if masks[index + 1..].iter().any(|second| first & second != 0) {
+                        return Err(Error::InvalidBitfieldMask);
+                    }
+                }
+
+                let data_start = file_header.data_offset as usize;
+                decode_masked(bytes, data_start, width, height, top_down, bit_count, [red, green, blue, alpha])?
+            },
+            Compression::Jpeg | Compression::Png => {
+                return Err(Error::UnsupportedCompression(info_header.compression));
             },
-            Compression::Rle8 => unimplemented!(),
-            Compression::Rle4 => unimplemented!(),
-            Compression::Bitfields => unimplemented!(),
-            Compression::Jpeg => unimplemented!(),
-            Compression::Png => unimplemented!(),
         };
 
         // Creat the bitmap from the parsed data.
         Ok(Bitmap {
-            width: info_header.width as usize,
-            height: info_header.height as usize,
+            width: width,
+            height: height,
             compression: info_header.compression,
             bit_count: info_header.bit_count as usize,
             data: data,
@@ -105,6 +226,11 @@ impl Bitmap {
         &self.data
     }
 
+    /// Consumes the bitmap, returning its pixel data without copying it.
+    pub fn into_data(self) -> BitmapData {
+        self.data
+    }
+
     /// The compression used by the bitmap.
     ///
     /// This determines the format of the data yielded by `data()`.
@@ -116,6 +242,26 @@ impl Bitmap {
 #[derive(Debug)]
 pub enum Error {
     IoError(io::Error),
+
+    /// Indicates that the file doesn't start with the "BM" signature, so it's either not a bitmap
+    /// or it's one of the OS/2 variants, which aren't supported.
+    NotABitmap,
+
+    /// Indicates that the file is inconsistent with its headers, e.g. the file is shorter than the
+    /// headers say or a pixel uses a color that isn't in the color table.
+    InvalidData,
+
+    /// Indicates that the bitmap uses a compression that can't be decoded, i.e. it embeds a JPEG
+    /// or PNG image.
+    UnsupportedCompression(Compression),
+
+    /// Indicates that the color masks for a `Bitfields` bitmap overlap, so a bit in the pixel data
+    /// would belong to more than one channel.
+    InvalidBitfieldMask,
+
+    /// Indicates that the data for a run-length encoded bitmap is malformed, e.g. a run extends
+    /// past the end of a row or the stream ends in the middle of a run.
+    CorruptRleStream,
 }
 
 impl From<io::Error> for Error {
@@ -124,6 +270,274 @@ impl From<io::Error> for Error {
     }
 }
 
+/// Reads the color table for an indexed bitmap.
+fn read_palette(
+    bytes: &[u8],
+    offset: usize,
+    info_header: &InfoHeader,
+) -> Result<Vec<RgbQuad>, Error> {
+    let num_colors = if info_header.colors_used != 0 {
+        info_header.colors_used as usize
+    } else {
+        1 << info_header.bit_count
+    };
+
+    let palette_bytes = bytes
+        .get(offset..offset + num_colors * 4)
+        .ok_or(Error::InvalidData)?;
+
+    let palette = palette_bytes
+        .chunks(4)
+        .map(|quad| RgbQuad {
+            blue: quad[0],
+            green: quad[1],
+            red: quad[2],
+            reserved: quad[3],
+        })
+        .collect();
+
+    Ok(palette)
+}
+
+/// Decodes 16 or 32 bit pixel data using a color mask for each channel.
+///
+/// `masks` holds the red, green, blue, and alpha masks in that order. Pixels are fully opaque if
+/// the alpha mask is 0.
+fn decode_masked(
+    bytes: &[u8],
+    data_start: usize,
+    width: usize,
+    height: usize,
+    top_down: bool,
+    bit_count: usize,
+    masks: [ChannelMask; 4],
+) -> Result<BitmapData, Error> {
+    let (red, green, blue, alpha) = (masks[0], masks[1], masks[2], masks[3]);
+
+    // Each row is padded to a multiple of 4 bytes.
+    let bytes_per_pixel = bit_count / 8;
+    let row_size = (width * bytes_per_pixel + 3) / 4 * 4;
+    let image_data = bytes
+        .get(data_start..data_start + row_size * height)
+        .ok_or(Error::InvalidData)?;
+
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row_index = if top_down { height - 1 - y } else { y };
+        let row_start = row_index * row_size;
+        let mut row = ByteReader::new(&image_data[row_start..row_start + row_size]);
+        for _ in 0..width {
+            let pixel = if bit_count == 16 {
+                row.read_u16()? as u32
+            } else {
+                row.read_u32()?
+            };
+
+            // Bitmaps without an alpha mask are fully opaque.
+            let alpha = if alpha.mask == 0 { 255 } else { alpha.extract(pixel) };
+            data.push((blue.extract(pixel), green.extract(pixel), red.extract(pixel), alpha));
+        }
+    }
+
+    Ok(BitmapData::Bgra(data))
+}
+
+/// The most pixels that a single byte of RLE data is allowed to describe.
+///
+/// A 4 byte delta escape can skip up to 255 rows, so a stream can legitimately describe far more
+/// pixels than it has bytes. This limit is generous enough for real bitmaps while still rejecting
+/// headers whose dimensions have no relation to the size of the compressed data.
+const MAX_RLE_PIXELS_PER_BYTE: usize = 255 * 255;
+
+/// Decompresses RLE8 or RLE4 image data into a list of color indices.
+///
+/// The indices are in the same order as uncompressed bitmap data, i.e. starting with the bottom
+/// row. Pixels that are skipped with a delta escape, or that are never written because the stream
+/// ends early, have index 0.
+///
+/// For details on the encoding see
+/// [Bitmap Compression](https://msdn.microsoft.com/en-us/library/dd183383(v=vs.85).aspx).
+fn decode_rle(
+    data: &[u8],
+    compression: Compression,
+    width: usize,
+    height: usize,
+) -> Result<Vec<u8>, Error> {
+    let is_rle4 = compression == Compression::Rle4;
+
+    // The dimensions come straight from the header, so make sure they're plausible for the amount
+    // of data before allocating the decompressed image.
+    let pixel_count = width.checked_mul(height).ok_or(Error::CorruptRleStream)?;
+    if pixel_count > data.len().saturating_mul(MAX_RLE_PIXELS_PER_BYTE) {
+        return Err(Error::CorruptRleStream);
+    }
+
+    let mut indices = vec![0; pixel_count];
+    let mut x = 0;
+    let mut y = 0;
+
+    // Writes the next pixel in the current row, failing if the row is already full.
+    let mut write_pixel = |x: &mut usize, y: usize, index: u8| -> Result<(), Error> {
+        if *x >= width || y >= height {
+            return Err(Error::CorruptRleStream);
+        }
+
+        indices[y * width + *x] = index;
+        *x += 1;
+        Ok(())
+    };
+
+    let mut bytes = data.iter().cloned();
+    let mut next_byte = || bytes.next().ok_or(Error::CorruptRleStream);
+
+    loop {
+        // A stream that ends without an end-of-bitmap escape is treated as if it had one.
+        let count = match next_byte() {
+            Ok(count) => count,
+            Err(_) => break,
+        };
+        let value = next_byte()?;
+
+        if count > 0 {
+            // Encoded mode: Repeat the color index (or for RLE4 the pair of indices) `count`
+            // times.
+            for pixel in 0..count {
+                let index = if !is_rle4 {
+                    value
+                } else if pixel % 2 == 0 {
+                    value >> 4
+                } else {
+                    value & 0x0F
+                };
+
+                write_pixel(&mut x, y, index)?;
+            }
+
+            continue;
+        }
+
+        match value {
+            // End of line.
+            0 => {
+                x = 0;
+                y += 1;
+            },
+
+            // End of bitmap.
+            1 => break,
+
+            // Delta, move the current position right and up.
+            2 => {
+                x += next_byte()? as usize;
+                y += next_byte()? as usize;
+                if x > width || y > height {
+                    return Err(Error::CorruptRleStream);
+                }
+            },
+
+            // Absolute mode: The next `value` indices are stored uncompressed, padded to a 16 bit
+            // boundary.
+            count => {
+                let byte_count = if is_rle4 { (count as usize + 1) / 2 } else { count as usize };
+
+                let mut pixel = 0;
+                for _ in 0..byte_count {
+                    let byte = next_byte()?;
+                    if is_rle4 {
+                        write_pixel(&mut x, y, byte >> 4)?;
+                        pixel += 1;
+
+                        if pixel < count {
+                            write_pixel(&mut x, y, byte & 0x0F)?;
+                            pixel += 1;
+                        }
+                    } else {
+                        write_pixel(&mut x, y, byte)?;
+                    }
+                }
+
+                if byte_count % 2 == 1 {
+                    next_byte()?;
+                }
+            },
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Reads little-endian values from a byte buffer.
+///
+/// Bitmap headers aren't aligned and are always little-endian, so each field has to be read
+/// byte-by-byte rather than by casting the buffer to a header struct.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader {
+            bytes: bytes,
+            offset: 0,
+        }
+    }
+
+    fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let bytes = self.bytes
+            .get(self.offset..self.offset + count)
+            .ok_or(Error::InvalidData)?;
+        self.offset += count;
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.read_bytes(2)?;
+        Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.read_bytes(4)?;
+        Ok(bytes[0] as u32
+            | (bytes[1] as u32) << 8
+            | (bytes[2] as u32) << 16
+            | (bytes[3] as u32) << 24)
+    }
+
+    fn read_i32(&mut self) -> Result<i32, Error> {
+        self.read_u32().map(|value| value as i32)
+    }
+}
+
+/// A color mask for one channel of a `Bitfields` bitmap.
+#[derive(Debug, Clone, Copy)]
+struct ChannelMask {
+    mask: u32,
+    shift: u32,
+    max: u32,
+}
+
+impl ChannelMask {
+    fn new(mask: u32) -> ChannelMask {
+        let shift = if mask == 0 { 0 } else { mask.trailing_zeros() };
+        ChannelMask {
+            mask: mask,
+            shift: shift,
+            max: mask >> shift,
+        }
+    }
+
+    /// Extracts the channel from `pixel`, scaled to the range of a `u8`.
+    fn extract(&self, pixel: u32) -> u8 {
+        if self.max == 0 {
+            return 0;
+        }
+
+        let value = (pixel & self.mask) >> self.shift;
+        (value as u64 * 255 / self.max as u64) as u8
+    }
+}
+
 /// Represents the possible data formats for a bitmap.
 #[derive(Debug, Clone)]
 pub enum BitmapData {
@@ -165,6 +579,20 @@ pub enum Compression {
     Png = 5,
 }
 
+impl Compression {
+    fn from_u32(value: u32) -> Result<Compression, Error> {
+        match value {
+            0 => Ok(Compression::Rgb),
+            1 => Ok(Compression::Rle8),
+            2 => Ok(Compression::Rle4),
+            3 => Ok(Compression::Bitfields),
+            4 => Ok(Compression::Jpeg),
+            5 => Ok(Compression::Png),
+            _ => Err(Error::InvalidData),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone)]
 pub struct RgbQuad {
@@ -174,11 +602,17 @@ pub struct RgbQuad {
     pub reserved: u8,
 }
 
-// TODO: Don't use #[repr(packed)] to load from the buffer, read members in a portable way.
-#[repr(C, packed)]
+/// The value of `FileHeader::file_type` for bitmap files, "BM" read as a little-endian `u16`.
+const BITMAP_FILE_TYPE: u16 = 0x4D42;
+
+/// The size of `FileHeader` in the file, which is smaller than the size of the struct in memory.
+const FILE_HEADER_SIZE: usize = 14;
+
+// Not every header field is used, but they're all read to document the file layout.
+#[allow(dead_code)]
 #[derive(Debug)]
 struct FileHeader {
-    /// The file type, must be BM (whatever that means).
+    /// The file type, must be BM (short for bitmap).
     pub file_type: u16,
 
     /// The file size in bytes.
@@ -195,7 +629,19 @@ struct FileHeader {
     pub data_offset: u32,
 }
 
-#[repr(C, packed)]
+impl FileHeader {
+    fn read(reader: &mut ByteReader) -> Result<FileHeader, Error> {
+        Ok(FileHeader {
+            file_type: reader.read_u16()?,
+            file_size: reader.read_u32()?,
+            reserved_1: reader.read_u16()?,
+            reserved_2: reader.read_u16()?,
+            data_offset: reader.read_u32()?,
+        })
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Debug)]
 struct InfoHeader {
     /// The number of bytes required by the structure (???).
@@ -245,9 +691,27 @@ struct InfoHeader {
     /// `bit_count` equals 16 or greater the `colors_used` member specifies the size of the color
     /// table used to optimize performance of the system color palettes. If `bit_count` equals 16
     /// or 32 the optimal color palette starts immediately following the three masks.
-    pub colors_used: u16,
+    pub colors_used: u32,
 
     /// The number of color indexes that are required for displaying the bitmap. If this value is
     /// zero all colors are required.
-    pub colors_important: u16,
+    pub colors_important: u32,
+}
+
+impl InfoHeader {
+    fn read(reader: &mut ByteReader) -> Result<InfoHeader, Error> {
+        Ok(InfoHeader {
+            size: reader.read_u32()?,
+            width: reader.read_i32()?,
+            height: reader.read_i32()?,
+            planes: reader.read_u16()?,
+            bit_count: reader.read_u16()?,
+            compression: Compression::from_u32(reader.read_u32()?)?,
+            image_size: reader.read_u32()?,
+            x_pixels_per_meter: reader.read_i32()?,
+            y_pixels_per_meter: reader.read_i32()?,
+            colors_used: reader.read_u32()?,
+            colors_important: reader.read_u32()?,
+        })
+    }
 }
@@ -0,0 +1,357 @@
+extern crate parse_bmp;
+
+use parse_bmp::*;
+
+/// Palette used by the test bitmaps, as (blue, green, red) tuples.
+static PALETTE: [(u8, u8, u8); 3] = [
+    (0, 0, 0),
+    (255, 0, 0),
+    (0, 255, 0),
+];
+
+/// Builds a bitmap file with the specified compression, using `PALETTE` as the color table.
+fn build_bitmap(compression: u32, bit_count: u16, width: i32, height: i32, image_data: &[u8]) -> Vec<u8> {
+    let palette_size = PALETTE.len() * 4;
+    let data_offset = 14 + 40 + palette_size;
+
+    let mut bytes = Vec::new();
+
+    // File header.
+    bytes.extend_from_slice(b"BM");
+    push_u32(&mut bytes, (data_offset + image_data.len()) as u32);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, data_offset as u32);
+
+    // Info header.
+    push_u32(&mut bytes, 40);
+    push_u32(&mut bytes, width as u32);
+    push_u32(&mut bytes, height as u32);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&[bit_count as u8, (bit_count >> 8) as u8]);
+    push_u32(&mut bytes, compression);
+    push_u32(&mut bytes, image_data.len() as u32);
+    push_u32(&mut bytes, 2835);
+    push_u32(&mut bytes, 2835);
+    push_u32(&mut bytes, PALETTE.len() as u32);
+    push_u32(&mut bytes, 0);
+
+    // Color table.
+    for &(blue, green, red) in &PALETTE {
+        bytes.extend_from_slice(&[blue, green, red, 0]);
+    }
+
+    bytes.extend_from_slice(image_data);
+    bytes
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]);
+}
+
+fn bgr_data(bitmap: &Bitmap) -> &[(u8, u8, u8)] {
+    match *bitmap.data() {
+        BitmapData::Bgr(ref data) => &*data,
+        ref data => panic!("Expected Bgr data, got {:?}", data),
+    }
+}
+
+#[test]
+fn decode_rle8() {
+    let image_data = [
+        // Bottom row: Two pixels of color 1 followed by the absolute run 2, 0, 1 (padded).
+        0x02, 0x01,
+        0x00, 0x03, 0x02, 0x00, 0x01, 0x00,
+        0x00, 0x00,
+
+        // Top row: Five pixels of color 2.
+        0x05, 0x02,
+        0x00, 0x01,
+    ];
+    let bytes = build_bitmap(1, 8, 5, 2, &image_data);
+
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+    assert_eq!(5, bitmap.width());
+    assert_eq!(2, bitmap.height());
+    assert_eq!(Compression::Rle8, bitmap.compression());
+
+    let (black, blue, green) = (PALETTE[0], PALETTE[1], PALETTE[2]);
+    assert_eq!(
+        &[
+            blue, blue, green, black, blue,
+            green, green, green, green, green,
+        ],
+        bgr_data(&bitmap));
+}
+
+#[test]
+fn decode_rle4() {
+    let image_data = [
+        // An encoded run alternating colors 1 and 2, then an absolute run of 0, 2, 1 (padded).
+        0x04, 0x12,
+        0x00, 0x03, 0x02, 0x10,
+        0x00, 0x01,
+    ];
+    let bytes = build_bitmap(2, 4, 7, 1, &image_data);
+
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+
+    let (black, blue, green) = (PALETTE[0], PALETTE[1], PALETTE[2]);
+    assert_eq!(&[blue, green, blue, green, black, green, blue], bgr_data(&bitmap));
+}
+
+#[test]
+fn corrupt_rle_stream() {
+    let corrupt_streams: [&[u8]; 4] = [
+        // Run extends past the end of the row.
+        &[0x06, 0x01],
+
+        // Stream ends in the middle of an absolute run.
+        &[0x00, 0x04, 0x01, 0x01],
+
+        // Delta moves past the top of the image.
+        &[0x00, 0x02, 0x00, 0x05],
+
+        // Color index isn't in the palette.
+        &[0x01, 0x07],
+    ];
+
+    for image_data in &corrupt_streams {
+        let bytes = build_bitmap(1, 8, 5, 2, image_data);
+        match Bitmap::from_bytes(&bytes) {
+            Err(Error::CorruptRleStream) => {},
+            result => panic!("Expected CorruptRleStream for {:?}, got {:?}", image_data, result),
+        }
+    }
+}
+
+#[test]
+fn rle_dimensions_too_large() {
+    // Dimensions that overflow, or that are far too large for the amount of compressed data, are
+    // rejected instead of allocating the decompressed image.
+    for &(width, height) in &[(0x7FFFFFFF, 0x7FFFFFFF), (100_000, 100_000)] {
+        let bytes = build_bitmap(1, 8, width, height, &[0x02, 0x01, 0x00, 0x01]);
+        match Bitmap::from_bytes(&bytes) {
+            Err(Error::CorruptRleStream) => {},
+            result => panic!("Expected CorruptRleStream for {}x{}, got {:?}", width, height, result),
+        }
+    }
+}
+
+#[test]
+fn rle_bit_count_mismatch() {
+    // RLE8 requires 8 bits per pixel and RLE4 requires 4, anything else is rejected before the
+    // color table is read.
+    for &(compression, bit_count) in &[(1, 4), (1, 200), (2, 8), (2, 200)] {
+        let bytes = build_bitmap(compression, bit_count, 5, 2, &[0x00, 0x01]);
+        match Bitmap::from_bytes(&bytes) {
+            Err(Error::InvalidData) => {},
+            result => panic!(
+                "Expected InvalidData for compression {} with bit count {}, got {:?}",
+                compression,
+                bit_count,
+                result),
+        }
+    }
+}
+
+/// Builds an uncompressed indexed bitmap, using `PALETTE` as the color table.
+fn indexed_bitmap(bit_count: u16, width: i32, height: i32, image_data: &[u8]) -> Vec<u8> {
+    let mut bytes = build_bitmap(0, bit_count, width, height, image_data);
+
+    // Uncompressed bitmaps may leave the image size as 0.
+    for byte in &mut bytes[34..38] {
+        *byte = 0;
+    }
+
+    bytes
+}
+
+#[test]
+fn decode_indexed() {
+    let (black, blue, green) = (PALETTE[0], PALETTE[1], PALETTE[2]);
+
+    // 8 bits per pixel, 3 pixels per row with each row padded to 4 bytes.
+    let bytes = indexed_bitmap(8, 3, 2, &[
+        0x01, 0x02, 0x00, 0xFF,
+        0x02, 0x02, 0x01, 0xFF,
+    ]);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+    assert_eq!(&[blue, green, black, green, green, blue], bgr_data(&bitmap));
+
+    // 4 bits per pixel, 5 pixels per row.
+    let bytes = indexed_bitmap(4, 5, 2, &[
+        0x12, 0x01, 0x20, 0x00,
+        0x00, 0x11, 0x10, 0x00,
+    ]);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        &[
+            blue, green, black, blue, green,
+            black, black, blue, blue, blue,
+        ],
+        bgr_data(&bitmap));
+
+    // 1 bit per pixel, 10 pixels per row.
+    let bytes = indexed_bitmap(1, 10, 1, &[0b1010_0000, 0b1100_0000, 0x00, 0x00]);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        &[blue, black, blue, black, black, black, black, black, blue, blue],
+        bgr_data(&bitmap));
+
+    // Color indices outside the color table are an error.
+    let bytes = indexed_bitmap(8, 1, 1, &[0x03, 0x00, 0x00, 0x00]);
+    match Bitmap::from_bytes(&bytes) {
+        Err(Error::InvalidData) => {},
+        result => panic!("Expected InvalidData, got {:?}", result),
+    }
+
+    // So is missing pixel data.
+    let bytes = indexed_bitmap(8, 4, 2, &[0x00, 0x00, 0x00, 0x00]);
+    match Bitmap::from_bytes(&bytes) {
+        Err(Error::InvalidData) => {},
+        result => panic!("Expected InvalidData, got {:?}", result),
+    }
+}
+
+#[test]
+fn decode_top_down() {
+    // A 2x2 24 bit bitmap stored top-down, with each 6 byte row padded to 8 bytes.
+    let image_data = [
+        // Top row.
+        1, 1, 1,  2, 2, 2,  0, 0,
+        // Bottom row.
+        3, 3, 3,  4, 4, 4,  0, 0,
+    ];
+
+    let bytes = build_bitmap(0, 24, 2, -2, &image_data);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+
+    assert_eq!(2, bitmap.width());
+    assert_eq!(2, bitmap.height());
+
+    // Rows are always returned bottom-up.
+    assert_eq!(
+        &[(3, 3, 3), (4, 4, 4), (1, 1, 1), (2, 2, 2)][..],
+        bgr_data(&bitmap));
+}
+
+#[test]
+fn decode_uncompressed_16_and_32_bit() {
+    fn bgra_data(bitmap: &Bitmap) -> &[(u8, u8, u8, u8)] {
+        match *bitmap.data() {
+            BitmapData::Bgra(ref data) => &*data,
+            ref data => panic!("Expected Bgra data, got {:?}", data),
+        }
+    }
+
+    // 16 bits per pixel as 5-5-5, 1 pixel per row with each row padded to 4 bytes.
+    let bytes = indexed_bitmap(16, 1, 2, &[
+        // Pure red.
+        0x00, 0x7C, 0x00, 0x00,
+        // Pure blue.
+        0x1F, 0x00, 0x00, 0x00,
+    ]);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+    assert_eq!(&[(0, 0, 255, 255), (255, 0, 0, 255)][..], bgra_data(&bitmap));
+
+    // 32 bits per pixel, the high byte is unused so pixels are opaque.
+    let bytes = indexed_bitmap(32, 2, 1, &[10, 20, 30, 0,  40, 50, 60, 99]);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+    assert_eq!(&[(10, 20, 30, 255), (40, 50, 60, 255)][..], bgra_data(&bitmap));
+
+    // Bit counts that uncompressed bitmaps can't have are rejected rather than panicking.
+    let bytes = indexed_bitmap(12, 1, 1, &[0, 0, 0, 0]);
+    match Bitmap::from_bytes(&bytes) {
+        Err(Error::InvalidData) => {},
+        result => panic!("Expected InvalidData, got {:?}", result),
+    }
+}
+
+#[test]
+fn not_a_bitmap() {
+    let mut bytes = build_bitmap(0, 24, 1, 1, &[1, 2, 3, 0]);
+    bytes[0] = b'X';
+
+    match Bitmap::from_bytes(&bytes) {
+        Err(Error::NotABitmap) => {},
+        result => panic!("Expected NotABitmap, got {:?}", result),
+    }
+
+    // Buffers too short to hold the headers are rejected rather than read out of bounds.
+    match Bitmap::from_bytes(b"BM") {
+        Err(Error::InvalidData) => {},
+        result => panic!("Expected InvalidData, got {:?}", result),
+    }
+}
+
+/// Builds a 32 bit `Bitfields` bitmap using a 56 byte info header, which includes the red, green,
+/// blue, and alpha masks.
+fn bitfield_bitmap(masks: [u32; 4], width: i32, height: i32, image_data: &[u8]) -> Vec<u8> {
+    let data_offset = 14 + 56;
+
+    let mut bytes = Vec::new();
+
+    // File header.
+    bytes.extend_from_slice(b"BM");
+    push_u32(&mut bytes, (data_offset + image_data.len()) as u32);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, data_offset as u32);
+
+    // Info header.
+    push_u32(&mut bytes, 56);
+    push_u32(&mut bytes, width as u32);
+    push_u32(&mut bytes, height as u32);
+    bytes.extend_from_slice(&[1, 0, 32, 0]);
+    push_u32(&mut bytes, 3);
+    push_u32(&mut bytes, image_data.len() as u32);
+    push_u32(&mut bytes, 2835);
+    push_u32(&mut bytes, 2835);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+    for &mask in &masks {
+        push_u32(&mut bytes, mask);
+    }
+
+    bytes.extend_from_slice(image_data);
+    bytes
+}
+
+#[test]
+fn decode_bitfields() {
+    let masks = [0x00FF0000, 0x0000FF00, 0x000000FF, 0xFF000000];
+    let image_data = [
+        // Bottom row.
+        10, 20, 30, 255,  40, 50, 60, 128,
+        // Top row.
+        0, 0, 255, 0,  255, 255, 255, 255,
+    ];
+
+    let bytes = bitfield_bitmap(masks, 2, 2, &image_data);
+    let bitmap = Bitmap::from_bytes(&bytes).unwrap();
+
+    match *bitmap.data() {
+        BitmapData::Bgra(ref data) => {
+            assert_eq!(
+                &[(10, 20, 30, 255), (40, 50, 60, 128), (0, 0, 255, 0), (255, 255, 255, 255)][..],
+                &**data);
+        },
+        ref data => panic!("Expected Bgra data, got {:?}", data),
+    }
+
+    // Masks that share bits are rejected.
+    let bytes = bitfield_bitmap([0x00FF0000, 0x0001FF00, 0x000000FF, 0xFF000000], 2, 2, &image_data);
+    match Bitmap::from_bytes(&bytes) {
+        Err(Error::InvalidBitfieldMask) => {},
+        result => panic!("Expected InvalidBitfieldMask, got {:?}", result),
+    }
+}
+
+#[test]
+fn unsupported_compression() {
+    let bytes = build_bitmap(5, 0, 1, 1, &[0x89, b'P', b'N', b'G']);
+
+    match Bitmap::from_bytes(&bytes) {
+        Err(Error::UnsupportedCompression(Compression::Png)) => {},
+        result => panic!("Expected UnsupportedCompression, got {:?}", result),
+    }
+}
@@ -1,14 +1,18 @@
+use std::io::BufRead;
+use std::ops::Range;
 use std::path::Path;
 use std::slice;
 use std::str::FromStr;
 
+pub mod mtl;
+
 pub type Point = (f32, f32, f32, f32);
 pub type Vector3 = (f32, f32, f32);
 
 /// A parsed OBJ file.
 ///
 /// TODO: Add a specialized implementation for `Debug` that does a better job pretty printing.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Obj {
     positions: Vec<Point>,
     position_indices: Vec<Vec<usize>>,
@@ -16,215 +20,147 @@ pub struct Obj {
     texcoord_indices: Vec<Vec<usize>>,
     normals: Vec<Vector3>,
     normal_indices: Vec<Vec<usize>>,
+    smoothing_groups: Vec<u32>,
+    material_names: Vec<Option<String>>,
+    material_libs: Vec<String>,
+    groups: Vec<Group>,
+
+    /// Whether the document contained any `s` directives.
+    has_smoothing_groups: bool,
 }
 
 impl Obj {
+    /// Loads and parses the OBJ file at `path`.
+    ///
+    /// The file is parsed as it's read, see `from_reader()` for more information.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Obj, Error> {
         use std::fs::File;
-        use std::io::prelude::*;
-
-        let mut file = try!(File::open(path));
-        let mut text = String::new();
-
-        try!(file.read_to_string(&mut text));
+        use std::io::BufReader;
 
-        Obj::from_str(&text)
+        let file = try!(File::open(path));
+        Obj::from_reader(BufReader::new(file))
     }
 
-    pub fn from_str(file_text: &str) -> Result<Obj, Error> {
-        /// Pulls the next token and parses it as an `f32`.
-        fn pull_f32(tokens: &mut Iterator<Item=&str>) -> Result<f32, Error> {
-            let token = try!(tokens.next().ok_or(Error::MissingElement));
-            let value = try!(f32::from_str(token));
-            Ok(value)
-        }
-
-        /// Parses the next token as `f32` or returns `None`.
-        ///
-        /// Returns `Ok(None)` if no tokens are left in `tokens`, but will treat an empty token as
-        /// an error.
-        fn pull_option_f32(tokens: &mut Iterator<Item=&str>) -> Result<Option<f32>, Error> {
-            match tokens.next() {
-                Some(token) => {
-                    let value = try!(f32::from_str(token));
-                    Ok(Some(value))
-                },
-                None => {
-                    Ok(None)
-                }
+    /// Parses an OBJ document from `reader` one line at a time.
+    ///
+    /// Unlike `from_str()` the document is never loaded into memory all at once, only a single
+    /// line is buffered at a time. This is useful for very large documents where reading the
+    /// whole file up front would require a large allocation.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<Obj, Error> {
+        let mut builder = ObjBuilder::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let bytes_read = try!(reader.read_line(&mut line).map_err(|error| Error {
+                line: builder.current_line + 1,
+                kind: ErrorKind::IoError(error),
+            }));
+
+            if bytes_read == 0 {
+                break;
             }
-        }
 
-        /// Parses the next token as 'usize', returning empty tokens as `None`.
-        fn pull_option_usize(tokens: &mut Iterator<Item=&str>) -> Result<Option<usize>, Error> {
-            let token = try!(tokens.next().ok_or(Error::MissingElement));
-            if token == "" {
-                Ok(None)
-            } else {
-                let value = try!(usize::from_str(token));
-                Ok(Some(value))
-            }
+            // Strip the line ending the same way `str::lines()` does.
+            let text = line.trim_end_matches('\n').trim_end_matches('\r');
+            try!(builder.parse_line(text));
         }
 
-        let mut positions = Vec::new();
-        let mut position_indices = Vec::new();
-        let mut texcoords = Vec::new();
-        let mut texcoord_indices = Vec::new();
-        let mut normals = Vec::new();
-        let mut normal_indices = Vec::new();
+        builder.build()
+    }
 
+    pub fn from_str(file_text: &str) -> Result<Obj, Error> {
+        let mut builder = ObjBuilder::new();
         for line in file_text.lines() {
-            let mut tokens = line.split_whitespace();
-            let line_beginning = match tokens.next() {
-                Some(token) => token,
-                None => continue, // Line is empty, skip it.
-            };
+            try!(builder.parse_line(line));
+        }
 
-            match line_beginning {
-                // Vertex position data.
-                "v" => {
-                    let x = try!(pull_f32(&mut tokens));
-                    let y = try!(pull_f32(&mut tokens));
-                    let z = try!(pull_f32(&mut tokens));
-                    let w = try!(pull_option_f32(&mut tokens)).unwrap_or(1.0);
-
-                    positions.push((x, y, z, w));
-                },
-
-                // Vertex texcoord data.
-                "vt" => {
-                    let u = try!(pull_f32(&mut tokens));
-                    let v = try!(pull_option_f32(&mut tokens)).unwrap_or(0.0);
-                    let w = try!(pull_option_f32(&mut tokens)).unwrap_or(0.0);
-
-                    texcoords.push((u, v, w));
-                },
-
-                // Vertex normal data.
-                "vn" => {
-                    let x = try!(pull_f32(&mut tokens));
-                    let y = try!(pull_f32(&mut tokens));
-                    let z = try!(pull_f32(&mut tokens));
-
-                    normals.push((x, y, z));
-                },
-
-                // Indices for the face.
-                "f" => {
-                    let mut face_positions = Vec::new();
-                    let mut face_texcoords = Vec::new();
-                    let mut face_normals = Vec::new();
-
-                    for vertex_str in tokens {
-                        let mut index_tokens = vertex_str.split('/');
-
-                        // Position index.
-                        if let Some(index) = try!(pull_option_usize(&mut index_tokens)) {
-                            face_positions.push(index - 1);
-                        }
-
-                        // Texcoord index.
-                        if let Some(index) = try!(pull_option_usize(&mut index_tokens)) {
-                            face_texcoords.push(index - 1);
-                        }
-
-                        // Normal index.
-                        if let Some(index) = try!(pull_option_usize(&mut index_tokens)) {
-                            face_normals.push(index - 1);
-                        }
-                    }
+        builder.build()
+    }
 
-                    if face_texcoords.len() != 0 {
-                        // The face has texcoord indices. There must be exactly one for each
-                        // vertex or it's an error.
-                        if face_texcoords.len() != face_positions.len() {
-                            return Err(Error::MismatchedIndexData);
-                        }
+    /// Loads and parses the OBJ file at `path` using `threads` worker threads.
+    ///
+    /// See `from_string_parallel()` for more information.
+    pub fn from_file_parallel<P: AsRef<Path>>(path: P, threads: usize) -> Result<Obj, Error> {
+        use std::fs::File;
+        use std::io::prelude::*;
 
-                        // Add face texcoords to the texcoords list.
-                        texcoord_indices.push(face_texcoords);
-                    }
+        let mut file = try!(File::open(path));
+        let mut text = String::new();
 
-                    if face_normals.len() != 0 {
-                        // The face has normal indices. There must be exactly one for each vertex
-                        // or it's an error.
-                        if face_normals.len() != face_positions.len() {
-                            return Err(Error::MismatchedIndexData);
-                        }
+        try!(file.read_to_string(&mut text));
 
-                        // Add face normals to the normals list.
-                        normal_indices.push(face_normals);
-                    }
+        Obj::from_string_parallel(text, threads)
+    }
 
-                    // All vertices must have position data.
-                    if face_positions.len() == 0 {
-                        return Err(Error::MissingPositionIndex);
-                    }
+    /// Parses the OBJ document using `threads` worker threads.
+    ///
+    /// The document is split into `threads` line-aligned chunks which are parsed concurrently,
+    /// then the resulting vertex and face data is merged back together in document order. The
+    /// resulting `Obj` is identical to the one produced by `from_str()`, so this is only a
+    /// performance optimization for very large documents. For small documents the overhead of
+    /// spawning the worker threads will likely outweigh any gains.
+    ///
+    /// # Panics
+    ///
+    /// - If `threads` is 0.
+    pub fn from_string_parallel(file_text: String, threads: usize) -> Result<Obj, Error> {
+        use std::sync::Arc;
+        use std::thread;
+
+        assert!(threads > 0, "Must use at least one thread to parse an OBJ document");
+
+        // Determine the byte ranges for each chunk, moving the end of each chunk forward to the
+        // next line break so that no line is split between two chunks.
+        let mut ranges = Vec::with_capacity(threads);
+        {
+            let bytes = file_text.as_bytes();
+            let chunk_len = bytes.len() / threads + 1;
+            let mut chunk_start = 0;
+            let mut lines_before = 0;
+            while chunk_start < bytes.len() {
+                let mut chunk_end = ::std::cmp::min(chunk_start + chunk_len, bytes.len());
+                while chunk_end < bytes.len() && bytes[chunk_end - 1] != b'\n' {
+                    chunk_end += 1;
+                }
 
-                    position_indices.push(face_positions);
-                },
-
-                // TODO: Handle the case where there is no space between the '#' and the rest of
-                // the comment (e.g. "#blah blah").
-                "#" => {},
-
-                // TODO: Implement these other directives.
-                // TODO: Warn about unimplemented directives.
-                "g" => {},
-                "s" => {},
-                "vp" => {},
-                "p" => {},
-                "l" => {},
-                "o" => {},
-                "mg" => {},
-                "cstype" => {},
-                "deg" => {},
-                "bmat" => {},
-                "step" => {},
-                "curv" => {},
-                "curv2" => {},
-                "surv" => {},
-                "parm" => {},
-                "trim" => {},
-                "hole" => {},
-                "scrv" => {},
-                "sp" => {},
-                "end" => {},
-                "con" => {},
-                "bevel" => {},
-                "c_interp" => {},
-                "d_interp" => {},
-                "lod" => {},
-                "usemtl" => {},
-                "shadow_obj" => {},
-                "trace_obj" => {},
-                "ctech" => {},
-                "stech" => {},
-
-                _ => {
-                    return Err(Error::UnrecognizedDirective(line_beginning.into()));
-                },
+                ranges.push((chunk_start, chunk_end, lines_before));
+                lines_before += bytes[chunk_start..chunk_end]
+                    .iter()
+                    .filter(|&&byte| byte == b'\n')
+                    .count();
+                chunk_start = chunk_end;
             }
         }
 
-        // Check that either all of the faces of texcoords or none do.
-        if texcoord_indices.len() != 0 && texcoord_indices.len() != position_indices.len() {
-            return Err(Error::MismatchedFaceData);
-        }
+        // Parse each chunk on its own thread.
+        let file_text = Arc::new(file_text);
+        let workers = ranges
+            .into_iter()
+            .map(|(chunk_start, chunk_end, lines_before)| {
+                let file_text = file_text.clone();
+                thread::spawn(move || -> Result<ObjBuilder, Error> {
+                    // Start counting lines from the beginning of the chunk so that errors report
+                    // the line number within the whole document.
+                    let mut builder = ObjBuilder::new();
+                    builder.current_line = lines_before;
+                    for line in file_text[chunk_start..chunk_end].lines() {
+                        try!(builder.parse_line(line));
+                    }
 
-        // Check that either all of the faces of normals or none do.
-        if normal_indices.len() != 0 && normal_indices.len() != position_indices.len() {
-            return Err(Error::MismatchedFaceData);
+                    Ok(builder)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Merge the chunks in document order. Face indices in an OBJ file are absolute, so the
+        // data for each chunk can simply be appended to the data from the previous chunks.
+        let mut merged = ObjBuilder::new();
+        for worker in workers {
+            let chunk = try!(worker.join().expect("OBJ parsing thread panicked"));
+            merged.append(chunk);
         }
 
-        Ok(Obj {
-            positions: positions,
-            position_indices: position_indices,
-            texcoords: texcoords,
-            texcoord_indices: texcoord_indices,
-            normals: normals,
-            normal_indices: normal_indices,
-        })
+        merged.build()
     }
 
     /// Gets the list of vertex position tuples.
@@ -301,6 +237,221 @@ impl Obj {
         &*self.normal_indices
     }
 
+    /// Gets the smoothing group for each face.
+    ///
+    /// Faces in smoothing group 0 (declared with `s off` or `s 0`) are not smoothed. Faces before
+    /// any `s` directive are in group 0.
+    pub fn smoothing_groups(&self) -> &[u32] {
+        &*self.smoothing_groups
+    }
+
+    /// Gets the name of the material used by each face.
+    ///
+    /// The material for a face is set by the most recent `usemtl` directive. Faces before any
+    /// `usemtl` directive have no material and are `None`.
+    pub fn material_names(&self) -> &[Option<String>] {
+        &*self.material_names
+    }
+
+    /// Gets the paths of the MTL libraries referenced with `mtllib`, in the order they appear.
+    ///
+    /// The paths are exactly as written in the OBJ file, so they're relative to the directory
+    /// containing the OBJ file. See `mtl::MaterialLibrary` for loading them.
+    pub fn material_libs(&self) -> &[String] {
+        &*self.material_libs
+    }
+
+    /// Gets the groups of faces declared with the `g` and `o` directives.
+    ///
+    /// Each face belongs to the group declared by the most recent `g` or `o` directive. Faces
+    /// before any group directive are put into an implicit group named `"default"`. Groups are
+    /// listed in the order they appear in the file, and groups with no faces are omitted.
+    pub fn groups(&self) -> &[Group] {
+        &*self.groups
+    }
+
+    /// Creates a copy of the mesh with vertex normals computed from the face geometry.
+    ///
+    /// Does nothing if the mesh already has normals. Otherwise a normal is computed for each face
+    /// and assigned to its vertices according to the face's smoothing group:
+    ///
+    /// - Vertices shared by faces in the same smoothing group are given the average of the normals
+    ///   of those faces, so the edges between them are smooth.
+    /// - Faces in smoothing group 0 are flat, and each of their vertices gets the face's normal.
+    ///
+    /// A position shared between faces that shouldn't be smoothed together gets a separate normal
+    /// for each, so hard edges stay hard. If the document doesn't use smoothing groups at all then
+    /// all faces are smoothed together. Degenerate faces don't contribute to the averaged normals.
+    pub fn with_generated_normals(&self) -> Obj {
+        use std::collections::HashMap;
+
+        let mut obj = self.clone();
+        if obj.normals.len() != 0 {
+            return obj;
+        }
+
+        /// Identifies a unique vertex normal.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        enum NormalKey {
+            /// A vertex of a flat face.
+            Flat { face: usize, position: usize },
+
+            /// A vertex shared by all faces in a smoothing group.
+            Smooth { group: u32, position: usize },
+        }
+
+        let mut normal_map = HashMap::new();
+        for (face_index, face) in obj.position_indices.iter().enumerate() {
+            // Without any smoothing groups every face is treated as being in the same group.
+            let group = if obj.has_smoothing_groups { obj.smoothing_groups[face_index] } else { 1 };
+            let face_normal = face_normal(&obj.positions, face);
+
+            let mut face_normals = Vec::with_capacity(face.len());
+            for &position in face {
+                let key = if group == 0 {
+                    NormalKey::Flat { face: face_index, position: position }
+                } else {
+                    NormalKey::Smooth { group: group, position: position }
+                };
+
+                let next_index = obj.normals.len();
+                let normal_index = *normal_map.entry(key).or_insert(next_index);
+                if normal_index == next_index {
+                    obj.normals.push((0.0, 0.0, 0.0));
+                }
+
+                // The face normal isn't normalized, so larger faces contribute more to the
+                // average. Degenerate faces have a zero normal and so don't contribute anything.
+                let normal = &mut obj.normals[normal_index];
+                normal.0 += face_normal.0;
+                normal.1 += face_normal.1;
+                normal.2 += face_normal.2;
+
+                face_normals.push(normal_index);
+            }
+
+            obj.normal_indices.push(face_normals);
+        }
+
+        for normal in &mut obj.normals {
+            *normal = normalize(*normal);
+        }
+
+        obj
+    }
+
+    /// Creates a copy of the mesh with every face split into triangles.
+    ///
+    /// Faces with more than three vertices are fan triangulated around their first vertex, so a
+    /// face `1 2 3 4` becomes the triangles `1 2 3` and `1 3 4`. This preserves the winding order
+    /// of the original face, but will only produce correct results for convex faces. The
+    /// texcoord and normal indices, smoothing group, and material for each face are carried over
+    /// to all of its triangles, and groups are updated to contain the triangles for their faces.
+    ///
+    /// Degenerate faces with fewer than three vertices are dropped.
+    pub fn triangulated(&self) -> Obj {
+        let mut obj = Obj {
+            positions: self.positions.clone(),
+            position_indices: Vec::with_capacity(self.position_indices.len()),
+            texcoords: self.texcoords.clone(),
+            texcoord_indices: Vec::with_capacity(self.texcoord_indices.len()),
+            normals: self.normals.clone(),
+            normal_indices: Vec::with_capacity(self.normal_indices.len()),
+            smoothing_groups: Vec::with_capacity(self.smoothing_groups.len()),
+            material_names: Vec::with_capacity(self.material_names.len()),
+            material_libs: self.material_libs.clone(),
+            groups: Vec::with_capacity(self.groups.len()),
+            has_smoothing_groups: self.has_smoothing_groups,
+        };
+
+        // The index of the first triangle generated for each face, used to remap the groups.
+        let mut first_triangles = Vec::with_capacity(self.position_indices.len() + 1);
+
+        for (face_index, face) in self.position_indices.iter().enumerate() {
+            first_triangles.push(obj.position_indices.len());
+            if face.len() < 3 {
+                continue;
+            }
+
+            for corner in 1..face.len() - 1 {
+                obj.position_indices.push(fan_triangle(face, corner));
+
+                // Texcoord and normal indices are either present for all faces or for none.
+                if let Some(texcoords) = self.texcoord_indices.get(face_index) {
+                    obj.texcoord_indices.push(fan_triangle(texcoords, corner));
+                }
+
+                if let Some(normals) = self.normal_indices.get(face_index) {
+                    obj.normal_indices.push(fan_triangle(normals, corner));
+                }
+
+                obj.smoothing_groups.push(self.smoothing_groups[face_index]);
+                obj.material_names.push(self.material_names[face_index].clone());
+            }
+        }
+        first_triangles.push(obj.position_indices.len());
+
+        for group in &self.groups {
+            let faces = first_triangles[group.faces.start]..first_triangles[group.faces.end];
+            if faces.start < faces.end {
+                obj.groups.push(Group {
+                    name: group.name.clone(),
+                    faces: faces,
+                });
+            }
+        }
+
+        obj
+    }
+
+    /// Converts the mesh into a single interleaved vertex buffer and index buffer.
+    ///
+    /// OBJ files index each vertex attribute separately, but OpenGL only supports a single index
+    /// buffer. This finds every unique combination of position, texcoord, and normal indices used
+    /// by the mesh's faces and creates one vertex for each, returning the vertex data and a list of
+    /// triangle indices into it.
+    ///
+    /// Each vertex is laid out as the four position components, followed by the three texcoord
+    /// components if the mesh has texcoords, followed by the three normal components if the mesh
+    /// has normals. Faces with more than three vertices are triangulated as in `triangulated()`.
+    pub fn to_indexed(&self) -> (Vec<f32>, Vec<u32>) {
+        use std::collections::HashMap;
+
+        let obj = self.triangulated();
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(obj.position_indices.len() * 3);
+        let mut vertex_map = HashMap::new();
+
+        for (face_index, face) in obj.position_indices.iter().enumerate() {
+            for (vertex, &position) in face.iter().enumerate() {
+                let texcoord = obj.texcoord_indices.get(face_index).map(|face| face[vertex]);
+                let normal = obj.normal_indices.get(face_index).map(|face| face[vertex]);
+
+                let next_index = vertex_map.len() as u32;
+                let index = *vertex_map.entry((position, texcoord, normal)).or_insert(next_index);
+                if index == next_index {
+                    let (x, y, z, w) = obj.positions[position];
+                    vertices.extend_from_slice(&[x, y, z, w]);
+
+                    if let Some(texcoord) = texcoord {
+                        let (u, v, w) = obj.texcoords[texcoord];
+                        vertices.extend_from_slice(&[u, v, w]);
+                    }
+
+                    if let Some(normal) = normal {
+                        let (x, y, z) = obj.normals[normal];
+                        vertices.extend_from_slice(&[x, y, z]);
+                    }
+                }
+
+                indices.push(index);
+            }
+        }
+
+        (vertices, indices)
+    }
+
     /// Returns an iterator over the faces in mesh.
     pub fn faces(&self) -> FaceIter {
         FaceIter {
@@ -309,13 +460,555 @@ impl Obj {
             position_faces: self.position_indices.iter(),
             texcoord_faces: self.texcoord_indices.iter(),
             normal_faces: self.normal_indices.iter(),
+            material_names: self.material_names.iter(),
+        }
+    }
+}
+
+/// Calculates the normal of a face, scaled by the face's area.
+///
+/// Uses Newell's method so that the normal is still reasonable for non-planar faces with more than
+/// three vertices. Returns a zero vector for degenerate faces.
+fn face_normal(positions: &[Point], face: &[usize]) -> Vector3 {
+    let mut normal = (0.0, 0.0, 0.0);
+    for (index, &position) in face.iter().enumerate() {
+        let current = positions[position];
+        let next = positions[face[(index + 1) % face.len()]];
+
+        normal.0 += (current.1 - next.1) * (current.2 + next.2);
+        normal.1 += (current.2 - next.2) * (current.0 + next.0);
+        normal.2 += (current.0 - next.0) * (current.1 + next.1);
+    }
+
+    (normal.0 * 0.5, normal.1 * 0.5, normal.2 * 0.5)
+}
+
+/// Gets the indices of the `corner`th triangle in the fan triangulation of `face`.
+fn fan_triangle(face: &[usize], corner: usize) -> Vec<usize> {
+    vec![face[0], face[corner], face[corner + 1]]
+}
+
+/// Normalizes `vector`, leaving zero vectors unchanged.
+fn normalize(vector: Vector3) -> Vector3 {
+    let len = (vector.0 * vector.0 + vector.1 * vector.1 + vector.2 * vector.2).sqrt();
+    if len > 0.0 {
+        (vector.0 / len, vector.1 / len, vector.2 / len)
+    } else {
+        vector
+    }
+}
+
+/// Pulls the next token and parses it as an `f32`.
+fn pull_f32<'a, I: Iterator<Item=&'a str>>(tokens: &mut I) -> Result<f32, ErrorKind> {
+    let token = try!(tokens.next().ok_or(ErrorKind::MissingElement));
+    let value = try!(f32::from_str(token));
+    Ok(value)
+}
+
+/// Parses the next token as `f32` or returns `None`.
+///
+/// Returns `Ok(None)` if no tokens are left in `tokens`, but will treat an empty token as
+/// an error.
+fn pull_option_f32<'a, I: Iterator<Item=&'a str>>(tokens: &mut I) -> Result<Option<f32>, ErrorKind> {
+    match tokens.next() {
+        Some(token) => {
+            let value = try!(f32::from_str(token));
+            Ok(Some(value))
+        },
+        None => {
+            Ok(None)
         }
     }
 }
 
-// TODO: Include line number and column in errors.
+/// A face index as written in an OBJ file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Index {
+    /// A zero-based index from the start of the vertex data list.
+    Absolute(usize),
+
+    /// An index counting backwards from the end of the vertex data list at the point the face was
+    /// declared, where 1 is the last element.
+    Relative(usize),
+}
+
+/// Identifies one of the lists of vertex data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attribute {
+    Position,
+    Texcoord,
+    Normal,
+}
+
+/// Parses the next token as a face index, returning empty tokens as `None`.
+///
+/// Positive indices are one-based and negative indices are relative to the end of the vertex data
+/// list. An index of 0 is invalid.
+fn pull_option_index<'a, I: Iterator<Item=&'a str>>(tokens: &mut I) -> Result<Option<Index>, ErrorKind> {
+    let token = try!(tokens.next().ok_or(ErrorKind::MissingElement));
+    if token == "" {
+        return Ok(None);
+    }
+
+    let value = try!(isize::from_str(token));
+    if value > 0 {
+        Ok(Some(Index::Absolute(value as usize - 1)))
+    } else if value < 0 {
+        Ok(Some(Index::Relative(-value as usize)))
+    } else {
+        Err(ErrorKind::IndexOutOfRange)
+    }
+}
+
+/// Accumulates the data for an `Obj` one line at a time.
+///
+/// `ObjBuilder` holds the per-line parsing logic so that it can be shared between the different
+/// ways of feeding an OBJ document to the parser.
+#[derive(Debug, Default)]
+struct ObjBuilder {
+    positions: Vec<Point>,
+    position_indices: Vec<Vec<usize>>,
+    texcoords: Vec<Vector3>,
+    texcoord_indices: Vec<Vec<usize>>,
+    normals: Vec<Vector3>,
+    normal_indices: Vec<Vec<usize>>,
+    smoothing_groups: Vec<u32>,
+
+    /// The smoothing group set by the most recent `s` directive, if any.
+    current_smoothing_group: Option<u32>,
+
+    /// The number of faces at the start of `smoothing_groups` that were parsed before any `s`
+    /// directive.
+    ///
+    /// When parsing in chunks the smoothing group for these faces is set by an earlier chunk, so
+    /// they're fixed up when the chunks are merged.
+    inherited_smoothing_faces: usize,
+
+    material_names: Vec<Option<String>>,
+    material_libs: Vec<String>,
+
+    /// The material set by the most recent `usemtl` directive, if any.
+    current_material: Option<String>,
+
+    /// The number of faces at the start of `material_names` that were parsed before any `usemtl`
+    /// directive.
+    ///
+    /// Works the same as `inherited_smoothing_faces`.
+    inherited_material_faces: usize,
+
+    /// The name and first face of each group, in the order they were declared.
+    ///
+    /// The last face of each group is determined by the start of the next group, so faces
+    /// parsed before the first group in a chunk are naturally part of the last group from the
+    /// previous chunk when the chunks are merged.
+    group_starts: Vec<(String, usize)>,
+
+    /// The locations of the face indices that were resolved from relative indices, given as the
+    /// attribute, the index of the face in the attribute's index list, the index of the vertex
+    /// in the face, and the line the face was declared on.
+    ///
+    /// When parsing in chunks a relative index can only be resolved against the vertex data in
+    /// its own chunk, so the index is stored as if the chunk's data were at the start of the
+    /// document (wrapping around if it points into an earlier chunk) and is offset when the
+    /// chunks are merged. Any index that is still out of range after merging is an error.
+    relative_indices: Vec<(Attribute, usize, usize, usize)>,
+
+    /// The number of the last line parsed, counting from 1.
+    current_line: usize,
+}
+
+impl ObjBuilder {
+    fn new() -> ObjBuilder {
+        ObjBuilder::default()
+    }
+
+    /// Parses the next line of an OBJ document, adding its data to the builder.
+    fn parse_line(&mut self, line: &str) -> Result<(), Error> {
+        self.current_line += 1;
+        self.parse_directive(line).map_err(|kind| Error { line: self.current_line, kind: kind })
+    }
+
+    /// Parses a single directive, adding its data to the builder.
+    fn parse_directive(&mut self, line: &str) -> Result<(), ErrorKind> {
+        let mut tokens = line.split_whitespace();
+        let line_beginning = match tokens.next() {
+            Some(token) => token,
+            None => return Ok(()), // Line is empty, skip it.
+        };
+
+        match line_beginning {
+            // Vertex position data.
+            "v" => {
+                let x = try!(pull_f32(&mut tokens));
+                let y = try!(pull_f32(&mut tokens));
+                let z = try!(pull_f32(&mut tokens));
+                let w = try!(pull_option_f32(&mut tokens)).unwrap_or(1.0);
+
+                self.positions.push((x, y, z, w));
+            },
+
+            // Vertex texcoord data.
+            "vt" => {
+                let u = try!(pull_f32(&mut tokens));
+                let v = try!(pull_option_f32(&mut tokens)).unwrap_or(0.0);
+                let w = try!(pull_option_f32(&mut tokens)).unwrap_or(0.0);
+
+                self.texcoords.push((u, v, w));
+            },
+
+            // Vertex normal data.
+            "vn" => {
+                let x = try!(pull_f32(&mut tokens));
+                let y = try!(pull_f32(&mut tokens));
+                let z = try!(pull_f32(&mut tokens));
+
+                self.normals.push((x, y, z));
+            },
+
+            // Indices for the face.
+            "f" => {
+                let mut face_positions = Vec::new();
+                let mut face_texcoords = Vec::new();
+                let mut face_normals = Vec::new();
+
+                for vertex_str in tokens {
+                    let mut index_tokens = vertex_str.split('/');
+
+                    // Position index.
+                    if let Some(index) = try!(pull_option_index(&mut index_tokens)) {
+                        let index = self.resolve_index(index, Attribute::Position, face_positions.len());
+                        face_positions.push(index);
+                    }
+
+                    // Texcoord index.
+                    if let Some(index) = try!(pull_option_index(&mut index_tokens)) {
+                        let index = self.resolve_index(index, Attribute::Texcoord, face_texcoords.len());
+                        face_texcoords.push(index);
+                    }
+
+                    // Normal index.
+                    if let Some(index) = try!(pull_option_index(&mut index_tokens)) {
+                        let index = self.resolve_index(index, Attribute::Normal, face_normals.len());
+                        face_normals.push(index);
+                    }
+                }
+
+                if face_texcoords.len() != 0 {
+                    // The face has texcoord indices. There must be exactly one for each
+                    // vertex or it's an error.
+                    if face_texcoords.len() != face_positions.len() {
+                        return Err(ErrorKind::MismatchedIndexData);
+                    }
+
+                    // Add face texcoords to the texcoords list.
+                    self.texcoord_indices.push(face_texcoords);
+                }
+
+                if face_normals.len() != 0 {
+                    // The face has normal indices. There must be exactly one for each vertex
+                    // or it's an error.
+                    if face_normals.len() != face_positions.len() {
+                        return Err(ErrorKind::MismatchedIndexData);
+                    }
+
+                    // Add face normals to the normals list.
+                    self.normal_indices.push(face_normals);
+                }
+
+                // All vertices must have position data.
+                if face_positions.len() == 0 {
+                    return Err(ErrorKind::MissingPositionIndex);
+                }
+
+                self.position_indices.push(face_positions);
+
+                self.smoothing_groups.push(self.current_smoothing_group.unwrap_or(0));
+                if self.current_smoothing_group.is_none() {
+                    self.inherited_smoothing_faces += 1;
+                }
+
+                self.material_names.push(self.current_material.clone());
+                if self.current_material.is_none() {
+                    self.inherited_material_faces += 1;
+                }
+            },
+
+            // Material for the following faces.
+            "usemtl" => {
+                let name = try!(tokens.next().ok_or(ErrorKind::MissingDirectiveData));
+                self.current_material = Some(name.into());
+            },
+
+            // Material libraries.
+            "mtllib" => {
+                let start = self.material_libs.len();
+                self.material_libs.extend(tokens.map(Into::into));
+
+                if self.material_libs.len() == start {
+                    return Err(ErrorKind::MissingDirectiveData);
+                }
+            },
+
+            // Smoothing group.
+            "s" => {
+                let token = try!(tokens.next().ok_or(ErrorKind::MissingElement));
+                let group = if token == "off" {
+                    0
+                } else {
+                    try!(u32::from_str(token))
+                };
+
+                self.current_smoothing_group = Some(group);
+            },
+
+            // Group and object names, which we treat the same.
+            "g" | "o" => {
+                let names = tokens.collect::<Vec<_>>();
+                let name = if names.is_empty() { DEFAULT_GROUP.into() } else { names.join(" ") };
+
+                self.group_starts.push((name, self.position_indices.len()));
+            },
+
+            // TODO: Handle the case where there is no space between the '#' and the rest of
+            // the comment (e.g. "#blah blah").
+            "#" => {},
+
+            // TODO: Implement these other directives.
+            // TODO: Warn about unimplemented directives.
+            "vp" => {},
+            "p" => {},
+            "l" => {},
+            "mg" => {},
+            "cstype" => {},
+            "deg" => {},
+            "bmat" => {},
+            "step" => {},
+            "curv" => {},
+            "curv2" => {},
+            "surv" => {},
+            "parm" => {},
+            "trim" => {},
+            "hole" => {},
+            "scrv" => {},
+            "sp" => {},
+            "end" => {},
+            "con" => {},
+            "bevel" => {},
+            "c_interp" => {},
+            "d_interp" => {},
+            "lod" => {},
+            "shadow_obj" => {},
+            "trace_obj" => {},
+            "ctech" => {},
+            "stech" => {},
+
+            _ => {
+                return Err(ErrorKind::UnrecognizedDirective(line_beginning.into()));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Converts a face index to a zero-based index into the vertex data for `attribute`.
+    ///
+    /// `vertex` is the index of the vertex within the face currently being parsed. Relative
+    /// indices are resolved against the amount of data parsed so far and recorded in
+    /// `relative_indices`.
+    fn resolve_index(&mut self, index: Index, attribute: Attribute, vertex: usize) -> usize {
+        match index {
+            Index::Absolute(index) => index,
+            Index::Relative(offset) => {
+                let (face, len) = match attribute {
+                    Attribute::Position => (self.position_indices.len(), self.positions.len()),
+                    Attribute::Texcoord => (self.texcoord_indices.len(), self.texcoords.len()),
+                    Attribute::Normal => (self.normal_indices.len(), self.normals.len()),
+                };
+
+                self.relative_indices.push((attribute, face, vertex, self.current_line));
+                len.wrapping_sub(offset)
+            },
+        }
+    }
+
+    /// Appends the data parsed by `other` to the end of the data in `self`.
+    fn append(&mut self, mut other: ObjBuilder) {
+        // Relative indices in `other` were resolved as if its data started at the beginning of
+        // the document, so offset them by the amount of data that comes before it.
+        for &(attribute, face, vertex, line) in &other.relative_indices {
+            let (index, offset, face_offset) = match attribute {
+                Attribute::Position => (
+                    &mut other.position_indices[face][vertex],
+                    self.positions.len(),
+                    self.position_indices.len(),
+                ),
+                Attribute::Texcoord => (
+                    &mut other.texcoord_indices[face][vertex],
+                    self.texcoords.len(),
+                    self.texcoord_indices.len(),
+                ),
+                Attribute::Normal => (
+                    &mut other.normal_indices[face][vertex],
+                    self.normals.len(),
+                    self.normal_indices.len(),
+                ),
+            };
+
+            *index = index.wrapping_add(offset);
+            self.relative_indices.push((attribute, face + face_offset, vertex, line));
+        }
+        self.current_line = other.current_line;
+
+        let face_offset = self.position_indices.len();
+        self.group_starts.extend(
+            other.group_starts
+                .drain(..)
+                .map(|(name, first_face)| (name, first_face + face_offset)));
+
+        // Faces at the start of `other` that came before any `s` directive are in whatever
+        // smoothing group was active at the end of `self`.
+        match self.current_smoothing_group {
+            Some(group) => {
+                for face_group in &mut other.smoothing_groups[..other.inherited_smoothing_faces] {
+                    *face_group = group;
+                }
+            },
+
+            None => {
+                self.inherited_smoothing_faces += other.inherited_smoothing_faces;
+            },
+        }
+        self.current_smoothing_group = other.current_smoothing_group.or(self.current_smoothing_group);
+        self.smoothing_groups.append(&mut other.smoothing_groups);
+
+        // Same for the faces at the start of `other` that came before any `usemtl` directive.
+        match self.current_material {
+            Some(ref material) => {
+                for face_material in &mut other.material_names[..other.inherited_material_faces] {
+                    *face_material = Some(material.clone());
+                }
+            },
+
+            None => {
+                self.inherited_material_faces += other.inherited_material_faces;
+            },
+        }
+        self.current_material = other.current_material.take().or(self.current_material.take());
+        self.material_names.append(&mut other.material_names);
+        self.material_libs.append(&mut other.material_libs);
+
+        self.positions.append(&mut other.positions);
+        self.position_indices.append(&mut other.position_indices);
+        self.texcoords.append(&mut other.texcoords);
+        self.texcoord_indices.append(&mut other.texcoord_indices);
+        self.normals.append(&mut other.normals);
+        self.normal_indices.append(&mut other.normal_indices);
+    }
+
+    /// Performs the final consistency checks and creates the `Obj`.
+    fn build(self) -> Result<Obj, Error> {
+        // Check that either all of the faces of texcoords or none do.
+        if self.texcoord_indices.len() != 0 && self.texcoord_indices.len() != self.position_indices.len() {
+            return Err(Error { line: 0, kind: ErrorKind::MismatchedFaceData });
+        }
+
+        // Check that either all of the faces of normals or none do.
+        if self.normal_indices.len() != 0 && self.normal_indices.len() != self.position_indices.len() {
+            return Err(Error { line: 0, kind: ErrorKind::MismatchedFaceData });
+        }
+
+        // Relative indices that point before the start of their vertex data are invalid.
+        for &(attribute, face, vertex, line) in &self.relative_indices {
+            let (index, len) = match attribute {
+                Attribute::Position => (self.position_indices[face][vertex], self.positions.len()),
+                Attribute::Texcoord => (self.texcoord_indices[face][vertex], self.texcoords.len()),
+                Attribute::Normal => (self.normal_indices[face][vertex], self.normals.len()),
+            };
+
+            if index >= len {
+                return Err(Error { line: line, kind: ErrorKind::IndexOutOfRange });
+            }
+        }
+
+        // Convert the group starts into face ranges, putting any faces before the first group into
+        // the default group.
+        let face_count = self.position_indices.len();
+        let first_group_start = self.group_starts.first().map(|&(_, start)| start).unwrap_or(face_count);
+
+        let mut groups = Vec::with_capacity(self.group_starts.len() + 1);
+        if first_group_start > 0 {
+            groups.push(Group {
+                name: DEFAULT_GROUP.into(),
+                faces: 0..first_group_start,
+            });
+        }
+
+        for (index, &(ref name, start)) in self.group_starts.iter().enumerate() {
+            let end = self.group_starts.get(index + 1).map(|&(_, start)| start).unwrap_or(face_count);
+            if end > start {
+                groups.push(Group {
+                    name: name.clone(),
+                    faces: start..end,
+                });
+            }
+        }
+
+        Ok(Obj {
+            positions: self.positions,
+            position_indices: self.position_indices,
+            texcoords: self.texcoords,
+            texcoord_indices: self.texcoord_indices,
+            normals: self.normals,
+            normal_indices: self.normal_indices,
+            smoothing_groups: self.smoothing_groups,
+            material_names: self.material_names,
+            material_libs: self.material_libs,
+            groups: groups,
+            has_smoothing_groups: self.current_smoothing_group.is_some(),
+        })
+    }
+}
+
+/// The name of the group containing any faces declared before the first `g` or `o` directive.
+const DEFAULT_GROUP: &'static str = "default";
+
+/// A named group of faces in an OBJ file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    name: String,
+    faces: Range<usize>,
+}
+
+impl Group {
+    /// Gets the name of the group.
+    pub fn name(&self) -> &str {
+        &*self.name
+    }
+
+    /// Gets the range of indices of the faces in the group.
+    ///
+    /// The indices can be used with `Obj::position_indices()` and the other per-face data.
+    pub fn faces(&self) -> Range<usize> {
+        self.faces.clone()
+    }
+}
+
+/// An error encountered while parsing an OBJ or MTL document.
+#[derive(Debug)]
+pub struct Error {
+    /// The line the error occurred on, counting from 1.
+    ///
+    /// Errors that aren't caused by a specific line, such as IO errors or faces that are
+    /// inconsistent with each other, have a line of 0.
+    pub line: usize,
+
+    /// The kind of error that occurred.
+    pub kind: ErrorKind,
+}
+
+// TODO: Include column in errors.
 #[derive(Debug)]
-pub enum Error {
+pub enum ErrorKind {
     UnrecognizedDirective(String),
 
     /// Indicates that some faces have normal or texcoord data but others don't.
@@ -330,8 +1023,16 @@ pub enum Error {
     /// vertices to not have that data.
     MismatchedIndexData,
 
+    /// Indicates that a face index is 0, or is a negative index that refers to a vertex before
+    /// the start of the vertex data.
+    IndexOutOfRange,
+
     MissingDirectiveData,
     MissingElement,
+
+    /// Indicates that an MTL file has a material property before the first `newmtl` directive.
+    MissingMaterial,
+
     MissingPositionData,
     MissingPositionIndex,
     ParseFloatError(::std::num::ParseFloatError),
@@ -339,33 +1040,44 @@ pub enum Error {
     IoError(::std::io::Error),
 }
 
-impl From<::std::num::ParseFloatError> for Error {
-    fn from(error: ::std::num::ParseFloatError) -> Error {
-        Error::ParseFloatError(error)
+impl From<::std::num::ParseFloatError> for ErrorKind {
+    fn from(error: ::std::num::ParseFloatError) -> ErrorKind {
+        ErrorKind::ParseFloatError(error)
     }
 }
 
-impl From<::std::num::ParseIntError> for Error {
-    fn from(error: ::std::num::ParseIntError) -> Error {
-        Error::ParseIntError(error)
+impl From<::std::num::ParseIntError> for ErrorKind {
+    fn from(error: ::std::num::ParseIntError) -> ErrorKind {
+        ErrorKind::ParseIntError(error)
     }
 }
 
 impl From<::std::io::Error> for Error {
     fn from(error: ::std::io::Error) -> Error {
-        Error::IoError(error)
+        Error {
+            line: 0,
+            kind: ErrorKind::IoError(error),
+        }
     }
 }
 
 /// An iterator over the vertices in a
 pub struct Face<'a> {
     obj: &'a Obj,
+    material: Option<&'a str>,
 
     position_indices: slice::Iter<'a, usize>,
     texcoord_indices: Option<slice::Iter<'a, usize>>,
     normal_indices: Option<slice::Iter<'a, usize>>,
 }
 
+impl<'a> Face<'a> {
+    /// Gets the name of the material used by the face, or `None` if the face has no material.
+    pub fn material(&self) -> Option<&'a str> {
+        self.material
+    }
+}
+
 impl<'a> Iterator for Face<'a> {
     type Item = (Point, Option<Vector3>, Option<Vector3>);
 
@@ -400,6 +1112,7 @@ pub struct FaceIter<'a> {
     position_faces: slice::Iter<'a, Vec<usize>>,
     texcoord_faces: slice::Iter<'a, Vec<usize>>,
     normal_faces: slice::Iter<'a, Vec<usize>>,
+    material_names: slice::Iter<'a, Option<String>>,
 }
 
 impl<'a> Iterator for FaceIter<'a> {
@@ -412,9 +1125,15 @@ impl<'a> Iterator for FaceIter<'a> {
             .map(|pos_face| {
                 let tex_face = self.texcoord_faces.next().map(|indices| indices.iter());
                 let norm_face = self.normal_faces.next().map(|indices| indices.iter());
+                let material = self
+                    .material_names
+                    .next()
+                    .and_then(|name| name.as_ref())
+                    .map(|name| &**name);
 
                 Face {
                     obj: self.obj,
+                    material: material,
 
                     position_indices: pos_face.iter(),
                     texcoord_indices: tex_face,
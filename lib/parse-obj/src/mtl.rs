@@ -0,0 +1,209 @@
+//! Parsing for MTL material libraries.
+//!
+//! OBJ files reference their materials in separate MTL files using the `mtllib` directive.
+//! Texture maps in an MTL file are given as paths relative to the MTL file itself, not relative to
+//! the working directory, so `MaterialLibrary` resolves them against the directory containing the
+//! MTL file. Both the path as written in the file and the resolved path are available through
+//! `TexturePath`.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use super::{Error, ErrorKind, Vector3};
+
+/// A parsed MTL material library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+}
+
+impl MaterialLibrary {
+    /// Loads and parses the MTL file at `path`.
+    ///
+    /// Texture paths are resolved against the directory containing `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<MaterialLibrary, Error> {
+        use std::fs::File;
+        use std::io::prelude::*;
+
+        let path = path.as_ref();
+
+        let mut file = try!(File::open(path));
+        let mut text = String::new();
+
+        try!(file.read_to_string(&mut text));
+
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        MaterialLibrary::from_str(&text, base_dir)
+    }
+
+    /// Parses an MTL document, resolving texture paths against `base_dir`.
+    ///
+    /// `base_dir` should be the directory containing the MTL document.
+    pub fn from_str<P: AsRef<Path>>(file_text: &str, base_dir: P) -> Result<MaterialLibrary, Error> {
+        let base_dir = base_dir.as_ref();
+        let mut materials: Vec<Material> = Vec::new();
+
+        for (index, line) in file_text.lines().enumerate() {
+            try!(
+                parse_line(line, &mut materials, base_dir)
+                .map_err(|kind| Error { line: index + 1, kind: kind }));
+        }
+
+        Ok(MaterialLibrary {
+            materials: materials,
+        })
+    }
+
+    /// Gets the list of materials in the library, in the order they were declared.
+    pub fn materials(&self) -> &[Material] {
+        &*self.materials
+    }
+
+    /// Gets the material with the specified name.
+    pub fn get(&self, name: &str) -> Option<&Material> {
+        self.materials.iter().find(|material| material.name == name)
+    }
+}
+
+/// A single material declared in an MTL file with `newmtl`.
+///
+/// Properties that weren't specified in the file are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    pub ambient_color: Option<Vector3>,
+    pub diffuse_color: Option<Vector3>,
+    pub specular_color: Option<Vector3>,
+    pub specular_exponent: Option<f32>,
+    pub dissolve: Option<f32>,
+    pub ambient_map: Option<TexturePath>,
+    pub diffuse_map: Option<TexturePath>,
+    pub specular_map: Option<TexturePath>,
+    pub normal_map: Option<TexturePath>,
+}
+
+impl Material {
+    fn new(name: &str) -> Material {
+        Material {
+            name: name.into(),
+            ambient_color: None,
+            diffuse_color: None,
+            specular_color: None,
+            specular_exponent: None,
+            dissolve: None,
+            ambient_map: None,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+        }
+    }
+}
+
+/// The path to a texture map referenced by a material.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TexturePath {
+    raw: String,
+    resolved: PathBuf,
+}
+
+impl TexturePath {
+    /// Parses the arguments of a texture map directive.
+    ///
+    /// Texture map directives may have options (e.g. `-s 1 1 1`) before the file name. We don't
+    /// support any of the options, so if any are present the last token is used as the file
+    /// name. Otherwise the entire remainder of the line is used, since file names may contain
+    /// spaces.
+    fn parse(args: &str, base_dir: &Path) -> Result<TexturePath, ErrorKind> {
+        let raw = if args.starts_with('-') {
+            try!(args.split_whitespace().last().ok_or(ErrorKind::MissingDirectiveData))
+        } else {
+            args
+        };
+
+        if raw.is_empty() {
+            return Err(ErrorKind::MissingDirectiveData);
+        }
+
+        // MTL files exported on Windows often use backslashes as separators, which aren't
+        // recognized as separators on other platforms. Forward slashes work everywhere.
+        let normalized = raw.replace('\\', "/");
+
+        Ok(TexturePath {
+            raw: raw.into(),
+            resolved: base_dir.join(normalized),
+        })
+    }
+
+    /// Gets the path exactly as it was written in the MTL file.
+    pub fn raw(&self) -> &str {
+        &*self.raw
+    }
+
+    /// Gets the path resolved against the directory containing the MTL file.
+    pub fn resolved(&self) -> &Path {
+        &*self.resolved
+    }
+}
+
+/// Parses a single line of an MTL document, adding its data to `materials`.
+fn parse_line(line: &str, materials: &mut Vec<Material>, base_dir: &Path) -> Result<(), ErrorKind> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(());
+    }
+
+    let (directive, rest) = match line.find(char::is_whitespace) {
+        Some(index) => (&line[..index], line[index..].trim()),
+        None => (line, ""),
+    };
+
+    if directive == "newmtl" {
+        if rest.is_empty() {
+            return Err(ErrorKind::MissingDirectiveData);
+        }
+
+        materials.push(Material::new(rest));
+        return Ok(());
+    }
+
+    let material = try!(materials.last_mut().ok_or(ErrorKind::MissingMaterial));
+    match directive {
+        "Ka" => material.ambient_color = Some(try!(parse_color(rest))),
+        "Kd" => material.diffuse_color = Some(try!(parse_color(rest))),
+        "Ks" => material.specular_color = Some(try!(parse_color(rest))),
+        "Ns" => material.specular_exponent = Some(try!(parse_f32(rest))),
+        "d" => material.dissolve = Some(try!(parse_f32(rest))),
+        "map_Ka" => material.ambient_map = Some(try!(TexturePath::parse(rest, base_dir))),
+        "map_Kd" => material.diffuse_map = Some(try!(TexturePath::parse(rest, base_dir))),
+        "map_Ks" => material.specular_map = Some(try!(TexturePath::parse(rest, base_dir))),
+        "map_Bump" | "map_bump" | "bump" | "norm" => {
+            material.normal_map = Some(try!(TexturePath::parse(rest, base_dir)));
+        },
+
+        // Ignore any directives we don't support, there are a lot of them.
+        _ => {},
+    }
+
+    Ok(())
+}
+
+fn parse_f32(text: &str) -> Result<f32, ErrorKind> {
+    let token = try!(text.split_whitespace().next().ok_or(ErrorKind::MissingElement));
+    let value = try!(f32::from_str(token));
+    Ok(value)
+}
+
+/// Parses an RGB color.
+///
+/// The MTL format allows the green and blue components to be omitted, in which case they are the
+/// same as the red component.
+fn parse_color(text: &str) -> Result<Vector3, ErrorKind> {
+    let mut tokens = text.split_whitespace();
+    let r = try!(f32::from_str(try!(tokens.next().ok_or(ErrorKind::MissingElement))));
+    let g = match tokens.next() {
+        Some(token) => try!(f32::from_str(token)),
+        None => return Ok((r, r, r)),
+    };
+    let b = try!(f32::from_str(try!(tokens.next().ok_or(ErrorKind::MissingElement))));
+
+    Ok((r, g, b))
+}
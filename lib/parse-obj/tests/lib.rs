@@ -48,3 +48,504 @@ fn test_iterator() {
         assert_eq!(Some(((0.0, 1.0, 0.0, 1.0), Some((1.0, 1.0, 1.0)), Some((1.0, 0.0, 0.0)))), face.next());
     }
 }
+
+/// Generates an OBJ document for a `size` by `size` grid of quads with texcoords and normals.
+fn grid_obj(size: usize) -> String {
+    let mut text = String::new();
+
+    for y in 0..size + 1 {
+        for x in 0..size + 1 {
+            text.push_str(&format!("v {} {} 0.0\n", x, y));
+            text.push_str(&format!("vt {} {}\n", x as f32 / size as f32, y as f32 / size as f32));
+        }
+    }
+
+    text.push_str("vn 0.0 0.0 1.0\n");
+
+    for y in 0..size {
+        // Change smoothing groups periodically so that parallel parsing has to carry the active
+        // group across chunk boundaries.
+        if y % 5 == 0 {
+            text.push_str(&format!("s {}\n", y % 3));
+        }
+
+        for x in 0..size {
+            let first = y * (size + 1) + x + 1;
+            let second = first + 1;
+            let third = second + size + 1;
+            let fourth = first + size + 1;
+            text.push_str(&format!(
+                "f {0}/{0}/1 {1}/{1}/1 {2}/{2}/1 {3}/{3}/1\n",
+                first,
+                second,
+                third,
+                fourth,
+            ));
+        }
+    }
+
+    text
+}
+
+#[test]
+fn parallel_matches_serial() {
+    let text = grid_obj(64);
+
+    let serial = Obj::from_str(&*text).unwrap();
+    assert_eq!(64 * 64, serial.position_indices().len());
+
+    for threads in 1..9 {
+        let parallel = Obj::from_string_parallel(text.clone(), threads).unwrap();
+        assert_eq!(serial, parallel);
+    }
+}
+
+static CUBE_OBJ: &'static str = r#"
+v -1.0 -1.0 -1.0
+v 1.0 -1.0 -1.0
+v 1.0 1.0 -1.0
+v -1.0 1.0 -1.0
+v -1.0 -1.0 1.0
+v 1.0 -1.0 1.0
+v 1.0 1.0 1.0
+v -1.0 1.0 1.0
+
+s off
+f 1// 4// 3// 2//
+f 5// 6// 7// 8//
+f 1// 2// 6// 5//
+f 4// 8// 7// 3//
+f 1// 5// 8// 4//
+f 2// 3// 7// 6//
+"#;
+
+fn assert_normal_eq(expected: (f32, f32, f32), actual: (f32, f32, f32)) {
+    let diff = (expected.0 - actual.0).abs() + (expected.1 - actual.1).abs() + (expected.2 - actual.2).abs();
+    assert!(diff < 0.001, "Expected normal {:?}, found {:?}", expected, actual);
+}
+
+#[test]
+fn generate_flat_normals() {
+    let obj = Obj::from_str(CUBE_OBJ).unwrap().with_generated_normals();
+
+    // Every corner of every face gets its own normal, since none of the edges are smooth.
+    assert_eq!(24, obj.normals().len());
+
+    let expected_normals = [
+        (0.0, 0.0, -1.0),
+        (0.0, 0.0, 1.0),
+        (0.0, -1.0, 0.0),
+        (0.0, 1.0, 0.0),
+        (-1.0, 0.0, 0.0),
+        (1.0, 0.0, 0.0),
+    ];
+    for (face, &expected) in obj.normal_indices().iter().zip(expected_normals.iter()) {
+        assert_eq!(4, face.len());
+        for &normal_index in face {
+            assert_normal_eq(expected, obj.normals()[normal_index]);
+        }
+    }
+}
+
+/// Generates a UV sphere with `rings` rings and `segments` segments, all in smoothing group 1.
+fn sphere_obj(rings: usize, segments: usize) -> String {
+    use std::f32::consts::PI;
+
+    let mut text = String::new();
+
+    // The poles are shared by all the faces touching them.
+    text.push_str("v 0.0 1.0 0.0\n");
+    for ring in 1..rings {
+        let theta = PI * ring as f32 / rings as f32;
+        for segment in 0..segments {
+            let phi = 2.0 * PI * segment as f32 / segments as f32;
+            text.push_str(&format!("v {} {} {}\n", theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin()));
+        }
+    }
+    text.push_str("v 0.0 -1.0 0.0\n");
+
+    let top = 1;
+    let bottom = 2 + (rings - 1) * segments;
+    let ring_vertex = |ring: usize, segment: usize| 2 + (ring - 1) * segments + segment % segments;
+
+    text.push_str("s 1\n");
+    for segment in 0..segments {
+        text.push_str(&format!("f {}// {}// {}//\n", top, ring_vertex(1, segment + 1), ring_vertex(1, segment)));
+    }
+    for ring in 1..rings - 1 {
+        for segment in 0..segments {
+            text.push_str(&format!(
+                "f {}// {}// {}// {}//\n",
+                ring_vertex(ring, segment),
+                ring_vertex(ring, segment + 1),
+                ring_vertex(ring + 1, segment + 1),
+                ring_vertex(ring + 1, segment),
+            ));
+        }
+    }
+    for segment in 0..segments {
+        text.push_str(&format!("f {}// {}// {}//\n", bottom, ring_vertex(rings - 1, segment), ring_vertex(rings - 1, segment + 1)));
+    }
+
+    text
+}
+
+#[test]
+fn generate_smooth_normals() {
+    let obj = Obj::from_str(&*sphere_obj(8, 16)).unwrap().with_generated_normals();
+
+    // Every position is shared by faces in the same smoothing group, so there's exactly one normal
+    // per position.
+    assert_eq!(obj.positions().len(), obj.normals().len());
+
+    // The averaged normals of a sphere point (roughly) away from the center.
+    for (positions, normals) in obj.position_indices().iter().zip(obj.normal_indices()) {
+        for (&position_index, &normal_index) in positions.iter().zip(normals) {
+            let (x, y, z, _) = obj.positions()[position_index];
+            let (nx, ny, nz) = obj.normals()[normal_index];
+            assert!(x * nx + y * ny + z * nz > 0.95, "Normal {:?} doesn't point away from {:?}", (nx, ny, nz), (x, y, z));
+        }
+    }
+}
+
+#[test]
+fn generate_normals_keeps_existing() {
+    let obj = Obj::from_str(TRIANGLE_WITH_NORM).unwrap();
+    assert_eq!(obj, obj.with_generated_normals());
+}
+
+#[test]
+fn mtl_texture_paths() {
+    use parse_obj::mtl::MaterialLibrary;
+    use std::path::Path;
+
+    let library = MaterialLibrary::from_file("tests/mtl/models/crate/crate.mtl").unwrap();
+    assert_eq!(2, library.materials().len());
+
+    let material = library.get("crate").unwrap();
+    assert_eq!(Some((0.8, 0.6, 0.4)), material.diffuse_color);
+    assert_eq!(Some((0.5, 0.5, 0.5)), material.specular_color);
+    assert_eq!(Some(32.0), material.specular_exponent);
+
+    // Texture paths are relative to the MTL file, not the working directory.
+    let diffuse_map = material.diffuse_map.as_ref().unwrap();
+    assert_eq!("../../textures/crate_diffuse.bmp", diffuse_map.raw());
+    assert_eq!(
+        Path::new("tests/mtl/models/crate/../../textures/crate_diffuse.bmp"),
+        diffuse_map.resolved());
+    assert!(diffuse_map.resolved().is_file());
+
+    // Backslash separators are preserved in the raw path but resolved like forward slashes.
+    let normal_map = material.normal_map.as_ref().unwrap();
+    assert_eq!(r"..\..\textures\crate_normal.bmp", normal_map.raw());
+    assert!(normal_map.resolved().is_file());
+
+    // Texture options are skipped.
+    let trim = library.get("trim").unwrap();
+    let trim_map = trim.diffuse_map.as_ref().unwrap();
+    assert_eq!("trim.bmp", trim_map.raw());
+    assert_eq!(Path::new("tests/mtl/models/crate/trim.bmp"), trim_map.resolved());
+}
+
+#[test]
+fn mtl_property_before_material() {
+    use parse_obj::mtl::MaterialLibrary;
+
+    match MaterialLibrary::from_str("Kd 1.0 1.0 1.0", ".") {
+        Err(Error { kind: ErrorKind::MissingMaterial, .. }) => {},
+        result => panic!("Expected MissingMaterial error, got {:?}", result),
+    }
+}
+
+static MATERIALS_OBJ: &'static str = r#"
+mtllib crate.mtl shared.mtl
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+
+f 1// 2// 3//
+usemtl wood
+f 1// 3// 4//
+f 2// 3// 4//
+usemtl metal
+f 1// 2// 4//
+"#;
+
+#[test]
+fn material_grouping() {
+    let obj = Obj::from_str(MATERIALS_OBJ).unwrap();
+
+    assert_eq!(&["crate.mtl".to_string(), "shared.mtl".to_string()], obj.material_libs());
+    assert_eq!(
+        &[None, Some("wood".into()), Some("wood".into()), Some("metal".into())],
+        obj.material_names());
+
+    let materials = obj.faces().map(|face| face.material()).collect::<Vec<_>>();
+    assert_eq!(vec![None, Some("wood"), Some("wood"), Some("metal")], materials);
+
+    // Materials that span chunk boundaries are merged correctly when parsing in parallel.
+    for threads in 1..6 {
+        let parallel = Obj::from_string_parallel(MATERIALS_OBJ.into(), threads).unwrap();
+        assert_eq!(obj, parallel);
+    }
+}
+
+static OBJECTS_OBJ: &'static str = r#"
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+
+f 1// 2// 3//
+
+o Cube
+f 1// 3// 4//
+f 2// 3// 4//
+
+o Pyramid Base
+f 1// 2// 4//
+"#;
+
+#[test]
+fn object_groups() {
+    let obj = Obj::from_str(OBJECTS_OBJ).unwrap();
+
+    let groups = obj.groups();
+    assert_eq!(3, groups.len());
+
+    assert_eq!("default", groups[0].name());
+    assert_eq!(0..1, groups[0].faces());
+
+    assert_eq!("Cube", groups[1].name());
+    assert_eq!(1..3, groups[1].faces());
+
+    assert_eq!("Pyramid Base", groups[2].name());
+    assert_eq!(3..4, groups[2].faces());
+
+    // Groups that span chunk boundaries are merged correctly when parsing in parallel.
+    for threads in 1..6 {
+        let parallel = Obj::from_string_parallel(OBJECTS_OBJ.into(), threads).unwrap();
+        assert_eq!(obj, parallel);
+    }
+}
+
+static POLYGONS_OBJ: &'static str = r#"
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+v -1.0 0.5 0.0
+
+vt 0.0 0.0
+vt 1.0 0.0
+vt 1.0 1.0
+vt 0.0 1.0
+vt 0.5 0.5
+
+vn 0.0 0.0 1.0
+
+g quad
+usemtl wood
+f 1/1/1 2/2/1 3/3/1 4/4/1
+g degenerate
+f 1/1/1 2/2/1
+g pentagon
+s 1
+f 1/5/1 2/4/1 3/3/1 4/2/1 5/1/1
+"#;
+
+#[test]
+fn triangulate_polygons() {
+    let obj = Obj::from_str(POLYGONS_OBJ).unwrap().triangulated();
+
+    // The quad becomes two triangles, the pentagon becomes three, and the degenerate face is
+    // dropped. All triangles keep the winding order of their original face.
+    assert_eq!(
+        &[
+            vec![0, 1, 2], vec![0, 2, 3],
+            vec![0, 1, 2], vec![0, 2, 3], vec![0, 3, 4],
+        ],
+        obj.position_indices());
+    assert_eq!(
+        &[
+            vec![0, 1, 2], vec![0, 2, 3],
+            vec![4, 3, 2], vec![4, 2, 1], vec![4, 1, 0],
+        ],
+        obj.texcoord_indices());
+    assert_eq!(5, obj.normal_indices().len());
+    assert_eq!(&[0, 0, 1, 1, 1], obj.smoothing_groups());
+    assert!(obj.material_names().iter().all(|name| name.as_ref().map(|name| &**name) == Some("wood")));
+
+    let groups = obj.groups();
+    assert_eq!(2, groups.len());
+    assert_eq!("quad", groups[0].name());
+    assert_eq!(0..2, groups[0].faces());
+    assert_eq!("pentagon", groups[1].name());
+    assert_eq!(2..5, groups[1].faces());
+
+    // Triangulating again doesn't change anything.
+    assert_eq!(obj, obj.triangulated());
+}
+
+static RELATIVE_OBJ: &'static str = r#"
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+vt 0.0 0.0
+vn 0.0 0.0 1.0
+f -3/-1/-1 -2/-1/-1 -1/-1/-1
+
+v 0.0 1.0 0.0
+vt 1.0 1.0
+f 1/1/1 -2/-1/-1 -1/-1/-1
+"#;
+
+#[test]
+fn relative_indices() {
+    let obj = Obj::from_str(RELATIVE_OBJ).unwrap();
+
+    assert_eq!(&[vec![0, 1, 2], vec![0, 2, 3]], obj.position_indices());
+    assert_eq!(&[vec![0, 0, 0], vec![0, 1, 1]], obj.texcoord_indices());
+    assert_eq!(&[vec![0, 0, 0], vec![0, 0, 0]], obj.normal_indices());
+
+    // Relative indices that refer back into earlier chunks are resolved when parsing in parallel.
+    for threads in 1..8 {
+        let parallel = Obj::from_string_parallel(RELATIVE_OBJ.into(), threads).unwrap();
+        assert_eq!(obj, parallel);
+    }
+}
+
+#[test]
+fn relative_index_out_of_range() {
+    let text = "v 0.0 0.0 0.0\nv 1.0 0.0 0.0\nf -1// -2// -3//\n";
+    match Obj::from_str(text) {
+        Err(Error { kind: ErrorKind::IndexOutOfRange, .. }) => {},
+        result => panic!("Expected IndexOutOfRange, got {:?}", result),
+    }
+
+    for threads in 1..4 {
+        match Obj::from_string_parallel(text.into(), threads) {
+            Err(Error { kind: ErrorKind::IndexOutOfRange, .. }) => {},
+            result => panic!("Expected IndexOutOfRange, got {:?}", result),
+        }
+    }
+
+    match Obj::from_str("v 0.0 0.0 0.0\nf 0// 1// 1//\n") {
+        Err(Error { kind: ErrorKind::IndexOutOfRange, .. }) => {},
+        result => panic!("Expected IndexOutOfRange, got {:?}", result),
+    }
+}
+
+#[test]
+fn error_line_numbers() {
+    let text = "v 0.0 0.0 0.0\nv 1.0 0.0 0.0\nv 1.0 1.0 0.0\n\nf 1/1/ 2// 3//\n";
+    match Obj::from_str(text) {
+        Err(Error { line: 5, kind: ErrorKind::MismatchedIndexData }) => {},
+        result => panic!("Expected MismatchedIndexData on line 5, got {:?}", result),
+    }
+
+    // Line numbers are for the whole document even when parsing in chunks.
+    for threads in 1..4 {
+        match Obj::from_string_parallel(text.into(), threads) {
+            Err(Error { line: 5, kind: ErrorKind::MismatchedIndexData }) => {},
+            result => panic!("Expected MismatchedIndexData on line 5, got {:?}", result),
+        }
+    }
+
+    // Out of range relative indices are reported on the line of their face.
+    match Obj::from_str("v 0.0 0.0 0.0\nf -1// -1// -2//\nf 1// 1// 1//\n") {
+        Err(Error { line: 2, kind: ErrorKind::IndexOutOfRange }) => {},
+        result => panic!("Expected IndexOutOfRange on line 2, got {:?}", result),
+    }
+}
+
+static SMOOTHING_OBJ: &'static str = r#"
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+
+f 1// 2// 3//
+s 1
+f 1// 3// 4//
+f 2// 3// 4//
+s off
+f 1// 2// 4//
+s 2
+f 1// 2// 3//
+s 0
+f 1// 3// 4//
+"#;
+
+#[test]
+fn smoothing_group_sections() {
+    let obj = Obj::from_str(SMOOTHING_OBJ).unwrap();
+    assert_eq!(&[0, 1, 1, 0, 2, 0], obj.smoothing_groups());
+
+    for threads in 1..6 {
+        let parallel = Obj::from_string_parallel(SMOOTHING_OBJ.into(), threads).unwrap();
+        assert_eq!(obj.smoothing_groups(), parallel.smoothing_groups());
+    }
+}
+
+#[test]
+fn parse_from_reader() {
+    use std::io::Cursor;
+
+    // Streaming gives the same result as parsing the whole document, including comments and
+    // Windows line endings.
+    let text = "# A comment\r\nv 0.0 0.0 0.0\r\nv 1.0 0.0 0.0\r\nv 1.0 1.0 0.0\r\nf 1// 2// 3//";
+    let streamed = Obj::from_reader(Cursor::new(text)).unwrap();
+    assert_eq!(Obj::from_str(text).unwrap(), streamed);
+
+    for &text in &[TRIANGLE_OBJ, TRIANGLE_WITH_NORM, MATERIALS_OBJ, OBJECTS_OBJ, RELATIVE_OBJ] {
+        let streamed = Obj::from_reader(Cursor::new(text)).unwrap();
+        assert_eq!(Obj::from_str(text).unwrap(), streamed);
+    }
+
+    // The final consistency checks still run.
+    let text = "v 0.0 0.0 0.0\nvt 0.0 0.0\nf 1/1/ 1/1/ 1/1/\nf 1// 1// 1//\n";
+    match Obj::from_reader(Cursor::new(text)) {
+        Err(Error { kind: ErrorKind::MismatchedFaceData, .. }) => {},
+        result => panic!("Expected MismatchedFaceData, got {:?}", result),
+    }
+}
+
+#[test]
+fn generate_normals_without_smoothing_groups() {
+    // Without any `s` directives all faces are smoothed together.
+    let text = sphere_obj(8, 16).replace("s 1\n", "");
+    let obj = Obj::from_str(&*text).unwrap().with_generated_normals();
+    assert_eq!(obj.positions().len(), obj.normals().len());
+
+    // A degenerate face sharing a vertex with a regular face doesn't affect its normal.
+    let text = "v 0.0 0.0 0.0\nv 1.0 0.0 0.0\nv 0.0 1.0 0.0\nv 2.0 0.0 0.0\nf 1// 2// 3//\nf 1// 2// 4//\n";
+    let obj = Obj::from_str(text).unwrap().with_generated_normals();
+    assert_eq!(4, obj.normals().len());
+    for &normal_index in &obj.normal_indices()[0] {
+        assert_normal_eq((0.0, 0.0, 1.0), obj.normals()[normal_index]);
+    }
+}
+
+#[test]
+fn indexed_vertex_data() {
+    let obj = Obj::from_str(TRIANGLE_WITH_NORM).unwrap();
+    let (vertices, indices) = obj.to_indexed();
+
+    assert_eq!(
+        vec![
+            -1.0, -1.0, 0.0, 1.0,   1.0, 1.0, 1.0,   1.0, 0.0, 0.0,
+             1.0, -1.0, 0.0, 1.0,   1.0, 1.0, 1.0,   1.0, 0.0, 0.0,
+             0.0,  1.0, 0.0, 1.0,   1.0, 1.0, 1.0,   1.0, 0.0, 0.0,
+        ],
+        vertices);
+    assert_eq!(vec![0, 1, 2], indices);
+
+    // Vertices that are shared between faces are only included once, but the same position with
+    // a different texcoord is a separate vertex.
+    let (vertices, indices) = Obj::from_str(POLYGONS_OBJ).unwrap().to_indexed();
+    assert_eq!(vec![0, 1, 2, 0, 2, 3, 4, 5, 2, 4, 2, 6, 4, 6, 7], indices);
+    assert_eq!(8 * 10, vertices.len());
+}
+
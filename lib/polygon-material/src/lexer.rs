@@ -100,6 +100,7 @@ impl<'a> Lexer<'a> {
                 let token = match word {
                     "property" => Token::Property,
                     "program" => Token::Program,
+                    "blend" => Token::Blend,
                     _ => Token::Identifier,
                 };
 
@@ -10,6 +10,11 @@ use std::path::Path;
 pub struct MaterialSource {
     pub properties: Vec<PropertySource>,
     pub programs: Vec<ProgramSource>,
+
+    /// The blend mode declared with a `blend` item, e.g. `blend alpha;`.
+    ///
+    /// Materials with a blend mode are transparent, and are rendered after opaque materials.
+    pub blend: Option<BlendMode>,
 }
 
 impl MaterialSource {
@@ -76,6 +81,21 @@ pub enum PropertyType {
     Vector3,
 }
 
+/// How a transparent material's output color is combined with the color already in the
+/// framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Standard alpha blending, declared with `blend alpha;`.
+    Alpha,
+
+    /// Alpha blending for colors that are already multiplied by their alpha, declared with
+    /// `blend premultiplied;`.
+    Premultiplied,
+
+    /// Adds the output color to the framebuffer, declared with `blend additive;`.
+    Additive,
+}
+
 /// Represents an error in parsing a material source file.
 #[derive(Debug)]
 pub enum Error {
@@ -1,5 +1,5 @@
 use lexer::{Lexer, Error as TokenError};
-use material_source::{MaterialSource, ProgramSource, PropertySource, PropertyType};
+use material_source::{BlendMode, MaterialSource, ProgramSource, PropertySource, PropertyType};
 use token::*;
 
 #[derive(Debug)]
@@ -19,12 +19,20 @@ impl<'a> Parser<'a> {
     pub fn parse(&mut self) -> Result<MaterialSource, Error> {
         let mut properties = Vec::new();
         let mut programs = Vec::new();
+        let mut blend = None;
 
         loop {
             let (token, span) = self.lexer.next()?;
             match token {
                 Token::Program => programs.push(self.parse_program(span)?),
                 Token::Property => properties.push(self.parse_property(span)?),
+                Token::Blend => {
+                    if blend.is_some() {
+                        return Err(Error::DuplicateBlend(span));
+                    }
+
+                    blend = Some(self.parse_blend(span)?);
+                },
 
                 Token::EndOfFile => break,
 
@@ -35,9 +43,36 @@ impl<'a> Parser<'a> {
         Ok(MaterialSource {
             properties: properties,
             programs: programs,
+            blend: blend,
         })
     }
 
+    /// Parses a blend item.
+    ///
+    /// # Preconditions
+    ///
+    /// - The "blend" keyword was already pulled from the lexer.
+    fn parse_blend(&mut self, _start_span: Span) -> Result<BlendMode, Error> {
+        let (token, span) = self.lexer.next()?;
+        let blend_mode = match token {
+            Token::Identifier => match &self.source[span] {
+                "alpha" => BlendMode::Alpha,
+                "premultiplied" => BlendMode::Premultiplied,
+                "additive" => BlendMode::Additive,
+                _ => return Err(Error::BadBlendMode(span)),
+            },
+            _ => return Err(Error::ExpectedIdent(span)),
+        };
+
+        let (token, span) = self.lexer.next()?;
+        match token {
+            Token::SemiColon => {},
+            _ => return Err(Error::ExpectedSemiColon(span)),
+        }
+
+        Ok(blend_mode)
+    }
+
     /// Parses a property item.
     ///
     /// # Preconditions
@@ -117,6 +152,8 @@ pub enum Error {
     ExpectedSemiColon(Span),
     BadPropertyType(Span),
     BadProgramType(Span),
+    BadBlendMode(Span),
+    DuplicateBlend(Span),
 }
 
 impl From<TokenError> for Error {
@@ -6,6 +6,7 @@ pub enum Token {
     /* Keywords */
     Program,
     Property,
+    Blend,
 
     /* Operator symbols */
     Eq,
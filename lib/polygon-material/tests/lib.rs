@@ -3,7 +3,7 @@
 extern crate polygon_material as material;
 
 use material::lexer::{Error as TokenError, ErrorData, Lexer};
-use material::material_source::{PropertySource, PropertyType, ProgramSource, MaterialSource, Error as MaterialSourceError};
+use material::material_source::{BlendMode, PropertySource, PropertyType, ProgramSource, MaterialSource, Error as MaterialSourceError};
 use material::parser::Error as ParseError;
 use material::token::*;
 
@@ -77,6 +77,7 @@ fn lex_properties() {
             }
         ],
         programs: vec![],
+        blend: None,
     });
 
     verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
@@ -133,6 +134,7 @@ fn lex_program() {
             ProgramSource::Vertex(" foo.bar(); ".to_string()),
             ProgramSource::Fragment("\n            fn program keyworkds do_stuff() {\n                bar.foo();\n            }\n        ".to_string()),
         ],
+        blend: None,
     });
 
     verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
@@ -167,3 +169,37 @@ fn lex_program_error() {
 
     verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
 }
+
+#[test]
+fn lex_blend() {
+    static SOURCE: &'static str = r#"
+        blend alpha;
+        program frag { @color = vec4(1.0, 1.0, 1.0, 0.5); }
+    "#;
+
+    static EXPECTED_TOKENS: &'static [Result<(Token, &'static str), (ErrorData, &'static str)>] = &[
+        Ok((Token::Blend, "blend")),
+        Ok((Token::Identifier, "alpha")),
+        Ok((Token::SemiColon, ";")),
+
+        Ok((Token::Program, "program")),
+        Ok((Token::Identifier, "frag")),
+        Ok((Token::ProgramLiteral, " @color = vec4(1.0, 1.0, 1.0, 0.5); ")),
+
+        Ok((Token::EndOfFile, "")),
+    ];
+
+    let expected_material = Ok(MaterialSource {
+        properties: vec![],
+        programs: vec![
+            ProgramSource::Fragment(" @color = vec4(1.0, 1.0, 1.0, 0.5); ".to_string()),
+        ],
+        blend: Some(BlendMode::Alpha),
+    });
+
+    verify_lexer(SOURCE, EXPECTED_TOKENS, expected_material);
+
+    assert_eq!(
+        Err(MaterialSourceError::ParseError(ParseError::BadBlendMode(Span::new(6, 14)))),
+        MaterialSource::from_str("blend multiply;"));
+}
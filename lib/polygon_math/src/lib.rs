@@ -7,6 +7,7 @@ pub mod matrix;
 pub mod orientation;
 pub mod point;
 pub mod quaternion;
+pub mod ray;
 pub mod vector;
 
 #[cfg(test)]
@@ -16,6 +17,7 @@ pub use color::Color;
 pub use matrix::{Matrix3, Matrix4};
 pub use orientation::Orientation;
 pub use point::Point;
+pub use ray::Ray;
 pub use std::f32::consts::PI;
 pub use vector::{Vector2, Vector3};
 
@@ -81,6 +81,13 @@ impl Matrix4 {
         q.into()
     }
 
+    /// Creates a new rotation matrix from a quaternion.
+    ///
+    /// `q` should be normalized.
+    pub fn from_quaternion(q: Quaternion) -> Matrix4 {
+        q.into()
+    }
+
     pub fn from_matrix3(other: Matrix3) -> Matrix4 {
         Matrix4([
             [other[0][0], other[0][1], other[0][2], 0.0],
@@ -109,6 +116,194 @@ impl Matrix4 {
         ])
     }
 
+    /// Creates a transform matrix from a translation, rotation, and scale.
+    ///
+    /// The resulting matrix applies the scale first, then the rotation, then the translation.
+    /// `rotation` should be normalized.
+    pub fn from_trs(translation: Point, rotation: Quaternion, scale: Vector3) -> Matrix4 {
+        Matrix4::from_point(translation)
+      * Matrix4::from(Orientation(rotation))
+      * Matrix4::from_scale_vector(scale)
+    }
+
+    /// Decomposes an affine transform matrix into its translation, rotation, and scale.
+    ///
+    /// This is the inverse of `from_trs()`, so for any well-formed affine transform
+    /// `Matrix4::from_trs(t, r, s)` decomposes to a translation, rotation, and scale that
+    /// recompose to the same matrix.
+    ///
+    /// Returns `None` if the matrix isn't affine, i.e. if its bottom row isn't `[0, 0, 0, 1]`.
+    ///
+    /// # Details
+    ///
+    /// - If the matrix contains a reflection (i.e. it has an odd number of negative scale
+    ///   components) the returned scale is negated on all three axes and the rotation is adjusted
+    ///   to compensate. The result recomposes to the original matrix, but may not match the
+    ///   scale and rotation the matrix was originally created from.
+    /// - Axes with a (near) zero scale have a scale of `0.0` and the rotation is chosen to be
+    ///   consistent with the remaining axes. If all three axes have zero scale the rotation is
+    ///   the identity.
+    /// - Any shear in the matrix is discarded.
+    pub fn decompose(&self) -> Option<(Point, Quaternion, Vector3)> {
+        // Scale below this threshold is treated as zero, since the direction of the axis can't be
+        // reliably recovered.
+        const SCALE_EPSILON: f32 = 1e-6;
+
+        if !self[3][0].is_zero()
+        || !self[3][1].is_zero()
+        || !self[3][2].is_zero()
+        || !(self[3][3] - 1.0).is_zero()
+        {
+            return None;
+        }
+
+        let translation = self.translation_part();
+
+        let mut axes = [self.x_part(), self.y_part(), self.z_part()];
+        let mut scale = Vector3::zero();
+        let mut valid = [false; 3];
+
+        // Extract the scale for each axis, and orthonormalize the axes with Gram-Schmidt to
+        // remove any shear (and numerical drift).
+        for index in 0..3 {
+            scale[index] = axes[index].magnitude();
+            if scale[index] < SCALE_EPSILON {
+                scale[index] = 0.0;
+                continue;
+            }
+
+            let mut axis = axes[index] / scale[index];
+            for other in 0..index {
+                if valid[other] {
+                    axis = axis - axes[other] * axis.dot(axes[other]);
+                }
+            }
+
+            let len = axis.magnitude();
+            if len >= SCALE_EPSILON {
+                axes[index] = axis / len;
+                valid[index] = true;
+            }
+        }
+
+        // Reconstruct any axes that were degenerate so that the axes still form a rotation.
+        let valid_count = valid.iter().filter(|&&valid| valid).count();
+        match valid_count {
+            0 => {
+                axes = [Vector3::right(), Vector3::up(), Vector3::forward()];
+            },
+            1 => {
+                let first = valid.iter().position(|&valid| valid).unwrap();
+                let second = (first + 1) % 3;
+                let third = (first + 2) % 3;
+                axes[second] = any_perpendicular(axes[first]);
+                axes[third] = Vector3::cross(axes[first], axes[second]);
+            },
+            2 => {
+                let missing = valid.iter().position(|&valid| !valid).unwrap();
+                axes[missing] = Vector3::cross(axes[(missing + 1) % 3], axes[(missing + 2) % 3]);
+            },
+            _ => {
+                // A left-handed basis means the matrix contains a reflection, which can't be
+                // represented by a rotation. Move it into the scale instead.
+                if Vector3::cross(axes[0], axes[1]).dot(axes[2]) < 0.0 {
+                    scale = -scale;
+                    for axis in &mut axes {
+                        *axis = -*axis;
+                    }
+                }
+            },
+        }
+
+        let rotation = Matrix3([
+            [axes[0].x, axes[1].x, axes[2].x],
+            [axes[0].y, axes[1].y, axes[2].y],
+            [axes[0].z, axes[1].z, axes[2].z],
+        ]);
+
+        Some((translation, rotation_to_quaternion(&rotation), scale))
+    }
+
+    /// Calculates the determinant of the matrix.
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.sub_determinants();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    /// Calculates the inverse of the matrix.
+    ///
+    /// Returns `None` if the matrix is singular (i.e. its determinant is zero), in which case it
+    /// has no inverse. Only an exactly-zero (or non-finite) determinant is rejected, since matrices
+    /// with small scales can have very small determinants while still being invertible.
+    ///
+    /// Details
+    /// -------
+    ///
+    /// Unlike `transpose()`, which only inverts pure rotation matrices, this works for any
+    /// invertible matrix, including transforms with non-uniform scale and projection matrices.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let (s, c) = self.sub_determinants();
+        let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let m = &self.0;
+        let inv_det = 1.0 / det;
+        Some(Matrix4([
+            [
+                ( m[1][1] * c[5] - m[1][2] * c[4] + m[1][3] * c[3]) * inv_det,
+                (-m[0][1] * c[5] + m[0][2] * c[4] - m[0][3] * c[3]) * inv_det,
+                ( m[3][1] * s[5] - m[3][2] * s[4] + m[3][3] * s[3]) * inv_det,
+                (-m[2][1] * s[5] + m[2][2] * s[4] - m[2][3] * s[3]) * inv_det,
+            ],
+            [
+                (-m[1][0] * c[5] + m[1][2] * c[2] - m[1][3] * c[1]) * inv_det,
+                ( m[0][0] * c[5] - m[0][2] * c[2] + m[0][3] * c[1]) * inv_det,
+                (-m[3][0] * s[5] + m[3][2] * s[2] - m[3][3] * s[1]) * inv_det,
+                ( m[2][0] * s[5] - m[2][2] * s[2] + m[2][3] * s[1]) * inv_det,
+            ],
+            [
+                ( m[1][0] * c[4] - m[1][1] * c[2] + m[1][3] * c[0]) * inv_det,
+                (-m[0][0] * c[4] + m[0][1] * c[2] - m[0][3] * c[0]) * inv_det,
+                ( m[3][0] * s[4] - m[3][1] * s[2] + m[3][3] * s[0]) * inv_det,
+                (-m[2][0] * s[4] + m[2][1] * s[2] - m[2][3] * s[0]) * inv_det,
+            ],
+            [
+                (-m[1][0] * c[3] + m[1][1] * c[1] - m[1][2] * c[0]) * inv_det,
+                ( m[0][0] * c[3] - m[0][1] * c[1] + m[0][2] * c[0]) * inv_det,
+                (-m[3][0] * s[3] + m[3][1] * s[1] - m[3][2] * s[0]) * inv_det,
+                ( m[2][0] * s[3] - m[2][1] * s[1] + m[2][2] * s[0]) * inv_det,
+            ],
+        ]))
+    }
+
+    /// Calculates the determinants of the 2x2 sub-matrices used by `determinant()` and
+    /// `inverse()`.
+    ///
+    /// The first array holds the determinants taken from the top two rows, the second holds the
+    /// determinants taken from the bottom two rows.
+    fn sub_determinants(&self) -> ([f32; 6], [f32; 6]) {
+        let m = &self.0;
+        let s = [
+            m[0][0] * m[1][1] - m[1][0] * m[0][1],
+            m[0][0] * m[1][2] - m[1][0] * m[0][2],
+            m[0][0] * m[1][3] - m[1][0] * m[0][3],
+            m[0][1] * m[1][2] - m[1][1] * m[0][2],
+            m[0][1] * m[1][3] - m[1][1] * m[0][3],
+            m[0][2] * m[1][3] - m[1][2] * m[0][3],
+        ];
+        let c = [
+            m[2][0] * m[3][1] - m[3][0] * m[2][1],
+            m[2][0] * m[3][2] - m[3][0] * m[2][2],
+            m[2][0] * m[3][3] - m[3][0] * m[2][3],
+            m[2][1] * m[3][2] - m[3][1] * m[2][2],
+            m[2][1] * m[3][3] - m[3][1] * m[2][3],
+            m[2][2] * m[3][3] - m[3][2] * m[2][3],
+        ];
+        (s, c)
+    }
+
     pub fn transpose(&self) -> Matrix4 {
         let mut transpose = *self;
         for row in 0..4 {
@@ -146,6 +341,63 @@ impl Matrix4 {
     }
 }
 
+/// Returns a normalized vector perpendicular to `vector`, which must be normalized.
+fn any_perpendicular(vector: Vector3) -> Vector3 {
+    // Cross with whichever basis vector is least parallel to `vector` for best precision.
+    let other = if vector.x.abs() < 0.9 { Vector3::right() } else { Vector3::up() };
+    Vector3::cross(vector, other).normalized()
+}
+
+/// Converts a pure rotation matrix into a quaternion.
+///
+/// Branches on the largest diagonal element to avoid dividing by a value near zero.
+fn rotation_to_quaternion(m: &Matrix3) -> Quaternion {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    let quaternion = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        Quaternion::new(
+            Vector3::new(
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            ),
+            0.25 * s,
+        )
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+        let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+        Quaternion::new(
+            Vector3::new(
+                0.25 * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            ),
+            (m[2][1] - m[1][2]) / s,
+        )
+    } else if m[1][1] > m[2][2] {
+        let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+        Quaternion::new(
+            Vector3::new(
+                (m[0][1] + m[1][0]) / s,
+                0.25 * s,
+                (m[1][2] + m[2][1]) / s,
+            ),
+            (m[0][2] - m[2][0]) / s,
+        )
+    } else {
+        let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+        Quaternion::new(
+            Vector3::new(
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                0.25 * s,
+            ),
+            (m[1][0] - m[0][1]) / s,
+        )
+    };
+
+    quaternion.normalized()
+}
+
 impl From<Matrix3> for Matrix4 {
     fn from(from: Matrix3) -> Matrix4 {
         Matrix4([
@@ -157,6 +409,12 @@ impl From<Matrix3> for Matrix4 {
     }
 }
 
+impl From<Quaternion> for Matrix4 {
+    fn from(q: Quaternion) -> Matrix4 {
+        Orientation(q).into()
+    }
+}
+
 impl From<Orientation> for Matrix4 {
     fn from(q: Orientation) -> Matrix4 {
         let Orientation(Quaternion { v: Vector3 { x, y, z }, w }) = q;
@@ -353,10 +611,27 @@ impl Matrix3 {
         transpose
     }
 
+    /// Creates a new rotation matrix from a quaternion.
+    ///
+    /// `q` should be normalized.
+    pub fn from_quaternion(q: Quaternion) -> Matrix3 {
+        q.into()
+    }
+
     pub fn as_matrix4(&self) -> Matrix4 {
         Matrix4::from_matrix3(*self)
     }
 
+    /// Converts a rotation matrix to the equivalent quaternion.
+    ///
+    /// The matrix must be a pure rotation (i.e. orthonormal with a determinant of 1), otherwise
+    /// the result is meaningless. The returned quaternion is normalized, but note that `q` and
+    /// `-q` represent the same rotation so converting a quaternion to a matrix and back may
+    /// return the negated quaternion.
+    pub fn as_quaternion(&self) -> Quaternion {
+        rotation_to_quaternion(self)
+    }
+
     pub fn x_part(&self) -> Vector3 {
         Vector3::new(self[0][0], self[1][0], self[2][0])
     }
@@ -401,6 +676,24 @@ impl From<Orientation> for Matrix3 {
     }
 }
 
+impl From<Quaternion> for Matrix3 {
+    fn from(q: Quaternion) -> Matrix3 {
+        Orientation(q).into()
+    }
+}
+
+impl From<Matrix3> for Quaternion {
+    fn from(from: Matrix3) -> Quaternion {
+        from.as_quaternion()
+    }
+}
+
+impl From<Matrix3> for Orientation {
+    fn from(from: Matrix3) -> Orientation {
+        Orientation(from.as_quaternion())
+    }
+}
+
 impl Index<usize> for Matrix3 {
     type Output = [f32; 3];
 
@@ -180,6 +180,12 @@ impl Orientation {
     }
 }
 
+impl From<Quaternion> for Orientation {
+    fn from(from: Quaternion) -> Orientation {
+        Orientation(from)
+    }
+}
+
 impl Default for Orientation {
     fn default() -> Orientation {
         Orientation::new()
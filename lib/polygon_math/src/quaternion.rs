@@ -16,7 +16,7 @@ use orientation::Orientation;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
 
 use vector::Vector3;
-use super::{IsZero, Dot};
+use super::{IsZero, Dot, Lerp};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Quaternion {
@@ -99,6 +99,55 @@ impl Quaternion {
         first + (second - first) * t
     }
 
+    /// Interpolates linearly between two rotations, normalizing the result.
+    ///
+    /// `nlerp()` is cheaper than `slerp()`, but doesn't rotate at a constant angular velocity
+    /// as `t` changes. The interpolation always takes the shortest path between the two
+    /// rotations. Both quaternions must not have a length of zero.
+    pub fn nlerp(t: f32, from: Quaternion, to: Quaternion) -> Quaternion {
+        let from = from.normalized();
+        let mut to = to.normalized();
+        if Quaternion::dot(from, to) < 0.0 {
+            to = to * -1.0;
+        }
+
+        Quaternion::lerp(from, to, t).normalized()
+    }
+
+    /// Interpolates spherically between two rotations.
+    ///
+    /// Unlike `nlerp()` the result rotates at a constant angular velocity as `t` goes from 0 to 1,
+    /// which makes it better suited for animating rotations. The interpolation always takes the
+    /// shortest path between the two rotations. Both quaternions must not have a length of zero.
+    ///
+    /// # Remarks
+    ///
+    /// When the two rotations are nearly parallel this falls back to `nlerp()`, since the two
+    /// give the same result and the slerp calculation would divide by a value close to zero.
+    pub fn slerp(t: f32, from: Quaternion, to: Quaternion) -> Quaternion {
+        // Above this cosine the angle between the quaternions is small enough to use nlerp.
+        const NLERP_THRESHOLD: f32 = 0.9995;
+
+        let from = from.normalized();
+        let mut to = to.normalized();
+
+        // `q` and `-q` represent the same rotation, so flip `to` if needed to take the shorter
+        // path around the hypersphere.
+        let mut cos_theta = Quaternion::dot(from, to);
+        if cos_theta < 0.0 {
+            to = to * -1.0;
+            cos_theta = -cos_theta;
+        }
+
+        if cos_theta > NLERP_THRESHOLD {
+            return Quaternion::lerp(from, to, t).normalized();
+        }
+
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        from * (((1.0 - t) * theta).sin() / sin_theta) + to * ((t * theta).sin() / sin_theta)
+    }
+
     pub fn inverse(self) -> Quaternion {
         (1.0 / self.len_sqr()) * self.conjugate()
     }
@@ -124,6 +173,19 @@ impl Default for Quaternion {
     }
 }
 
+/// Interpolates between two rotations using `Quaternion::slerp()`.
+///
+/// Note that this is *not* the same as the inherent `Quaternion::lerp()`, which interpolates
+/// linearly, takes its arguments in a different order, and doesn't normalize the result. Generic
+/// code that uses `Lerp` (e.g. animation curves) gets spherical interpolation, which is what
+/// rotations need. Calling `Quaternion::lerp(a, b, t)` directly resolves to the inherent method;
+/// use `<Quaternion as Lerp>::lerp(t, a, b)` or call `slerp()` to get the trait behavior.
+impl Lerp for Quaternion {
+    fn lerp(t: f32, from: Quaternion, to: Quaternion) -> Quaternion {
+        Quaternion::slerp(t, from, to)
+    }
+}
+
 impl Add for Quaternion {
     type Output = Quaternion;
 
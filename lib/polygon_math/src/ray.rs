@@ -0,0 +1,59 @@
+use matrix::Matrix4;
+use point::Point;
+use vector::{Vector2, Vector3};
+
+/// A ray in 3D space, starting at `origin` and extending infinitely in `direction`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+
+    /// The direction of the ray, which is normalized.
+    pub direction: Vector3,
+}
+
+impl Ray {
+    /// Creates a new ray, normalizing `direction`.
+    pub fn new(origin: Point, direction: Vector3) -> Ray {
+        Ray {
+            origin: origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    /// Returns the point `distance` units along the ray.
+    pub fn point_at(&self, distance: f32) -> Point {
+        self.origin + self.direction * distance
+    }
+}
+
+/// Converts a screen coordinate into a world-space ray, e.g. for mouse picking.
+///
+/// `screen` is in pixels with the origin at the top-left corner of a viewport that is
+/// `viewport.0` pixels wide and `viewport.1` pixels tall, which matches how mouse positions are
+/// usually reported. `view` and `projection` are the camera's view and projection matrices.
+///
+/// The returned ray starts on the camera's near plane and points towards the far plane.
+///
+/// Returns `None` if the combined view-projection matrix isn't invertible, which can only happen
+/// if the camera's matrices are degenerate.
+pub fn unproject(screen: Vector2, viewport: (u32, u32), view: Matrix4, projection: Matrix4) -> Option<Ray> {
+    let inverse = match (projection * view).inverse() {
+        Some(inverse) => inverse,
+        None => return None,
+    };
+
+    // Convert to normalized device coordinates, flipping y since NDC has y pointing up.
+    let ndc_x = 2.0 * screen.x / viewport.0 as f32 - 1.0;
+    let ndc_y = 1.0 - 2.0 * screen.y / viewport.1 as f32;
+
+    let near = unproject_point(Point::new(ndc_x, ndc_y, -1.0), inverse);
+    let far = unproject_point(Point::new(ndc_x, ndc_y, 1.0), inverse);
+
+    Some(Ray::new(near, far - near))
+}
+
+/// Transforms a point from clip space into world space, applying the perspective divide.
+fn unproject_point(point: Point, inverse_view_projection: Matrix4) -> Point {
+    let world = point * inverse_view_projection;
+    Point::new(world.x / world.w, world.y / world.w, world.z / world.w)
+}
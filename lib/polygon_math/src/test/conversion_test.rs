@@ -0,0 +1,110 @@
+use std::f32::consts::PI;
+
+use matrix::Matrix3;
+use orientation::Orientation;
+use quaternion::Quaternion;
+use vector::Vector3;
+
+/// A set of rotations that covers every branch of the matrix-to-quaternion conversion, including
+/// half turns where the trace of the rotation matrix is negative.
+fn test_rotations() -> Vec<Orientation>
+{
+    let diagonal = Vector3::new(1.0, 1.0, 1.0).normalized();
+    let skewed = Vector3::new(-0.3, 0.5, 0.8).normalized();
+
+    vec![
+        Orientation::new(),
+        Orientation::axis_angle(Vector3::right(), PI * 0.5),
+        Orientation::axis_angle(Vector3::up(), PI * 0.5),
+        Orientation::axis_angle(Vector3::forward(), PI * 0.5),
+        Orientation::axis_angle(Vector3::right(), PI),
+        Orientation::axis_angle(Vector3::up(), PI),
+        Orientation::axis_angle(Vector3::forward(), PI),
+        Orientation::axis_angle(diagonal, PI * 0.9),
+        Orientation::axis_angle(skewed, 2.5),
+        Orientation::axis_angle(skewed, -0.1),
+    ]
+}
+
+/// Compares two quaternions, treating `q` and `-q` as equal since they represent the same
+/// rotation.
+fn assert_same_rotation(expected: Quaternion, actual: Quaternion)
+{
+    assert!(
+        Quaternion::dot(expected, actual).abs() > 1.0 - 1e-5,
+        "Quaternions represent different rotations, expected: {:?}, actual: {:?}",
+        expected,
+        actual);
+}
+
+fn assert_matrix3_approx_eq(expected: Matrix3, actual: Matrix3)
+{
+    for row in 0..3 {
+        for col in 0..3 {
+            assert!(
+                (expected[row][col] - actual[row][col]).abs() < 1e-5,
+                "Matrices differ at [{}][{}], expected: {:?}, actual: {:?}",
+                row,
+                col,
+                expected,
+                actual);
+        }
+    }
+}
+
+#[test]
+fn quaternion_matrix_round_trip()
+{
+    for orientation in test_rotations() {
+        let quaternion = Quaternion::from(orientation);
+
+        let matrix = Matrix3::from_quaternion(quaternion);
+        assert_matrix3_approx_eq(Matrix3::from(orientation), matrix);
+
+        let round_trip = Quaternion::from(matrix);
+        assert!(round_trip.is_normalized());
+        assert_same_rotation(quaternion, round_trip);
+    }
+}
+
+#[test]
+fn orientation_round_trip()
+{
+    for orientation in test_rotations() {
+        // Orientation -> Quaternion -> Orientation.
+        let quaternion: Quaternion = orientation.into();
+        assert_eq!(orientation, Orientation::from(quaternion));
+
+        // Orientation -> Matrix3 -> Orientation.
+        let matrix: Matrix3 = orientation.into();
+        let round_trip = Orientation::from(matrix);
+        assert_same_rotation(orientation.0, round_trip.0);
+
+        // Orientation -> Matrix3 -> Quaternion -> Matrix3 gives back the same matrix.
+        assert_matrix3_approx_eq(matrix, Matrix3::from_quaternion(matrix.as_quaternion()));
+    }
+}
+
+#[test]
+fn matrix_round_trip()
+{
+    let matrices = [
+        Matrix3::identity(),
+        Matrix3::rotation(PI, 0.0, 0.0),
+        Matrix3::rotation(0.0, PI, 0.0),
+        Matrix3::rotation(0.0, 0.0, PI),
+        Matrix3::rotation(0.3, -1.2, 2.9),
+        Matrix3::rotation(-2.0, 0.7, 0.1),
+    ];
+
+    for &matrix in &matrices {
+        let quaternion = matrix.as_quaternion();
+        assert_matrix3_approx_eq(matrix, Matrix3::from(quaternion));
+
+        // The rotated vectors match too, not just the matrix elements.
+        let vector = Vector3::new(0.2, -1.0, 3.0);
+        let expected = vector * matrix;
+        let actual = Orientation(quaternion) * vector;
+        assert!((expected - actual).magnitude() < 1e-4, "expected: {:?}, actual: {:?}", expected, actual);
+    }
+}
@@ -1,6 +1,36 @@
 use matrix::Matrix4;
+use orientation::Orientation;
+use point::Point;
+use vector::Vector3;
 use super::test::{Bencher, black_box};
 
+/// Compares two matrices with a tolerance loose enough to absorb the error from decomposing and
+/// recomposing a matrix.
+fn assert_matrix_approx_eq(expected: Matrix4, actual: Matrix4)
+{
+    for row in 0..4 {
+        for col in 0..4 {
+            assert!(
+                (expected[row][col] - actual[row][col]).abs() < 1e-4,
+                "Matrices differ at [{}][{}], expected: {:?}, actual: {:?}",
+                row,
+                col,
+                expected,
+                actual);
+        }
+    }
+}
+
+/// Builds a matrix from its rows.
+fn matrix_from_rows(rows: [[f32; 4]; 4]) -> Matrix4
+{
+    let mut matrix = Matrix4::new();
+    for row in 0..4 {
+        matrix[row] = rows[row];
+    }
+    matrix
+}
+
 #[test]
 fn matrix_equality()
 {
@@ -58,6 +88,141 @@ fn matrix_translation()
     assert!(translation_2[3][3] == 1.0);
 }
 
+#[test]
+fn matrix_decompose_round_trip()
+{
+    let rotation = Orientation::axis_angle(Vector3::new(1.0, 2.0, 3.0).normalized(), 1.2).0;
+    let matrix = Matrix4::from_trs(
+        Point::new(1.0, -2.0, 3.0),
+        rotation,
+        Vector3::new(2.0, 0.5, 3.0));
+
+    let (translation, decomposed_rotation, scale) = matrix.decompose().unwrap();
+    assert_eq!(Point::new(1.0, -2.0, 3.0), translation);
+    assert!((scale.x - 2.0).abs() < 1e-4);
+    assert!((scale.y - 0.5).abs() < 1e-4);
+    assert!((scale.z - 3.0).abs() < 1e-4);
+    assert_matrix_approx_eq(matrix, Matrix4::from_trs(translation, decomposed_rotation, scale));
+}
+
+#[test]
+fn matrix_decompose_negative_scale()
+{
+    let rotation = Orientation::axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.7).0;
+
+    // A single negative axis is a reflection, two negative axes is equivalent to a rotation.
+    for &scale in &[
+        Vector3::new(-1.0, 1.0, 1.0),
+        Vector3::new(2.0, -3.0, 0.5),
+        Vector3::new(-2.0, -3.0, 0.5),
+        Vector3::new(-1.0, -1.0, -1.0),
+    ] {
+        let matrix = Matrix4::from_trs(Point::new(0.0, 5.0, 0.0), rotation, scale);
+        let (translation, decomposed_rotation, decomposed_scale) = matrix.decompose().unwrap();
+        assert!(decomposed_rotation.is_normalized());
+        assert_matrix_approx_eq(
+            matrix,
+            Matrix4::from_trs(translation, decomposed_rotation, decomposed_scale));
+    }
+}
+
+#[test]
+fn matrix_decompose_zero_scale()
+{
+    let rotation = Orientation::axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.3).0;
+
+    for &scale in &[
+        Vector3::new(0.0, 1.0, 2.0),
+        Vector3::new(0.0, 0.0, 2.0),
+        Vector3::new(0.0, 0.0, 0.0),
+    ] {
+        let matrix = Matrix4::from_trs(Point::origin(), rotation, scale);
+        let (translation, decomposed_rotation, decomposed_scale) = matrix.decompose().unwrap();
+        assert!(decomposed_rotation.is_normalized());
+        assert_matrix_approx_eq(
+            matrix,
+            Matrix4::from_trs(translation, decomposed_rotation, decomposed_scale));
+    }
+}
+
+#[test]
+fn matrix_decompose_non_affine()
+{
+    let mut matrix = Matrix4::identity();
+    matrix[3][2] = 1.0;
+    assert!(matrix.decompose().is_none());
+}
+
+#[test]
+fn matrix_determinant()
+{
+    assert_eq!(1.0, Matrix4::identity().determinant());
+    assert_eq!(24.0, Matrix4::scale(2.0, 3.0, 4.0).determinant());
+    assert_eq!(1.0, Matrix4::translation(1.0, 2.0, 3.0).determinant());
+
+    let matrix = matrix_from_rows([
+        [1.0, 0.0, 2.0, -1.0],
+        [3.0, 0.0, 0.0,  5.0],
+        [2.0, 1.0, 4.0, -3.0],
+        [1.0, 0.0, 5.0,  0.0],
+    ]);
+    assert_eq!(30.0, matrix.determinant());
+}
+
+#[test]
+fn matrix_inverse()
+{
+    let rotation = Orientation::axis_angle(Vector3::new(1.0, 2.0, 3.0).normalized(), 1.2).0;
+    let matrix = Matrix4::from_trs(
+        Point::new(1.0, -2.0, 3.0),
+        rotation,
+        Vector3::new(2.0, 0.5, 3.0));
+
+    let inverse = matrix.inverse().unwrap();
+    assert_matrix_approx_eq(Matrix4::identity(), matrix * inverse);
+    assert_matrix_approx_eq(Matrix4::identity(), inverse * matrix);
+
+    // The inverse of a non-uniform scale is the reciprocal scale, which `transpose()` gets wrong.
+    assert_matrix_approx_eq(
+        Matrix4::scale(0.5, 0.25, 2.0),
+        Matrix4::scale(2.0, 4.0, 0.5).inverse().unwrap());
+
+    let matrix = matrix_from_rows([
+        [1.0, 0.0, 2.0, -1.0],
+        [3.0, 0.0, 0.0,  5.0],
+        [2.0, 1.0, 4.0, -3.0],
+        [1.0, 0.0, 5.0,  0.0],
+    ]);
+    assert_matrix_approx_eq(Matrix4::identity(), matrix * matrix.inverse().unwrap());
+}
+
+#[test]
+fn matrix_inverse_small_scale()
+{
+    // The determinant is 1.25e-7, which is tiny but the matrix is still invertible.
+    let matrix = Matrix4::from_scale_vector(Vector3::new(0.005, 0.005, 0.005));
+    let inverse = matrix.inverse().unwrap();
+    assert_matrix_approx_eq(Matrix4::scale(200.0, 200.0, 200.0), inverse);
+    assert_matrix_approx_eq(Matrix4::identity(), matrix * inverse);
+}
+
+#[test]
+fn matrix_inverse_singular()
+{
+    // Two identical rows.
+    let matrix = matrix_from_rows([
+        [1.0, 2.0, 3.0, 4.0],
+        [1.0, 2.0, 3.0, 4.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    assert_eq!(0.0, matrix.determinant());
+    assert!(matrix.inverse().is_none());
+
+    assert!(Matrix4::scale(1.0, 0.0, 1.0).inverse().is_none());
+    assert!(Matrix4::new().inverse().is_none());
+}
+
 #[bench]
 fn bench_multiply(bencher: &mut Bencher) {
     let first = Matrix4::identity();
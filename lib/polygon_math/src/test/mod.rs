@@ -1,4 +1,7 @@
 extern crate test;
 
+mod conversion_test;
 mod matrix_test;
+mod quaternion_lerp_test;
 mod quaternion_test;
+mod ray_test;
@@ -0,0 +1,88 @@
+use orientation::Orientation;
+use quaternion::Quaternion;
+use vector::Vector3;
+use Lerp;
+
+fn assert_rotation_approx_eq(expected: Quaternion, actual: Quaternion) {
+    // `q` and `-q` are the same rotation, so compare using the dot product.
+    assert!(
+        Quaternion::dot(expected, actual).abs() > 1.0 - 1e-5,
+        "Rotations differ, expected: {:?}, actual: {:?}",
+        expected,
+        actual);
+}
+
+fn axis_angle(axis: Vector3, angle: f32) -> Quaternion {
+    Orientation::axis_angle(axis, angle).0
+}
+
+#[test]
+fn slerp_endpoints() {
+    let from = axis_angle(Vector3::up(), 0.2);
+    let to = axis_angle(Vector3::new(1.0, 1.0, 0.0).normalized(), 2.0);
+
+    assert_rotation_approx_eq(from, Quaternion::slerp(0.0, from, to));
+    assert_rotation_approx_eq(to, Quaternion::slerp(1.0, from, to));
+}
+
+#[test]
+fn slerp_constant_velocity() {
+    let from = Quaternion::identity();
+    let to = axis_angle(Vector3::up(), 2.0);
+
+    for &t in &[0.1, 0.25, 0.5, 0.9] {
+        let result = Quaternion::slerp(t, from, to);
+        assert!(result.is_normalized());
+        assert_rotation_approx_eq(axis_angle(Vector3::up(), 2.0 * t), result);
+    }
+}
+
+#[test]
+fn slerp_shortest_path() {
+    // Negating a quaternion gives the same rotation, slerp should still take the short path.
+    let from = Quaternion::identity();
+    let to = axis_angle(Vector3::up(), 1.0) * -1.0;
+
+    assert_rotation_approx_eq(axis_angle(Vector3::up(), 0.5), Quaternion::slerp(0.5, from, to));
+}
+
+#[test]
+fn slerp_nearly_parallel() {
+    let from = axis_angle(Vector3::up(), 1.0);
+    let to = axis_angle(Vector3::up(), 1.0 + 1e-4);
+
+    let result = Quaternion::slerp(0.5, from, to);
+    assert!(result.is_normalized());
+    assert_rotation_approx_eq(from, result);
+
+    // Identical rotations would divide by zero without the fallback.
+    assert_rotation_approx_eq(from, Quaternion::slerp(0.5, from, from));
+}
+
+#[test]
+fn slerp_normalizes_inputs() {
+    let from = Quaternion::identity() * 2.0;
+    let to = axis_angle(Vector3::up(), 1.0) * 0.5;
+
+    let result = Quaternion::slerp(0.5, from, to);
+    assert!(result.is_normalized());
+    assert_rotation_approx_eq(axis_angle(Vector3::up(), 0.5), result);
+}
+
+#[test]
+fn lerp_trait_uses_slerp() {
+    let from = Quaternion::identity();
+    let to = axis_angle(Vector3::up(), 2.0);
+
+    assert_eq!(Quaternion::slerp(0.3, from, to), <Quaternion as Lerp>::lerp(0.3, from, to));
+}
+
+#[test]
+fn nlerp_shortest_path() {
+    let from = Quaternion::identity();
+    let to = axis_angle(Vector3::up(), 1.0) * -1.0;
+
+    let result = Quaternion::nlerp(0.5, from, to);
+    assert!(result.is_normalized());
+    assert_rotation_approx_eq(axis_angle(Vector3::up(), 0.5), result);
+}
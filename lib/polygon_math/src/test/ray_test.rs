@@ -0,0 +1,82 @@
+use matrix::Matrix4;
+use orientation::Orientation;
+use point::Point;
+use ray::unproject;
+use vector::{Vector2, Vector3};
+
+const NEAR: f32 = 0.1;
+const FAR: f32 = 100.0;
+
+/// Builds a perspective projection matrix the same way polygon's `Camera` does.
+fn projection(fov: f32, aspect: f32) -> Matrix4 {
+    let height = 2.0 * NEAR * (fov * 0.5).tan();
+    let width = aspect * height;
+
+    let mut projection = Matrix4::new();
+    projection[0][0] = 2.0 * NEAR / width;
+    projection[1][1] = 2.0 * NEAR / height;
+    projection[2][2] = -(FAR + NEAR) / (FAR - NEAR);
+    projection[2][3] = -2.0 * FAR * NEAR / (FAR - NEAR);
+    projection[3][2] = -1.0;
+    projection
+}
+
+fn assert_vector_approx_eq(expected: Vector3, actual: Vector3) {
+    assert!(
+        (expected - actual).magnitude() < 1e-4,
+        "Vectors differ, expected: {:?}, actual: {:?}",
+        expected,
+        actual);
+}
+
+#[test]
+fn unproject_center() {
+    let ray = unproject(
+        Vector2::new(400.0, 300.0),
+        (800, 600),
+        Matrix4::identity(),
+        projection(1.0, 800.0 / 600.0)).unwrap();
+
+    assert_vector_approx_eq(Vector3::forward(), ray.direction);
+    assert_vector_approx_eq(Vector3::new(0.0, 0.0, -NEAR), ray.origin - Point::origin());
+}
+
+#[test]
+fn unproject_center_transformed_camera() {
+    let orientation = Orientation::axis_angle(Vector3::up(), 0.8);
+    let camera_transform = Matrix4::from_point(Point::new(1.0, 2.0, 3.0)) * Matrix4::from(orientation);
+    let view = camera_transform.inverse().unwrap();
+
+    let ray = unproject(Vector2::new(320.0, 240.0), (640, 480), view, projection(1.0, 640.0 / 480.0)).unwrap();
+
+    let forward = orientation.forward();
+    assert_vector_approx_eq(forward, ray.direction);
+    assert_vector_approx_eq(
+        Point::new(1.0, 2.0, 3.0) + forward * NEAR - Point::origin(),
+        ray.origin - Point::origin());
+}
+
+#[test]
+fn unproject_corners() {
+    // Rays through the top-left and bottom-right corners point up-left and down-right.
+    let projection = projection(1.0, 1.0);
+
+    let top_left = unproject(Vector2::new(0.0, 0.0), (100, 100), Matrix4::identity(), projection).unwrap();
+    assert!(top_left.direction.x < 0.0 && top_left.direction.y > 0.0 && top_left.direction.z < 0.0);
+
+    let bottom_right = unproject(Vector2::new(100.0, 100.0), (100, 100), Matrix4::identity(), projection).unwrap();
+    assert!(bottom_right.direction.x > 0.0 && bottom_right.direction.y < 0.0 && bottom_right.direction.z < 0.0);
+}
+
+#[test]
+fn unproject_small_scale_view() {
+    // A view matrix with a tiny scale has a tiny determinant, but can still be unprojected.
+    let view = Matrix4::scale(0.005, 0.005, 0.005);
+    let ray = unproject(Vector2::new(50.0, 50.0), (100, 100), view, projection(1.0, 1.0)).unwrap();
+    assert_vector_approx_eq(Vector3::forward(), ray.direction);
+}
+
+#[test]
+fn unproject_degenerate() {
+    assert!(unproject(Vector2::new(50.0, 50.0), (100, 100), Matrix4::new(), projection(1.0, 1.0)).is_none());
+}
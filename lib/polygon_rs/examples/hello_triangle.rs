@@ -30,7 +30,7 @@ fn main() {
         .unwrap();
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Create an anchor and register it with the renderer.
     let anchor = Anchor::new();
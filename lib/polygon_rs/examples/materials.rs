@@ -19,7 +19,7 @@ fn main() {
 
     // Load mesh data from an OBJ file and send it to the GPU.
     let mesh = utils::load_mesh("resources/meshes/epps_head.obj").unwrap();
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Load texture data from a BMP file and send it to the GPU.
     let texture = utils::load_texture("resources/textures/structured.bmp");
@@ -0,0 +1,123 @@
+extern crate bootstrap_rs as bootstrap;
+extern crate polygon;
+
+use bootstrap::window::*;
+use polygon::*;
+use polygon::anchor::*;
+use polygon::camera::*;
+use polygon::geometry::mesh::*;
+use polygon::light::*;
+use polygon::math::*;
+use polygon::mesh_instance::*;
+use polygon::texture::*;
+
+static VERTEX_POSITIONS: [f32; 16] = [
+    -1.0, -1.0, 0.0, 1.0,
+     1.0, -1.0, 0.0, 1.0,
+     1.0,  1.0, 0.0, 1.0,
+    -1.0,  1.0, 0.0, 1.0,
+];
+
+static TEXCOORDS: [f32; 8] = [
+    0.0, 0.0,
+    1.0, 0.0,
+    1.0, 1.0,
+    0.0, 1.0,
+];
+
+static INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+const NORMAL_MAP_SIZE: usize = 64;
+
+/// Builds a normal map with vertical ridges, so a light moving left and right across the quad
+/// makes the ridges visible. The left half of each ridge faces -x and the right half faces +x.
+fn ridge_normal_map() -> Texture2d {
+    let mut data = Vec::with_capacity(NORMAL_MAP_SIZE * NORMAL_MAP_SIZE);
+    for _ in 0..NORMAL_MAP_SIZE {
+        for column in 0..NORMAL_MAP_SIZE {
+            let normal = if column % 16 < 8 {
+                (64, 128, 221)
+            } else {
+                (192, 128, 221)
+            };
+            data.push(normal);
+        }
+    }
+
+    Texture2d::from_data(NORMAL_MAP_SIZE, NORMAL_MAP_SIZE, DataFormat::Rgb, TextureData::u8x3(data))
+}
+
+fn main() {
+    // Open a window and create the renderer instance.
+    let mut window = Window::new("Normal Mapping").unwrap();
+    let mut renderer = RendererBuilder::new(&window).build();
+
+    // Build a flat quad facing the camera. The tangent points along the direction of increasing
+    // u texcoord, which is +x for every vertex.
+    let normals = [Vector3::new(0.0, 0.0, 1.0); 4];
+    let tangents = [Vector3::new(1.0, 0.0, 0.0); 4];
+    let mesh = MeshBuilder::new()
+        .set_position_data(Point::slice_from_f32_slice(&VERTEX_POSITIONS))
+        .set_normal_data(&normals)
+        .set_tangent_data(&tangents)
+        .set_texcoord_data(Vector2::slice_from_f32_slice(&TEXCOORDS))
+        .set_indices(&INDICES)
+        .build()
+        .unwrap();
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
+
+    let gpu_normal_map = renderer.register_texture(&ridge_normal_map());
+
+    // Create an anchor and register it with the renderer.
+    let mesh_anchor_id = renderer.register_anchor(Anchor::new());
+
+    // The default material supports normal mapping through the `normal_map` property.
+    let mut material = renderer.default_material();
+    material.set_color("surface_color", Color::rgb(1.0, 1.0, 1.0));
+    material.set_color("surface_specular", Color::rgb(0.2, 0.2, 0.2));
+    material.set_f32("surface_shininess", 4.0);
+    material.set_texture("normal_map", gpu_normal_map);
+
+    let mut mesh_instance = MeshInstance::with_owned_material(gpu_mesh, material);
+    mesh_instance.set_anchor(mesh_anchor_id);
+    renderer.register_mesh_instance(mesh_instance);
+
+    // Create a camera and an anchor for it.
+    let mut camera_anchor = Anchor::new();
+    camera_anchor.set_position(Point::new(0.0, 0.0, 3.0));
+    let camera_anchor_id = renderer.register_anchor(camera_anchor);
+
+    let mut camera = Camera::default();
+    camera.set_anchor(camera_anchor_id);
+    renderer.register_camera(camera);
+
+    // Create the light and an anchor for it.
+    let light_anchor_id = renderer.register_anchor(Anchor::new());
+    let mut light = Light::point(LIGHT_RADIUS, 1.0, Color::new(1.0, 1.0, 1.0, 1.0));
+    light.set_anchor(light_anchor_id);
+    renderer.register_light(light);
+
+    const LIGHT_RADIUS: f32 = 3.0;
+
+    let mut t: f32 = 0.0;
+    'outer: loop {
+        while let Some(message) = window.next_message() {
+            match message {
+                Message::Close => break 'outer,
+                _ => {},
+            }
+        }
+
+        // Sweep the light back and forth across the quad. When the light is to the left the
+        // left-facing halves of the ridges should be lit, and vice versa.
+        {
+            let anchor = renderer.get_anchor_mut(light_anchor_id).unwrap();
+            anchor.set_position(Point::new(t.sin() * 1.5, 0.0, 0.5));
+        }
+
+        // Render the mesh.
+        renderer.draw();
+
+        t += 0.0005;
+    }
+}
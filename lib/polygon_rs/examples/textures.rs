@@ -22,7 +22,7 @@ fn main() {
     let texture = utils::load_texture("resources/textures/structured.bmp");
 
     // Send the mesh to the GPU.
-    let gpu_mesh = renderer.register_mesh(&mesh);
+    let gpu_mesh = renderer.register_mesh(&mesh).unwrap();
 
     // Send the texture to the GPU.
     let gpu_texture = renderer.register_texture(&texture);
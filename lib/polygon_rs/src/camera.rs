@@ -9,6 +9,8 @@ pub struct Camera
     aspect: f32,
     near: f32,
     far: f32,
+    viewport: Rect,
+    order: i32,
 
     anchor: Option<AnchorId>,
 }
@@ -21,6 +23,8 @@ impl Camera
             aspect: aspect,
             near: near,
             far: far,
+            viewport: Rect::default(),
+            order: 0,
 
             anchor: None,
         }
@@ -73,6 +77,35 @@ impl Camera
         debug_assert!(far > self.near, "Far plane distance must be greater than near plane distance, near: {}, far: {}", self.near, far);
         self.far = far;
     }
+
+    /// Gets the region of the window the camera renders to.
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Sets the region of the window the camera renders to.
+    ///
+    /// The viewport is given as a fraction of the window's size, so the default viewport of
+    /// `Rect::new(0.0, 0.0, 1.0, 1.0)` covers the whole window. For split-screen rendering give
+    /// each camera a different part of the window, e.g. `Rect::new(0.0, 0.0, 0.5, 1.0)` for the
+    /// left half.
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+    }
+
+    /// Gets the order the camera renders in relative to other cameras.
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+
+    /// Sets the order the camera renders in relative to other cameras.
+    ///
+    /// Cameras with a higher order render after (and therefore on top of) cameras with a lower
+    /// order, which makes it possible to composite overlays such as a picture-in-picture view.
+    /// Cameras with the same order render in the order they were registered.
+    pub fn set_order(&mut self, order: i32) {
+        self.order = order;
+    }
 }
 
 impl Default for Camera {
@@ -83,13 +116,44 @@ impl Default for Camera {
             aspect: 1.0,
             near: 0.001,
             far: 1_000.0,
+            viewport: Rect::default(),
+            order: 0,
 
             anchor: None,
         }
     }
 }
 
+/// A rectangular region of the window, given as fractions of the window's size.
+///
+/// The origin is the lower-left corner of the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+}
+
+impl Default for Rect {
+    /// Creates a rect covering the whole window.
+    fn default() -> Rect {
+        Rect::new(0.0, 0.0, 1.0, 1.0)
+    }
+}
+
 /// Identifies an achor that has been registered with the renderer.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct CameraId(usize);
 derive_Counter!(CameraId);
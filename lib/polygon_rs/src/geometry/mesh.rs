@@ -13,6 +13,7 @@ pub struct Mesh {
 
     position: VertexAttribute,
     normal:   Option<VertexAttribute>,
+    tangent:  Option<VertexAttribute>,
     texcoord: Vec<VertexAttribute>,
 }
 
@@ -33,6 +34,10 @@ impl Mesh {
         self.normal
     }
 
+    pub fn tangent(&self) -> Option<VertexAttribute> {
+        self.tangent
+    }
+
     pub fn texcoord(&self) -> &[VertexAttribute] {
         &*self.texcoord
     }
@@ -44,8 +49,18 @@ pub struct Vertex {
     pub position: Point,
     pub normal: Option<Vector3>,
 
-    /// Support an arbitrary number of texture units. The actual maximum is dependent on hardware
-    /// and so is not limited by polygon directly. If the number of
+    /// The direction of increasing u texture coordinate, used for normal mapping.
+    ///
+    /// Tangents are expected to be orthogonal to the normal. The bitangent is reconstructed as
+    /// the cross product of the normal and tangent, so meshes with mirrored texture coordinates
+    /// are not currently supported.
+    pub tangent: Option<Vector3>,
+
+    /// The texture coordinates for each texcoord channel, e.g. a second channel of UVs for a
+    /// lightmap.
+    ///
+    /// The number of channels isn't limited by the mesh, but renderers only make a limited number
+    /// of them available to shaders. All vertices in a mesh must have the same number of channels.
     pub texcoord: Vec<Vector2>,
 }
 
@@ -54,6 +69,7 @@ impl Vertex {
         Vertex {
             position: position,
             normal: None,
+            tangent: None,
             texcoord: Vec::new(),
         }
     }
@@ -92,6 +108,7 @@ pub enum BuildMeshError {
 pub enum VertexAttributeType {
     Position,
     Normal,
+    Tangent,
     Texcoord,
 }
 
@@ -111,7 +128,8 @@ pub enum VertexAttributeType {
 pub struct MeshBuilder {
     position_data: Vec<Point>,
     normal_data: Vec<Vector3>,
-    texcoord_data: Vec<Vector2>,
+    tangent_data: Vec<Vector3>,
+    texcoord_data: Vec<Vec<Vector2>>,
 
     indices:  Vec<u32>,
 }
@@ -121,6 +139,7 @@ impl MeshBuilder {
         MeshBuilder {
             position_data: Vec::new(),
             normal_data:   Vec::new(),
+            tangent_data:  Vec::new(),
             texcoord_data: Vec::new(),
             indices:       Vec::new(),
         }
@@ -133,11 +152,17 @@ impl MeshBuilder {
             self.normal_data.push(normal);
         }
 
-        assert!(vertex.texcoord.len() <= 1, "More than one texcoord per vertex is currently not supported");
+        if let Some(tangent) = vertex.tangent {
+            self.tangent_data.push(tangent);
+        }
 
         // Add each texcoord to its corresponding list.
-        if vertex.texcoord.len() > 0 {
-            self.texcoord_data.push(vertex.texcoord[0])
+        for (channel, &texcoord) in vertex.texcoord.iter().enumerate() {
+            if channel == self.texcoord_data.len() {
+                self.texcoord_data.push(Vec::new());
+            }
+
+            self.texcoord_data[channel].push(texcoord);
         }
     }
 
@@ -158,9 +183,27 @@ impl MeshBuilder {
         self
     }
 
-    pub fn set_texcoord_data(mut self, texcoord_data: &[Vector2]) -> MeshBuilder {
-        self.texcoord_data.clear();
-        self.texcoord_data.extend(texcoord_data);
+    pub fn set_tangent_data(mut self, tangent_data: &[Vector3]) -> MeshBuilder {
+        self.tangent_data.clear();
+        self.tangent_data.extend(tangent_data);
+        self
+    }
+
+    /// Sets the data for the first texcoord channel.
+    pub fn set_texcoord_data(self, texcoord_data: &[Vector2]) -> MeshBuilder {
+        self.set_texcoord_channel(0, texcoord_data)
+    }
+
+    /// Sets the data for the specified texcoord channel.
+    ///
+    /// All channels up to `channel` must also be given data before the mesh is built.
+    pub fn set_texcoord_channel(mut self, channel: usize, texcoord_data: &[Vector2]) -> MeshBuilder {
+        while self.texcoord_data.len() <= channel {
+            self.texcoord_data.push(Vec::new());
+        }
+
+        self.texcoord_data[channel].clear();
+        self.texcoord_data[channel].extend(texcoord_data);
         self
     }
 
@@ -170,7 +213,7 @@ impl MeshBuilder {
         self
     }
 
-    pub fn build(self) -> Result<Mesh, BuildMeshError> {
+    pub fn build(mut self) -> Result<Mesh, BuildMeshError> {
         // The vertex count is defined by the position data, since position is the only required
         // vertex attribute.
         let vertex_count = self.position_data.len();
@@ -184,14 +227,31 @@ impl MeshBuilder {
             });
         }
 
-        if self.texcoord_data.len() != 0 && self.texcoord_data.len() != vertex_count {
+        if self.tangent_data.len() != 0 && self.tangent_data.len() != vertex_count {
             return Err(BuildMeshError::IncorrectAttributeCount {
-                attribute: VertexAttributeType::Texcoord,
+                attribute: VertexAttributeType::Tangent,
                 expected: vertex_count,
-                actual: self.texcoord_data.len(),
+                actual: self.tangent_data.len(),
             });
         }
 
+        // Empty trailing texcoord channels are the same as not having those channels, but an empty
+        // channel before a non-empty one is an error since it would change which shader inputs
+        // the following channels are bound to.
+        while self.texcoord_data.last().map_or(false, |data| data.is_empty()) {
+            self.texcoord_data.pop();
+        }
+
+        for texcoord_data in &self.texcoord_data {
+            if texcoord_data.len() != vertex_count {
+                return Err(BuildMeshError::IncorrectAttributeCount {
+                    attribute: VertexAttributeType::Texcoord,
+                    expected: vertex_count,
+                    actual: texcoord_data.len(),
+                });
+            }
+        }
+
         // Make sure all indices at least point to a valid vertex.
         for index in self.indices.iter().cloned() {
             if index >= vertex_count as MeshIndex {
@@ -207,7 +267,8 @@ impl MeshBuilder {
         let float_count =
             self.position_data.len() * 4
           + self.normal_data.len() * 3
-          + self.texcoord_data.len() * 2;
+          + self.tangent_data.len() * 3
+          + self.texcoord_data.len() * vertex_count * 2;
 
         // Create the mesh.
         let mut vertex_data = Vec::<f32>::with_capacity(float_count);
@@ -234,15 +295,29 @@ impl MeshBuilder {
             None
         };
 
+        // Setup tangent data.
+        let tangent_attrib = if self.tangent_data.len() > 0 {
+            let attrib = VertexAttribute {
+                elements: 3,
+                offset: vertex_data.len(),
+                stride: 0,
+            };
+            vertex_data.extend(Vector3::as_ref(&*self.tangent_data));
+
+            Some(attrib)
+        } else {
+            None
+        };
+
         // Setup texcoord data.
-        let mut texcoord_attribs = Vec::new();
-        if self.texcoord_data.len() > 0 {
+        let mut texcoord_attribs = Vec::with_capacity(self.texcoord_data.len());
+        for texcoord_data in &self.texcoord_data {
             texcoord_attribs.push(VertexAttribute {
                 elements: 2,
                 offset: vertex_data.len(),
                 stride: 0,
             });
-            vertex_data.extend(Vector2::as_ref(&*self.texcoord_data));
+            vertex_data.extend(Vector2::as_ref(&**texcoord_data));
         }
 
         // By our powers combined! We are! A mesh.
@@ -252,6 +327,7 @@ impl MeshBuilder {
 
             position: position_attrib,
             normal: normal_attrib,
+            tangent: tangent_attrib,
             texcoord: texcoord_attribs,
         })
     }
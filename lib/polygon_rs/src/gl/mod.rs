@@ -1,6 +1,6 @@
 pub extern crate gl_util;
 
-use {BuildMaterialError, Counter, GpuMesh, Renderer};
+use {BuildMaterialError, Counter, GpuMesh, RegisterMeshError, Renderer};
 use anchor::*;
 use bootstrap::window::Window;
 use camera::*;
@@ -10,22 +10,57 @@ use material::*;
 use mesh_instance::*;
 use math::*;
 use self::gl_util::*;
-use self::gl_util::context::{Context, Error as ContextError};
+use self::gl_util::context::{Context, Error as ContextError, Rect as GlRect};
 use self::gl_util::shader::*;
 use self::gl_util::shader::Shader as GlShader;
 use self::gl_util::texture::{
     Texture2d as GlTexture2d,
+    TextureData as GlTextureData,
     TextureFormat,
     TextureInternalFormat,
 };
 use shader::Shader;
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::result::Result;
 use std::str;
 use stopwatch::Stopwatch;
 use texture::*;
 
 static DEFAULT_SHADER_BYTES: &'static [u8] = include_bytes!("../../resources/materials/diffuse_lit.material");
 
+/// The maximum number of bytes of queued texture data uploaded to the GPU each frame.
+///
+/// See `Renderer::queue_texture()` for more information.
+const TEXTURE_UPLOAD_BUDGET: usize = 1024 * 1024;
+
+/// The number of texcoord channels made available to shaders as `@vertex.uv0` through
+/// `@vertex.uv3`.
+///
+/// Any additional channels in a mesh are ignored.
+const MAX_TEXCOORD_CHANNELS: usize = 4;
+
+/// The number of lights that can be sent to the shader in a single pass.
+///
+/// This must match the size of the light uniform arrays declared in the generated shaders.
+const MAX_LIGHTS_PER_PASS: usize = 8;
+
+/// The built-in uniforms that hold per-light data.
+///
+/// A program that uses none of these doesn't depend on the lights in the scene.
+const LIGHT_UNIFORMS: &'static [&'static str] = &[
+    "light_type",
+    "light_position",
+    "light_position_view",
+    "light_strength",
+    "light_color",
+    "light_radius",
+    "light_direction",
+    "light_direction_view",
+];
+
 #[derive(Debug)]
 pub struct GlRender {
     context: Context,
@@ -33,14 +68,17 @@ pub struct GlRender {
     shared_materials: HashMap<MaterialId, Material>,
     meshes: HashMap<GpuMesh, MeshData>,
     textures: HashMap<GpuTexture, GlTexture2d>,
+    pending_textures: VecDeque<PendingTexture>,
     mesh_instances: HashMap<MeshInstanceId, MeshInstance>,
     anchors: HashMap<AnchorId, Anchor>,
     cameras: HashMap<CameraId, Camera>,
+    active_camera: Option<CameraId>,
+    window_viewport: GlRect,
     lights: HashMap<LightId, Light>,
     programs: HashMap<Shader, Program>,
-
-    mesh_instances_with_shared_materials: HashMap<MaterialId, Vec<MeshInstanceId>>,
-    mesh_instances_with_owned_material: Vec<MeshInstanceId>,
+    program_sources: HashMap<u64, Shader>,
+    layers_without_depth_test: HashSet<i32>,
+    max_lights_per_pass: usize,
 
     material_counter: MaterialId,
     mesh_counter: GpuMesh,
@@ -52,6 +90,7 @@ pub struct GlRender {
     shader_counter: Shader,
 
     ambient_color: Color,
+    clear_color: Color,
 
     default_material: Material,
 }
@@ -70,20 +109,24 @@ impl GlRender {
             context.clear();
         }
 
+        let window_viewport = context.viewport();
         let mut renderer = GlRender {
             context: context,
 
             shared_materials: HashMap::new(),
             meshes: HashMap::new(),
             textures: HashMap::new(),
+            pending_textures: VecDeque::new(),
             mesh_instances: HashMap::new(),
             anchors: HashMap::new(),
             cameras: HashMap::new(),
+            active_camera: None,
+            window_viewport: window_viewport,
             lights: HashMap::new(),
             programs: HashMap::new(),
-
-            mesh_instances_with_shared_materials: HashMap::new(),
-            mesh_instances_with_owned_material: Vec::new(),
+            program_sources: HashMap::new(),
+            layers_without_depth_test: HashSet::new(),
+            max_lights_per_pass: MAX_LIGHTS_PER_PASS,
 
             material_counter: MaterialId::initial(),
             mesh_counter: GpuMesh::initial(),
@@ -95,6 +138,7 @@ impl GlRender {
             shader_counter: Shader::initial(),
 
             ambient_color: Color::rgb(0.01, 0.01, 0.01),
+            clear_color: Color::rgb(0.0, 0.0, 0.0),
 
             // Use temporary value and replace it later.
             default_material: Material::new(Shader::initial()),
@@ -115,14 +159,72 @@ impl GlRender {
         Ok(renderer)
     }
 
+    /// Gets the maximum number of lights applied in each rendering pass.
+    pub fn max_lights_per_pass(&self) -> usize {
+        self.max_lights_per_pass
+    }
+
+    /// Sets the maximum number of lights applied in each rendering pass.
+    ///
+    /// Each mesh is drawn once for every `max_lights_per_pass` lights in the scene, so lowering
+    /// it trades more draw calls for shaders that loop over fewer lights.
+    ///
+    /// # Panics
+    ///
+    /// - If `max_lights_per_pass` is 0 or greater than 8.
+    pub fn set_max_lights_per_pass(&mut self, max_lights_per_pass: usize) {
+        assert!(
+            max_lights_per_pass > 0 && max_lights_per_pass <= MAX_LIGHTS_PER_PASS,
+            "Lights per pass must be between 1 and {}, got {}",
+            MAX_LIGHTS_PER_PASS,
+            max_lights_per_pass);
+
+        self.max_lights_per_pass = max_lights_per_pass;
+    }
+
+    /// Uploads queued texture data to the GPU.
+    ///
+    /// Textures are uploaded in the order they were queued, a few rows at a time, until
+    /// `TEXTURE_UPLOAD_BUDGET` bytes have been uploaded. Once all of a texture's rows have been
+    /// uploaded it's moved into `textures`, making it available for rendering.
+    fn upload_pending_textures(&mut self) {
+        let _stopwatch = Stopwatch::new("Upload pending textures");
+
+        let mut budget = TEXTURE_UPLOAD_BUDGET;
+        while budget > 0 {
+            let finished = match self.pending_textures.front_mut() {
+                Some(pending) => {
+                    let row_bytes = row_bytes(&pending.texture);
+                    let rows_remaining = pending.texture.height() - pending.rows_uploaded;
+                    let rows = rows_to_upload(row_bytes, rows_remaining, budget);
+
+                    pending.upload_rows(rows);
+                    budget = budget.saturating_sub(rows * row_bytes);
+
+                    pending.rows_uploaded == pending.texture.height()
+                },
+
+                None => break,
+            };
+
+            if finished {
+                let pending = self.pending_textures.pop_front().unwrap();
+                let old = self.textures.insert(pending.id, pending.gl_texture);
+                assert!(old.is_none());
+            }
+        }
+    }
+
     fn render_mesh_instance(
         &self,
         mesh_instance: &MeshInstance,
         material: &Material,
         camera: &Camera,
         camera_anchor: &Anchor,
+        light_batches: &[&[&Light]],
         has_setup_lights: &mut bool,
         has_setup_material: &mut bool,
+        depth_test: bool,
     ) {
         let _s = Stopwatch::new("Rendering mesh instance");
 
@@ -134,7 +236,11 @@ impl GlRender {
         let model_transform = anchor.matrix();
         let normal_transform = anchor.normal_matrix();
 
-        let mesh_data = self.meshes.get(mesh_instance.mesh()).expect("Mesh data does not exist for mesh id");
+        // The mesh may have been unregistered while the mesh instance still referenced it.
+        let mesh_data = match self.meshes.get(mesh_instance.mesh()) {
+            Some(mesh_data) => mesh_data,
+            None => return,
+        };
 
         let default_texture = GlTexture2d::empty(&self.context);
 
@@ -151,176 +257,221 @@ impl GlRender {
             inverse_model_view.transpose()
         };
 
-        // The data for the light uniforms must be declared before `draw_builder` so they
-        // can outlive it, since they are borrowed when the uniforms are set.
-        let mut light_type = [0i32; 8];
-        let mut light_strength = [0.0f32; 8];
-        let mut light_color = [Color::rgb(0.0, 0.0, 0.0); 8];
-        let mut light_position = [Point::origin(); 8];
-        let mut light_position_view = [Point::origin(); 8];
-        let mut light_radius = [0.0f32; 8];
-        let mut light_direction = [Vector3::zero(); 8];
-        let mut light_direction_view = [Vector3::zero(); 8];
-
         let program = self
             .programs
             .get(material.shader())
             .expect("Material is using a shader that does not exist");
 
-        // Set the shader to use.
-        let mut draw_builder = DrawBuilder::new(
-            &self.context,
-            &mesh_data.vertex_array,
-            DrawMode::Triangles,
-        );
-
-        draw_builder
-        .program(program)
-        .cull(Face::Back)
-        .depth_test(Comparison::Less);
-
-        // Set uniform transforms.
-        {
-            let _stopwatch = Stopwatch::new("Transform uniforms");
-
-            draw_builder
-            .uniform(
-                "model_transform",
-                GlMatrix {
-                    data: model_transform.raw_data(),
-                    transpose: true,
-                },
-            )
-            .uniform(
-                "normal_transform",
-                GlMatrix {
-                    data: normal_transform.raw_data(),
-                    transpose: true,
-                },
-            )
-            .uniform(
-                "view_normal_transform",
-                GlMatrix {
-                    data: view_normal_transform.raw_data(),
-                    transpose: true,
-                },
-            )
-            .uniform(
-                "view_transform",
-                GlMatrix {
-                    data: view_transform.raw_data(),
-                    transpose: true,
-                },
-            )
-            .uniform(
-                "model_view_transform",
-                GlMatrix {
-                    data: model_view_transform.raw_data(),
-                    transpose: true,
-                },
-            )
-            .uniform(
-                "projection_transform",
-                GlMatrix {
-                    data: projection_transform.raw_data(),
-                    transpose: true,
-                },
-            )
-            .uniform(
-                "model_view_projection",
-                GlMatrix {
-                    data: model_view_projection.raw_data(),
-                    transpose: true,
-                },
+        // When there are more lights than fit in a single pass the mesh is drawn once for each
+        // batch of lights, and each pass after the first is blended additively on top of the
+        // previous ones. Since the light uniforms change between passes they have to be set for
+        // every draw, and ambient light is only added in the first pass. Unlit materials would
+        // only be blended in again by the extra passes, so they're drawn once.
+        let light_batches = lighting_passes(light_batches, |name| program.has_uniform(name));
+        let multi_pass = light_batches.len() > 1;
+
+        for (pass, lights) in light_batches.iter().enumerate() {
+            // The data for the light uniforms must be declared before `draw_builder` so they
+            // can outlive it, since they are borrowed when the uniforms are set.
+            let mut light_type = [0i32; MAX_LIGHTS_PER_PASS];
+            let mut light_strength = [0.0f32; MAX_LIGHTS_PER_PASS];
+            let mut light_color = [Color::rgb(0.0, 0.0, 0.0); MAX_LIGHTS_PER_PASS];
+            let mut light_position = [Point::origin(); MAX_LIGHTS_PER_PASS];
+            let mut light_position_view = [Point::origin(); MAX_LIGHTS_PER_PASS];
+            let mut light_radius = [0.0f32; MAX_LIGHTS_PER_PASS];
+            let mut light_direction = [Vector3::zero(); MAX_LIGHTS_PER_PASS];
+            let mut light_direction_view = [Vector3::zero(); MAX_LIGHTS_PER_PASS];
+
+            // Set the shader to use.
+            let mut draw_builder = DrawBuilder::new(
+                &self.context,
+                &mesh_data.vertex_array,
+                DrawMode::Triangles,
             );
-        }
 
-        // Apply material attributes.
-        if !*has_setup_material {
-            let _stopwatch = Stopwatch::new("Material uniforms");
+            draw_builder
+            .program(program)
+            .cull(Face::Back)
+            .polygon_mode(polygon_mode(mesh_instance.render_mode()));
+
+            if pass == 0 {
+                if let Some(blend) = material.blend() {
+                    let (source_factor, dest_factor) = blend_factors(blend);
+                    draw_builder.blend(source_factor, dest_factor);
+                }
 
-            *has_setup_material = true;
+                if depth_test {
+                    draw_builder.depth_test(Comparison::Less);
+                }
+            } else {
+                // Later passes cover exactly the same fragments as the first one, so they need
+                // to pass the depth test against the depth the first pass wrote. Alpha blended
+                // materials scale each pass's lighting by alpha, same as the first pass.
+                let source_factor = match material.blend() {
+                    Some(BlendMode::Alpha) => SourceFactor::SourceAlpha,
+                    _ => SourceFactor::One,
+                };
+                draw_builder.blend(source_factor, DestFactor::One);
+                if depth_test {
+                    draw_builder.depth_test(Comparison::LessThanOrEqual);
+                }
+            }
 
-            // Set uniform colors.
-            draw_builder.uniform::<[f32; 4]>("global_ambient", self.ambient_color.into());
+            // Transparent geometry is still depth tested against opaque geometry, but doesn't
+            // write depth so that it doesn't hide transparent geometry drawn after it.
+            if material.is_transparent() {
+                draw_builder.depth_write(false);
+            }
 
-            // Other uniforms.
-            draw_builder.uniform("camera_position", *camera_anchor.position().as_array());
+            // Set uniform transforms.
+            {
+                let _stopwatch = Stopwatch::new("Transform uniforms");
 
-            for (name, property) in material.properties() {
-                match *property {
-                    MaterialProperty::Color(ref color) => {
-                        draw_builder.uniform::<[f32; 4]>(name, color.into());
+                draw_builder
+                .uniform(
+                    "model_transform",
+                    GlMatrix {
+                        data: model_transform.raw_data(),
+                        transpose: true,
+                    },
+                )
+                .uniform(
+                    "normal_transform",
+                    GlMatrix {
+                        data: normal_transform.raw_data(),
+                        transpose: true,
+                    },
+                )
+                .uniform(
+                    "view_normal_transform",
+                    GlMatrix {
+                        data: view_normal_transform.raw_data(),
+                        transpose: true,
+                    },
+                )
+                .uniform(
+                    "view_transform",
+                    GlMatrix {
+                        data: view_transform.raw_data(),
+                        transpose: true,
                     },
-                    MaterialProperty::f32(value) => {
-                        draw_builder.uniform(name, value);
+                )
+                .uniform(
+                    "model_view_transform",
+                    GlMatrix {
+                        data: model_view_transform.raw_data(),
+                        transpose: true,
                     },
-                    MaterialProperty::Vector3(value) => {
-                        draw_builder.uniform::<[f32; 3]>(name, value.into());
+                )
+                .uniform(
+                    "projection_transform",
+                    GlMatrix {
+                        data: projection_transform.raw_data(),
+                        transpose: true,
                     },
-                    MaterialProperty::Texture(ref texture) => {
-                        let gl_texture =
-                        self.textures
-                        .get(texture)
-                        .unwrap_or(&default_texture);
-                        draw_builder.uniform(name, gl_texture);
+                )
+                .uniform(
+                    "model_view_projection",
+                    GlMatrix {
+                        data: model_view_projection.raw_data(),
+                        transpose: true,
                     },
+                );
+            }
+
+            // Apply material attributes.
+            if !*has_setup_material {
+                let _stopwatch = Stopwatch::new("Material uniforms");
+
+                *has_setup_material = true;
+
+                // Set uniform colors.
+                draw_builder.uniform::<[f32; 4]>("global_ambient", self.ambient_color.into());
+
+                // Other uniforms.
+                draw_builder.uniform("camera_position", *camera_anchor.position().as_array());
+
+                for (name, property) in material.properties() {
+                    match *property {
+                        MaterialProperty::Color(ref color) => {
+                            draw_builder.uniform::<[f32; 4]>(name, color.into());
+                        },
+                        MaterialProperty::f32(value) => {
+                            draw_builder.uniform(name, value);
+                        },
+                        MaterialProperty::Vector3(value) => {
+                            draw_builder.uniform::<[f32; 3]>(name, value.into());
+                        },
+                        MaterialProperty::Texture(ref texture) => {
+                            let gl_texture =
+                            self.textures
+                            .get(texture)
+                            .unwrap_or(&default_texture);
+                            draw_builder.uniform(name, gl_texture);
+                        },
+                    }
                 }
             }
-        }
 
-        // Render all lights in a single pass by sending 8 lights at once in arrays.
-        // All light-related uniforms will stay the same for all draws for a given camera,
-        // so we only specify them for the first draw and leave them the same after that.
-        if !*has_setup_lights {
-            *has_setup_lights = true;
-
-            let _stopwatch = Stopwatch::new("Setup lights");
-
-            // TODO: Support having more than 8 lights active at a time. Maybe pick the 8
-            // most relevant lights? Or simply support more lights at once in the shader.
-            for (index, light) in self.lights.values().take(8).enumerate() {
-                // Setup common light data.
-                light_color[index] = light.color;
-                light_strength[index] = light.strength;
-
-                // Setup data specific to the current type of light.
-                match light.data {
-                    LightData::Point { radius } => {
-                        // Get the light's anchor.
-                        let light_anchor = match light.anchor() {
-                            Some(anchor_id) => self.anchors.get(&anchor_id).expect("No such anchor exists"),
-                            None => panic!("Cannot render point light if it's not attached to an anchor"),
-                        };
-
-                        light_type[index] = 1;
-                        light_position[index] = light_anchor.position();
-                        light_position_view[index] = light_anchor.position() * view_transform;
-                        light_radius[index] = radius;
-                    },
+            if multi_pass {
+                let ambient = if pass == 0 { self.ambient_color } else { Color::rgb(0.0, 0.0, 0.0) };
+                draw_builder.uniform::<[f32; 4]>("global_ambient", ambient.into());
+            }
 
-                    LightData::Directional { direction } => {
-                        light_type[index] = 2;
-                        light_direction[index] = direction;
-                        light_direction_view[index] = direction * view_transform;
-                    },
+            // Send a batch of lights at once in arrays. When all lights fit in a single pass the
+            // light uniforms stay the same for all draws for a given camera, so we only specify
+            // them for the first draw and leave them the same after that.
+            if multi_pass || !*has_setup_lights {
+                *has_setup_lights = true;
+
+                let _stopwatch = Stopwatch::new("Setup lights");
+
+                for (index, light) in lights.iter().enumerate() {
+                    // Setup common light data.
+                    light_color[index] = light.color;
+                    light_strength[index] = light.strength;
+
+                    // Setup data specific to the current type of light.
+                    match light.data {
+                        LightData::Point { radius } => {
+                            // Get the light's anchor.
+                            let light_anchor = match light.anchor() {
+                                Some(anchor_id) => self.anchors.get(&anchor_id).expect("No such anchor exists"),
+                                None => panic!("Cannot render point light if it's not attached to an anchor"),
+                            };
+
+                            light_type[index] = 1;
+                            light_position[index] = light_anchor.position();
+                            light_position_view[index] = light_anchor.position() * view_transform;
+                            light_radius[index] = radius;
+                        },
+
+                        LightData::Directional { direction } => {
+                            light_type[index] = 2;
+                            light_direction[index] = direction;
+                            light_direction_view[index] = direction * view_transform;
+                        },
+                    }
                 }
-            }
 
-            draw_builder.uniform("light_type", &light_type[..]);
-            draw_builder.uniform("light_strength", &light_strength[..]);
-            draw_builder.uniform("light_color", Color::as_slice_of_arrays(&light_color));
-            draw_builder.uniform("light_position", Point::as_slice_of_arrays(&light_position));
-            draw_builder.uniform("light_position_view", Point::as_slice_of_arrays(&light_position_view));
-            draw_builder.uniform("light_radius", &light_radius[..]);
-            draw_builder.uniform("light_direction", Vector3::as_slice_of_arrays(&light_direction));
-            draw_builder.uniform("light_direction_view", Vector3::as_slice_of_arrays(&light_direction_view));
-        }
+                draw_builder.uniform("light_type", &light_type[..]);
+                draw_builder.uniform("light_strength", &light_strength[..]);
+                draw_builder.uniform("light_color", Color::as_slice_of_arrays(&light_color));
+                draw_builder.uniform("light_position", Point::as_slice_of_arrays(&light_position));
+                draw_builder.uniform("light_position_view", Point::as_slice_of_arrays(&light_position_view));
+                draw_builder.uniform("light_radius", &light_radius[..]);
+                draw_builder.uniform("light_direction", Vector3::as_slice_of_arrays(&light_direction));
+                draw_builder.uniform("light_direction_view", Vector3::as_slice_of_arrays(&light_direction_view));
+            }
 
-        {
-            let _s = Stopwatch::new("Draw mesh");
+            {
+                let _s = Stopwatch::new("Draw mesh");
 
-            draw_builder.draw();
+                // TODO: Surface draw errors to the caller instead of silently skipping the mesh.
+                if let Err(error) = draw_builder.draw() {
+                    println!("WARNING: Failed to draw mesh: {:?}", error);
+                }
+            }
         }
     }
 }
@@ -338,11 +489,13 @@ impl Drop for GlRender {
         self.shared_materials.clear();
         self.meshes.clear();
         self.textures.clear();
+        self.pending_textures.clear();
         self.mesh_instances.clear();
         self.anchors.clear();
         self.cameras.clear();
         self.lights.clear();
         self.programs.clear();
+        self.program_sources.clear();
     }
 }
 
@@ -350,58 +503,103 @@ impl Renderer for GlRender {
     fn draw(&mut self) {
         let _stopwatch = Stopwatch::new("GLRender::draw()");
 
+        self.upload_pending_textures();
+
         {
             let _stopwatch = Stopwatch::new("Clearing buffer");
+            self.context.set_clear_color(self.clear_color.into());
             self.context.clear();
         }
 
-        // TODO: Support rendering multiple cameras.
         // TODO: Should we warn if there are no cameras?
-        if let Some(camera) = self.cameras.values().next() {
+        for camera_id in camera_order(&self.cameras, self.active_camera) {
+            let camera = &self.cameras[&camera_id];
             let _stopwatch = Stopwatch::new("Rendering camera");
 
+            // Restrict rendering to the camera's viewport, and clear the depth buffer within it so
+            // that cameras rendering on top of other cameras aren't occluded by their geometry.
+            let viewport = window_rect(self.window_viewport, camera.viewport());
+            self.context.set_viewport(viewport);
+            self.context.set_scissor(Some(viewport));
+            self.context.clear_depth();
+
             let camera_anchor = match camera.anchor() {
                 Some(ref anchor_id) => self.anchors.get(anchor_id).expect("No such anchor exists"),
                 None => unimplemented!(),
             };
 
+            let lights: Vec<&Light> = self.lights.values().collect();
+            let light_batches = light_batches(&lights, self.max_lights_per_pass);
+
+            // Track the shared material used by the previous mesh instance so that material and
+            // light uniforms only need to be set once for consecutive instances sharing a material.
+            let mut current_material = None;
             let mut has_setup_lights = false;
+            let mut has_setup_material = false;
+
+            // Transparent instances are sorted by the view space depth of their anchor.
+            let view_transform = camera_anchor.view_matrix();
+            let is_transparent = |mesh_instance: &MeshInstance| match *mesh_instance.material_type() {
+                MaterialType::Shared(material_id) => {
+                    self.shared_materials
+                        .get(&material_id)
+                        .map(Material::is_transparent)
+                        .unwrap_or(false)
+                },
+                MaterialType::Owned(ref material) => material.is_transparent(),
+            };
+            let view_depth = |mesh_instance: &MeshInstance| match mesh_instance.anchor() {
+                Some(anchor_id) => (self.anchors[&anchor_id].position() * view_transform).z,
+                None => 0.0,
+            };
 
-            // Render shared materials first.
-            for (material_id, mesh_instances) in &self.mesh_instances_with_shared_materials {
-                let _s = Stopwatch::new("Rendering shared material");
-
-                let material = self.shared_materials.get(material_id).expect("No such material exists");
-                let mut has_setup_material = false;
-
-                for mesh_instance_id in mesh_instances {
-                    let mesh_instance = self.mesh_instances.get(mesh_instance_id).expect("No such mesh instance");
-                    self.render_mesh_instance(
-                        mesh_instance,
-                        material,
-                        camera,
-                        camera_anchor,
-                        &mut has_setup_lights,
-                        &mut has_setup_material,
-                    );
-                }
-            }
+            for mesh_instance_id in draw_order(&self.mesh_instances, is_transparent, view_depth) {
+                let mesh_instance = &self.mesh_instances[&mesh_instance_id];
+                let depth_test = !self.layers_without_depth_test.contains(&mesh_instance.render_layer());
+
+                match *mesh_instance.material_type() {
+                    MaterialType::Shared(material_id) => {
+                        let material = self.shared_materials.get(&material_id).expect("No such material exists");
+
+                        if current_material != Some(material_id) {
+                            current_material = Some(material_id);
+                            has_setup_lights = false;
+                            has_setup_material = false;
+                        }
+
+                        self.render_mesh_instance(
+                            mesh_instance,
+                            material,
+                            camera,
+                            camera_anchor,
+                            &light_batches,
+                            &mut has_setup_lights,
+                            &mut has_setup_material,
+                            depth_test,
+                        );
+                    },
 
-            // Render meshes with unique materials.
-            for mesh_instance_id in &self.mesh_instances_with_owned_material {
-                let mesh_instance = self.mesh_instances.get(mesh_instance_id).expect("No such mesh instance");
-                let material = mesh_instance.material().expect("Mesh instance was in wrong bucket (was in the owned material bucket, had shared material)");
-                self.render_mesh_instance(
-                    mesh_instance,
-                    material,
-                    camera,
-                    camera_anchor,
-                    &mut false,
-                    &mut false,
-                );
+                    MaterialType::Owned(ref material) => {
+                        current_material = None;
+
+                        self.render_mesh_instance(
+                            mesh_instance,
+                            material,
+                            camera,
+                            camera_anchor,
+                            &light_batches,
+                            &mut false,
+                            &mut false,
+                            depth_test,
+                        );
+                    },
+                }
             }
         }
 
+        self.context.set_viewport(self.window_viewport);
+        self.context.set_scissor(None);
+
         {
             let _stopwatch = Stopwatch::new("Swap buffers");
             self.context.swap_buffers();
@@ -464,19 +662,27 @@ impl Renderer for GlRender {
         "#;
 
         // Generate the GLSL source for the vertex shader.
-        let vert_shader = {
+        let vert_source = {
             static DEFAULT_VERT_MAIN: &'static str = r#"
                 @position = model_view_projection * vertex_position;
 
                 @vertex.position = vertex_position;
                 @vertex.normal = vertex_normal;
                 @vertex.uv0 = vertex_uv0;
+                @vertex.uv1 = vertex_uv1;
+                @vertex.uv2 = vertex_uv2;
+                @vertex.uv3 = vertex_uv3;
 
                 @vertex.world_position = model_transform * vertex_position;
                 @vertex.world_normal = normalize(normal_transform * vertex_normal);
 
                 @vertex.view_position = model_view_transform * vertex_position;
                 @vertex.view_normal = normalize(view_normal_transform * vertex_normal);
+
+                // NOTE: The tangent isn't normalized because it's zero for meshes without
+                // tangents, which the fragment shader uses to fall back to the geometric normal.
+                @vertex.tangent = vertex_tangent;
+                @vertex.view_tangent = mat3(model_view_transform) * vertex_tangent;
             "#;
 
             // Retrieve source string for the vertex shader.
@@ -494,10 +700,15 @@ impl Renderer for GlRender {
                 .replace("@vertex.position", "_vertex_position_")
                 .replace("@vertex.normal", "_vertex_normal_")
                 .replace("@vertex.uv0", "_vertex_uv0_")
+                .replace("@vertex.uv1", "_vertex_uv1_")
+                .replace("@vertex.uv2", "_vertex_uv2_")
+                .replace("@vertex.uv3", "_vertex_uv3_")
                 .replace("@vertex.world_position", "_vertex_world_position_")
                 .replace("@vertex.world_normal", "_vertex_world_normal_")
                 .replace("@vertex.view_position", "_vertex_view_position_")
-                .replace("@vertex.view_normal", "_vertex_view_normal_");
+                .replace("@vertex.view_normal", "_vertex_view_normal_")
+                .replace("@vertex.tangent", "_vertex_tangent_")
+                .replace("@vertex.view_tangent", "_vertex_view_tangent_");
             let replaced_source = format!(r#"
                     #version 330 core
 
@@ -508,14 +719,23 @@ impl Renderer for GlRender {
                     layout(location = 0) in vec4 vertex_position;
                     layout(location = 1) in vec3 vertex_normal;
                     layout(location = 2) in vec2 vertex_uv0;
+                    layout(location = 3) in vec3 vertex_tangent;
+                    layout(location = 4) in vec2 vertex_uv1;
+                    layout(location = 5) in vec2 vertex_uv2;
+                    layout(location = 6) in vec2 vertex_uv3;
 
                     out vec4 _vertex_position_;
                     out vec3 _vertex_normal_;
                     out vec2 _vertex_uv0_;
+                    out vec2 _vertex_uv1_;
+                    out vec2 _vertex_uv2_;
+                    out vec2 _vertex_uv3_;
                     out vec4 _vertex_world_position_;
                     out vec3 _vertex_world_normal_;
                     out vec4 _vertex_view_position_;
                     out vec3 _vertex_view_normal_;
+                    out vec3 _vertex_tangent_;
+                    out vec3 _vertex_view_tangent_;
 
                     void main(void) {{
                         {}
@@ -523,13 +743,37 @@ impl Renderer for GlRender {
                 "#,
                 BUILT_IN_UNIFORMS,
                 uniform_declarations,
-                replaced_source);
-
-            GlShader::new(&self.context, replaced_source, ShaderType::Vertex).map_err(|err| BuildMaterialError)?
+                replaced_source)
         };
 
+        // The alpha test is run after the material's own fragment program so that it applies to
+        // the final output color.
+        let alpha_test = alpha_test_source(&source);
+
+        // Helper functions available to all fragment programs.
+        static BUILT_IN_FRAGMENT_FUNCTIONS: &'static str = r#"
+            // Perturbs `normal` by the tangent-space normal sampled from `normal_map`.
+            //
+            // Falls back to the geometric normal if the mesh doesn't have tangents or if no normal
+            // map is set. An unset texture samples as black, which is never a valid normal.
+            vec3 apply_normal_map(sampler2D normal_map, vec2 uv, vec3 normal, vec3 tangent) {
+                vec3 n = normalize(normal);
+                vec3 sampled = texture(normal_map, uv).rgb;
+                if (dot(tangent, tangent) < 0.000001 || sampled.b == 0.0) {
+                    return n;
+                }
+
+                // Re-orthogonalize the interpolated tangent against the normal.
+                vec3 t = normalize(tangent - n * dot(n, tangent));
+                vec3 b = cross(n, t);
+                mat3 tbn = mat3(t, b, n);
+
+                return normalize(tbn * (sampled * 2.0 - 1.0));
+            }
+        "#;
+
         // Generate the GLSL source for the fragment shader.
-        let frag_shader = {
+        let frag_source = {
             // Retrieve source string for the fragment shader.
             let raw_source =
                 source
@@ -537,7 +781,7 @@ impl Renderer for GlRender {
                 .iter()
                 .find(|program_source| program_source.is_fragment())
                 .map(|program_source| program_source.source())
-                .ok_or(BuildMaterialError)?;
+                .ok_or(BuildMaterialError::MissingFragmentProgram)?;
 
             // Perform text replacements for the various keywords.
             let replaced_source = raw_source
@@ -545,10 +789,15 @@ impl Renderer for GlRender {
                 .replace("@vertex.position", "_vertex_position_")
                 .replace("@vertex.normal", "_vertex_normal_")
                 .replace("@vertex.uv0", "_vertex_uv0_")
+                .replace("@vertex.uv1", "_vertex_uv1_")
+                .replace("@vertex.uv2", "_vertex_uv2_")
+                .replace("@vertex.uv3", "_vertex_uv3_")
                 .replace("@vertex.world_position", "_vertex_world_position_")
                 .replace("@vertex.world_normal", "_vertex_world_normal_")
                 .replace("@vertex.view_position", "_vertex_view_position_")
-                .replace("@vertex.view_normal", "_vertex_view_normal_");
+                .replace("@vertex.view_normal", "_vertex_view_normal_")
+                .replace("@vertex.tangent", "_vertex_tangent_")
+                .replace("@vertex.view_tangent", "_vertex_view_tangent_");
             let replaced_source = format!(r#"
                     #version 330 core
 
@@ -559,33 +808,67 @@ impl Renderer for GlRender {
                     in vec4 _vertex_position_;
                     in vec3 _vertex_normal_;
                     in vec2 _vertex_uv0_;
+                    in vec2 _vertex_uv1_;
+                    in vec2 _vertex_uv2_;
+                    in vec2 _vertex_uv3_;
                     in vec4 _vertex_world_position_;
                     in vec3 _vertex_world_normal_;
                     in vec4 _vertex_view_position_;
                     in vec3 _vertex_view_normal_;
+                    in vec3 _vertex_tangent_;
+                    in vec3 _vertex_view_tangent_;
 
                     out vec4 _fragment_color_;
 
+                    {}
+
                     void main(void) {{
                         {}
+
+                        {}
                     }}
                 "#,
                 BUILT_IN_UNIFORMS,
                 uniform_declarations,
-                replaced_source);
+                BUILT_IN_FRAGMENT_FUNCTIONS,
+                replaced_source,
+                alpha_test)
+        };
 
-            GlShader::new(&self.context, replaced_source, ShaderType::Fragment).map_err(|err| BuildMaterialError)?
+        // Materials built from the same source generate identical GLSL, so reuse the program
+        // compiled for an earlier material instead of compiling a duplicate.
+        let source_hash = {
+            let mut hasher = DefaultHasher::new();
+            vert_source.hash(&mut hasher);
+            frag_source.hash(&mut hasher);
+            hasher.finish()
         };
 
-        let program = Program::new(&self.context, &[vert_shader, frag_shader]).map_err(|err| BuildMaterialError)?;
+        let program_id = match self.program_sources.get(&source_hash) {
+            Some(&program_id) => program_id,
+            None => {
+                let _s = Stopwatch::new("Compiling material program");
 
-        let program_id = self.shader_counter.next();
-        self.programs.insert(program_id, program);
+                let vert_shader = GlShader::new(&self.context, vert_source, ShaderType::Vertex)
+                    .map_err(|error| BuildMaterialError::VertexCompile(shader_error_log(error)))?;
+                let frag_shader = GlShader::new(&self.context, frag_source, ShaderType::Fragment)
+                    .map_err(|error| BuildMaterialError::FragmentCompile(shader_error_log(error)))?;
+                let program = Program::new(&self.context, &[vert_shader, frag_shader])
+                    .map_err(|error| BuildMaterialError::Link(program_error_log(error)))?;
+
+                let program_id = self.shader_counter.next();
+                self.programs.insert(program_id, program);
+                self.program_sources.insert(source_hash, program_id);
+
+                program_id
+            },
+        };
 
         // BUILD MATERIAL OBJECT
         // =====================
 
         let mut material = Material::new(program_id);
+        material.set_blend(source.blend);
 
         // Add the properties from the material declaration.
         for property in source.properties {
@@ -606,9 +889,6 @@ impl Renderer for GlRender {
         let old = self.shared_materials.insert(material_id, material);
         assert!(old.is_none());
 
-        // Ensure there's a bucket for the shared material's mesh instances.
-        self.mesh_instances_with_shared_materials.entry(material_id).or_insert(Vec::new());
-
         material_id
     }
 
@@ -616,26 +896,30 @@ impl Renderer for GlRender {
         self.shared_materials.get(&material_id)
     }
 
-    fn register_mesh(&mut self, mesh: &Mesh) -> GpuMesh {
+    fn register_mesh(&mut self, mesh: &Mesh) -> Result<GpuMesh, RegisterMeshError> {
         // Configure vertex attributes.
         let position = mesh.position();
 
-        let mesh_id = self.mesh_counter.next();
-
         let mut vertex_array = VertexArray::with_index_buffer(
             &self.context,
             mesh.vertex_data(),
             mesh.indices(),
-        );
+        ).map_err(|error| RegisterMeshError::Allocation(format!("{:?}", error)))?;
+
+        let mesh_id = self.mesh_counter.next();
         vertex_array.set_attrib(AttributeLocation::from_index(0), position.into());
 
         if let Some(normal) = mesh.normal() {
             vertex_array.set_attrib(AttributeLocation::from_index(1), normal.into());
         }
 
-        // TODO: Support multiple texcoords.
-        if let Some(texcoord) = mesh.texcoord().first().cloned() {
-            vertex_array.set_attrib(AttributeLocation::from_index(2), texcoord.into());
+        // Channels the mesh doesn't have are left disabled, so shaders read them as zero.
+        for (channel, &texcoord) in mesh.texcoord().iter().take(MAX_TEXCOORD_CHANNELS).enumerate() {
+            vertex_array.set_attrib(AttributeLocation::from_index(texcoord_location(channel)), texcoord.into());
+        }
+
+        if let Some(tangent) = mesh.tangent() {
+            vertex_array.set_attrib(AttributeLocation::from_index(3), tangent.into());
         }
 
         self.meshes.insert(
@@ -648,16 +932,11 @@ impl Renderer for GlRender {
                 element_count: mesh.indices().len(),
             });
 
-        mesh_id
+        Ok(mesh_id)
     }
 
     fn register_texture(&mut self, texture: &Texture2d) -> GpuTexture {
-        let (format, internal_format) = match texture.format() {
-            DataFormat::Rgb => (TextureFormat::Rgb, TextureInternalFormat::Rgb),
-            DataFormat::Rgba => (TextureFormat::Rgba, TextureInternalFormat::Rgba),
-            DataFormat::Bgr => (TextureFormat::Bgr, TextureInternalFormat::Rgb),
-            DataFormat::Bgra => (TextureFormat::Bgra, TextureInternalFormat::Rgba),
-        };
+        let (format, internal_format) = gl_formats(texture.format());
 
         // Create the Texture2d from the texture data.
         let texture_result = match texture.data() {
@@ -709,15 +988,97 @@ impl Renderer for GlRender {
         texture_id
     }
 
-    fn register_mesh_instance(&mut self, mesh_instance: MeshInstance) -> MeshInstanceId {
-        let mesh_instance_id = self.mesh_instance_counter.next();
+    fn queue_texture(&mut self, texture: Texture2d) -> GpuTexture {
+        let (format, internal_format) = gl_formats(texture.format());
+
+        // Allocate the texture's storage now, the data is uploaded over the next few frames.
+        let texture_result = match *texture.data() {
+            TextureData::f32(_) => {
+                GlTexture2d::uninitialized::<f32>(
+                    &self.context,
+                    format,
+                    internal_format,
+                    texture.width(),
+                    texture.height())
+            },
+            TextureData::u8(_) => {
+                GlTexture2d::uninitialized::<u8>(
+                    &self.context,
+                    format,
+                    internal_format,
+                    texture.width(),
+                    texture.height())
+            },
+            TextureData::u8x3(_) => {
+                GlTexture2d::uninitialized::<(u8, u8, u8)>(
+                    &self.context,
+                    format,
+                    internal_format,
+                    texture.width(),
+                    texture.height())
+            },
+            TextureData::u8x4(_) => {
+                GlTexture2d::uninitialized::<(u8, u8, u8, u8)>(
+                    &self.context,
+                    format,
+                    internal_format,
+                    texture.width(),
+                    texture.height())
+            },
+        };
+        let gl_texture = texture_result.expect("Unable to create texture on GPU");
+
+        let texture_id = self.texture_counter.next();
+        self.pending_textures.push_back(PendingTexture {
+            id: texture_id,
+            texture: texture,
+            gl_texture: gl_texture,
+            rows_uploaded: 0,
+        });
+
+        texture_id
+    }
+
+    fn texture_ready(&self, texture: GpuTexture) -> bool {
+        self.textures.contains_key(&texture)
+    }
+
+    fn unregister_mesh(&mut self, mesh: GpuMesh) {
+        if cfg!(debug_assertions) {
+            let in_use = self.mesh_instances.values().any(|mesh_instance| *mesh_instance.mesh() == mesh);
+            if in_use {
+                println!("WARNING: Unregistering {:?} while mesh instances still reference it", mesh);
+            }
+        }
+
+        // Dropping the mesh data deletes its vertex array and buffers.
+        self.meshes.remove(&mesh);
+    }
+
+    fn unregister_texture(&mut self, texture: GpuTexture) {
+        if cfg!(debug_assertions) {
+            let in_use = self.mesh_instances.values().any(|mesh_instance| {
+                let material = match *mesh_instance.material_type() {
+                    MaterialType::Shared(material_id) => self.shared_materials.get(&material_id),
+                    MaterialType::Owned(ref material) => Some(material),
+                };
 
-        // Add the mesh instance to the right bucket based on its material type.
-        match mesh_instance.material_type() {
-            &MaterialType::Shared(id) => self.mesh_instances_with_shared_materials.get_mut(&id).unwrap().push(mesh_instance_id),
-            &MaterialType::Owned(_) => self.mesh_instances_with_owned_material.push(mesh_instance_id),
+                material.map(|material| uses_texture(material, texture)).unwrap_or(false)
+            });
+            if in_use {
+                println!("WARNING: Unregistering {:?} while mesh instances still reference it", texture);
+            }
         }
 
+        // Dropping the texture deletes the texture object, including for partially uploaded
+        // textures.
+        self.textures.remove(&texture);
+        self.pending_textures.retain(|pending| pending.id != texture);
+    }
+
+    fn register_mesh_instance(&mut self, mesh_instance: MeshInstance) -> MeshInstanceId {
+        let mesh_instance_id = self.mesh_instance_counter.next();
+
         let old = self.mesh_instances.insert(mesh_instance_id, mesh_instance);
         assert!(old.is_none());
 
@@ -766,6 +1127,18 @@ impl Renderer for GlRender {
         self.cameras.get_mut(&camera_id)
     }
 
+    fn set_active_camera(&mut self, camera_id: CameraId) {
+        self.active_camera = Some(camera_id);
+    }
+
+    fn set_layer_depth_test(&mut self, layer: i32, enabled: bool) {
+        if enabled {
+            self.layers_without_depth_test.remove(&layer);
+        } else {
+            self.layers_without_depth_test.insert(layer);
+        }
+    }
+
     fn register_light(&mut self, light: Light) -> LightId {
         let light_id = self.light_counter.next();
 
@@ -786,6 +1159,14 @@ impl Renderer for GlRender {
     fn set_ambient_light(&mut self, color: Color) {
         self.ambient_color = color;
     }
+
+    fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = color;
+    }
+
+    fn clear_color(&self) -> Color {
+        self.clear_color
+    }
 }
 
 unsafe impl Send for GlRender {}
@@ -801,6 +1182,80 @@ impl From<ContextError> for Error {
     }
 }
 
+/// A texture that has been queued with `queue_texture()` but hasn't finished uploading.
+#[derive(Debug)]
+struct PendingTexture {
+    id: GpuTexture,
+    texture: Texture2d,
+    gl_texture: GlTexture2d,
+    rows_uploaded: usize,
+}
+
+impl PendingTexture {
+    /// Uploads the next `rows` rows of the texture.
+    fn upload_rows(&mut self, rows: usize) {
+        let (format, _) = gl_formats(self.texture.format());
+        let first_row = self.rows_uploaded;
+
+        match *self.texture.data() {
+            TextureData::f32(ref data) => write_rows(&mut self.gl_texture, format, data, first_row, rows),
+            TextureData::u8(ref data) => write_rows(&mut self.gl_texture, format, data, first_row, rows),
+            TextureData::u8x3(ref data) => write_rows(&mut self.gl_texture, format, data, first_row, rows),
+            TextureData::u8x4(ref data) => write_rows(&mut self.gl_texture, format, data, first_row, rows),
+        }
+
+        self.rows_uploaded += rows;
+    }
+}
+
+fn write_rows<T: GlTextureData>(
+    gl_texture: &mut GlTexture2d,
+    format: TextureFormat,
+    data: &[T],
+    first_row: usize,
+    rows: usize,
+) {
+    if rows == 0 {
+        return;
+    }
+
+    let row_len = gl_texture.width() * format.elements() / T::ELEMENTS;
+    gl_texture.write_rows(format, first_row, &data[first_row * row_len .. (first_row + rows) * row_len]);
+}
+
+/// Gets the GL data format and internal format for a texture's data format.
+fn gl_formats(format: DataFormat) -> (TextureFormat, TextureInternalFormat) {
+    match format {
+        DataFormat::Rgb => (TextureFormat::Rgb, TextureInternalFormat::Rgb),
+        DataFormat::Rgba => (TextureFormat::Rgba, TextureInternalFormat::Rgba),
+        DataFormat::Bgr => (TextureFormat::Bgr, TextureInternalFormat::Rgb),
+        DataFormat::Bgra => (TextureFormat::Bgra, TextureInternalFormat::Rgba),
+    }
+}
+
+/// Gets the size in bytes of a single row of a texture's data.
+fn row_bytes(texture: &Texture2d) -> usize {
+    let (format, _) = gl_formats(texture.format());
+    let element_size = match *texture.data() {
+        TextureData::f32(_) => 4,
+        _ => 1,
+    };
+
+    texture.width() * format.elements() * element_size
+}
+
+/// Determines how many rows of a texture to upload given the remaining upload budget.
+///
+/// At least one row is always uploaded so that textures with rows larger than the budget still
+/// make progress.
+fn rows_to_upload(row_bytes: usize, rows_remaining: usize, budget: usize) -> usize {
+    if row_bytes == 0 {
+        return rows_remaining;
+    }
+
+    cmp::min(rows_remaining, cmp::max(1, budget / row_bytes))
+}
+
 #[derive(Debug)]
 struct MeshData {
     vertex_array: VertexArray,
@@ -819,3 +1274,469 @@ impl Into<AttribLayout> for VertexAttribute {
         }
     }
 }
+
+/// Gets the attribute location for the specified texcoord channel.
+///
+/// The first channel predates support for multiple channels and uses location 2, so the other
+/// channels are placed after the tangent attribute.
+fn texcoord_location(channel: usize) -> u32 {
+    match channel {
+        0 => 2,
+        _ => 3 + channel as u32,
+    }
+}
+
+/// Splits `lights` into the batches of lights applied in each rendering pass.
+///
+/// There is always at least one batch so that meshes are still drawn when there are no lights.
+fn light_batches<'a, T>(lights: &'a [T], max_per_pass: usize) -> Vec<&'a [T]> {
+    if lights.is_empty() {
+        vec![lights]
+    } else {
+        lights.chunks(max_per_pass).collect()
+    }
+}
+
+/// Selects the light batches that a program needs to be drawn with.
+///
+/// Programs that don't use any of the light uniforms look the same in every pass, so they only
+/// get the first pass. `has_uniform` reports whether the program uses a given uniform.
+fn lighting_passes<'a, T, F>(light_batches: &'a [T], has_uniform: F) -> &'a [T]
+    where
+    F: Fn(&str) -> bool,
+{
+    if LIGHT_UNIFORMS.iter().any(|name| has_uniform(name)) {
+        light_batches
+    } else {
+        &light_batches[..cmp::min(light_batches.len(), 1)]
+    }
+}
+
+/// Chooses the camera to render with.
+///
+/// Uses `active` if it's a registered camera, otherwise falls back to the camera with the lowest
+/// id so that the choice doesn't depend on `HashMap` iteration order.
+fn select_camera(cameras: &HashMap<CameraId, Camera>, active: Option<CameraId>) -> Option<CameraId> {
+    match active {
+        Some(camera_id) if cameras.contains_key(&camera_id) => Some(camera_id),
+        _ => cameras.keys().min().cloned(),
+    }
+}
+
+/// Determines the order in which cameras are rendered.
+///
+/// Once an active camera has been set, the camera chosen by `select_camera()` renders first and
+/// the remaining cameras render on top of it. Cameras are otherwise sorted by their order, with
+/// ties broken by id so that the order doesn't depend on `HashMap` iteration order.
+fn camera_order(cameras: &HashMap<CameraId, Camera>, active: Option<CameraId>) -> Vec<CameraId> {
+    let mut order: Vec<_> = cameras.keys().cloned().collect();
+    order.sort_by_key(|id| (cameras[id].order(), *id));
+
+    if active.is_some() {
+        if let Some(first) = select_camera(cameras, active) {
+            order.retain(|&id| id != first);
+            order.insert(0, first);
+        }
+    }
+
+    order
+}
+
+/// Converts a viewport given as a fraction of the window into window coordinates.
+fn window_rect(window: GlRect, viewport: Rect) -> GlRect {
+    let x = window.x + (viewport.x * window.width as f32).round() as i32;
+    let y = window.y + (viewport.y * window.height as f32).round() as i32;
+    let right = window.x + ((viewport.x + viewport.width) * window.width as f32).round() as i32;
+    let top = window.y + ((viewport.y + viewport.height) * window.height as f32).round() as i32;
+
+    GlRect::new(x, y, right - x, top - y)
+}
+
+/// Gets the driver's log for a shader error.
+fn shader_error_log(error: ShaderError) -> String {
+    match error {
+        ShaderError::CreateShaderError => "Unable to create shader object".into(),
+        ShaderError::CompileError(log) => log,
+    }
+}
+
+/// Gets the driver's log for a program error.
+fn program_error_log(error: ProgramError) -> String {
+    match error {
+        ProgramError::CreateProgramError => "Unable to create program object".into(),
+        ProgramError::LinkError(log) => log,
+    }
+}
+
+/// Generates the GLSL that alpha tests a material's output color.
+///
+/// Materials that declare an `alpha_cutoff` property are alpha tested, discarding any fragments
+/// whose alpha is below the cutoff. Returns an empty string for all other materials.
+fn alpha_test_source(source: &MaterialSource) -> &'static str {
+    use polygon_material::material_source::PropertyType;
+
+    let has_cutoff = source.properties.iter().any(|property| {
+        property.name == ALPHA_CUTOFF_PROPERTY && property.property_type == PropertyType::f32
+    });
+
+    if has_cutoff {
+        "if (_fragment_color_.a < alpha_cutoff) { discard; }"
+    } else {
+        ""
+    }
+}
+
+/// Checks if any of a material's properties reference `texture`.
+fn uses_texture(material: &Material, texture: GpuTexture) -> bool {
+    material.properties().any(|(_, property)| match *property {
+        MaterialProperty::Texture(property_texture) => property_texture == texture,
+        _ => false,
+    })
+}
+
+/// Gets the polygon mode used to draw a mesh instance with the specified render mode.
+fn polygon_mode(render_mode: RenderMode) -> PolygonMode {
+    match render_mode {
+        RenderMode::Filled => PolygonMode::Fill,
+        RenderMode::Wireframe => PolygonMode::Line,
+        RenderMode::Points => PolygonMode::Point,
+    }
+}
+
+/// Gets the blend factors used to draw a transparent material.
+fn blend_factors(blend: BlendMode) -> (SourceFactor, DestFactor) {
+    match blend {
+        BlendMode::Alpha => (SourceFactor::SourceAlpha, DestFactor::OneMinusSourceAlpha),
+        BlendMode::Premultiplied => (SourceFactor::One, DestFactor::OneMinusSourceAlpha),
+        BlendMode::Additive => (SourceFactor::One, DestFactor::One),
+    }
+}
+
+/// Determines the order in which mesh instances are drawn.
+///
+/// Instances are drawn in two passes: All opaque instances draw first, then all transparent
+/// instances, so that transparent geometry blends over everything opaque regardless of layer.
+/// Within each pass instances are sorted by render layer so that higher layers draw after lower
+/// ones. Opaque instances sharing a material are grouped together to minimize material setup, and
+/// transparent instances are sorted back-to-front by `view_depth` so that they blend correctly with
+/// each other. Remaining ties are broken by id so that the order is stable from frame to frame.
+fn draw_order<T, D>(
+    mesh_instances: &HashMap<MeshInstanceId, MeshInstance>,
+    is_transparent: T,
+    view_depth: D,
+) -> Vec<MeshInstanceId>
+    where
+    T: Fn(&MeshInstance) -> bool,
+    D: Fn(&MeshInstance) -> f32,
+{
+    let mut order: Vec<_> = mesh_instances
+        .iter()
+        .map(|(&id, mesh_instance)| {
+            // Only transparent instances are sorted by depth, so opaque instances all use the same
+            // depth.
+            let transparent = is_transparent(mesh_instance);
+            let depth = if transparent { view_depth(mesh_instance) } else { 0.0 };
+            (transparent, mesh_instance.render_layer(), depth, mesh_instance.shared_material(), id)
+        })
+        .collect();
+
+    // The camera looks down the negative z axis in view space, so instances farther from the
+    // camera have a lower depth and are drawn first.
+    order.sort_by(|a, b| {
+        a.0.cmp(&b.0)
+            .then(a.1.cmp(&b.1))
+            .then(a.2.partial_cmp(&b.2).unwrap_or(cmp::Ordering::Equal))
+            .then(a.3.cmp(&b.3))
+            .then(a.4.cmp(&b.4))
+    });
+
+    order.into_iter().map(|(_, _, _, _, id)| id).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use {Counter, GpuMesh};
+    use camera::{Camera, CameraId, Rect};
+    use material::{Material, MaterialId, MaterialSource, ALPHA_CUTOFF_PROPERTY};
+    use mesh_instance::{MeshInstance, MeshInstanceId};
+    use shader::Shader;
+    use std::collections::HashMap;
+    use texture::GpuTexture;
+    use super::{
+        alpha_test_source,
+        camera_order,
+        draw_order,
+        light_batches,
+        lighting_passes,
+        rows_to_upload,
+        select_camera,
+        texcoord_location,
+        uses_texture,
+        window_rect,
+        GlRect,
+        MAX_TEXCOORD_CHANNELS,
+        TEXTURE_UPLOAD_BUDGET,
+    };
+
+    #[test]
+    fn cameras_render_in_order() {
+        let mut counter = CameraId::initial();
+        let mut cameras = HashMap::new();
+        let ids: Vec<CameraId> = (0..4).map(|_| counter.next()).collect();
+        for (&id, &order) in ids.iter().zip(&[1, 0, -2, 0]) {
+            let mut camera = Camera::default();
+            camera.set_order(order);
+            cameras.insert(id, camera);
+        }
+
+        // Every camera is rendered, lowest order first, with ties broken by id.
+        assert_eq!(vec![ids[2], ids[1], ids[3], ids[0]], camera_order(&cameras, None));
+
+        // The active camera renders first, and the others follow by order.
+        assert_eq!(vec![ids[0], ids[2], ids[1], ids[3]], camera_order(&cameras, Some(ids[0])));
+        assert_eq!(vec![ids[3], ids[2], ids[1], ids[0]], camera_order(&cameras, Some(ids[3])));
+
+        assert!(camera_order(&HashMap::new(), None).is_empty());
+    }
+
+    #[test]
+    fn select_active_camera() {
+        let mut counter = CameraId::initial();
+        let mut cameras = HashMap::new();
+        let ids: Vec<CameraId> = (0..8).map(|_| counter.next()).collect();
+        for &id in &ids {
+            cameras.insert(id, Camera::default());
+        }
+
+        // Without an active camera the lowest id is used.
+        assert_eq!(Some(ids[0]), select_camera(&cameras, None));
+
+        // The active camera is used when set.
+        for &id in &ids {
+            assert_eq!(Some(id), select_camera(&cameras, Some(id)));
+        }
+
+        // An unregistered active camera falls back to the lowest id.
+        let unregistered = counter.next();
+        assert_eq!(Some(ids[0]), select_camera(&cameras, Some(unregistered)));
+
+        assert_eq!(None, select_camera(&HashMap::new(), Some(ids[0])));
+    }
+
+    #[test]
+    fn split_screen_viewports() {
+        let window = GlRect::new(0, 0, 1280, 720);
+
+        assert_eq!(window, window_rect(window, Rect::default()));
+        assert_eq!(GlRect::new(0, 0, 640, 720), window_rect(window, Rect::new(0.0, 0.0, 0.5, 1.0)));
+        assert_eq!(GlRect::new(640, 0, 640, 720), window_rect(window, Rect::new(0.5, 0.0, 0.5, 1.0)));
+
+        // Adjacent viewports share an edge without gaps or overlap, even when the window doesn't
+        // divide evenly.
+        let window = GlRect::new(0, 0, 1001, 3);
+        let left = window_rect(window, Rect::new(0.0, 0.0, 1.0 / 3.0, 1.0));
+        let right = window_rect(window, Rect::new(1.0 / 3.0, 0.0, 2.0 / 3.0, 1.0));
+        assert_eq!(left.x + left.width, right.x);
+        assert_eq!(window.width, left.width + right.width);
+    }
+
+    #[test]
+    fn draw_order_by_layer() {
+        let mut mesh_counter = GpuMesh::initial();
+        let mut material_counter = MaterialId::initial();
+        let mut id_counter = MeshInstanceId::initial();
+        let material = material_counter.next();
+
+        // Register instances in reverse layer order so that id order doesn't match layer order.
+        let mut mesh_instances = HashMap::new();
+        let mut ids = Vec::new();
+        for &layer in &[10, 2, 2, 0, -5] {
+            let mut mesh_instance = MeshInstance::with_shared_material(mesh_counter.next(), material);
+            mesh_instance.set_render_layer(layer);
+
+            let id = id_counter.next();
+            mesh_instances.insert(id, mesh_instance);
+            ids.push(id);
+        }
+
+        let order = draw_order(&mesh_instances, |_| false, |_| 0.0);
+        assert_eq!(vec![ids[4], ids[3], ids[1], ids[2], ids[0]], order);
+
+        // Higher layers always draw after lower ones.
+        let layers: Vec<_> = order.iter().map(|id| mesh_instances[id].render_layer()).collect();
+        assert!(layers.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn transparent_draws_back_to_front() {
+        let mut mesh_counter = GpuMesh::initial();
+        let mut material_counter = MaterialId::initial();
+        let mut id_counter = MeshInstanceId::initial();
+        let opaque = material_counter.next();
+        let transparent = material_counter.next();
+
+        // Each instance is given as (layer, material, view depth).
+        let instances = [
+            (0, transparent, -1.0),
+            (0, opaque, -1.0),
+            (0, transparent, -10.0),
+            (1, opaque, -20.0),
+            (0, opaque, -5.0),
+            (0, transparent, -5.0),
+        ];
+
+        let mut mesh_instances = HashMap::new();
+        let mut depths = HashMap::new();
+        let mut ids = Vec::new();
+        for &(layer, material, depth) in &instances {
+            let mesh = mesh_counter.next();
+            let mut mesh_instance = MeshInstance::with_shared_material(mesh, material);
+            mesh_instance.set_render_layer(layer);
+            depths.insert(mesh, depth);
+
+            let id = id_counter.next();
+            mesh_instances.insert(id, mesh_instance);
+            ids.push(id);
+        }
+
+        let order = draw_order(
+            &mesh_instances,
+            |mesh_instance| mesh_instance.shared_material() == Some(transparent),
+            |mesh_instance| depths[mesh_instance.mesh()],
+        );
+
+        // All opaque instances draw first regardless of depth or layer, then transparent instances
+        // from farthest to nearest.
+        assert_eq!(vec![ids[1], ids[4], ids[3], ids[2], ids[5], ids[0]], order);
+
+        let transparent_start = order
+            .iter()
+            .position(|id| mesh_instances[id].shared_material() == Some(transparent))
+            .unwrap();
+        assert!(order[transparent_start..].iter().all(|id| mesh_instances[id].shared_material() == Some(transparent)));
+    }
+
+    #[test]
+    fn material_texture_references() {
+        let mut texture_counter = GpuTexture::initial();
+        let diffuse = texture_counter.next();
+        let unused = texture_counter.next();
+
+        let mut material = Material::new(Shader::initial());
+        material.set_f32("roughness", 0.5);
+        material.set_texture("surface_diffuse", diffuse);
+
+        assert!(uses_texture(&material, diffuse));
+        assert!(!uses_texture(&material, unused));
+    }
+
+    #[test]
+    fn cutout_material_discards_low_alpha() {
+        let cutout = MaterialSource::from_str(include_str!("../../resources/materials/texture_cutout.material")).unwrap();
+        let alpha_test = alpha_test_source(&cutout);
+        assert!(alpha_test.contains("discard"));
+        assert!(alpha_test.contains("alpha_cutoff"));
+
+        // Materials without a cutoff aren't alpha tested.
+        let lit = MaterialSource::from_str(include_str!("../../resources/materials/diffuse_lit.material")).unwrap();
+        assert_eq!("", alpha_test_source(&lit));
+
+        // Alpha tested materials stay in the opaque pass.
+        let mut material = Material::new(Shader::initial());
+        assert!(!material.is_alpha_tested());
+        material.set_f32(ALPHA_CUTOFF_PROPERTY, 0.5);
+        assert!(material.is_alpha_tested());
+        assert!(!material.is_transparent());
+    }
+
+    #[test]
+    fn unset_textures_never_registered() {
+        // Registered textures never share an id with the default texture used for unset
+        // texture properties, such as the default material's normal map.
+        let mut texture_counter = GpuTexture::initial();
+        let first = texture_counter.next();
+        let second = texture_counter.next();
+        assert!(first != GpuTexture::default());
+        assert!(second != GpuTexture::default());
+
+        let mut material = Material::new(Shader::initial());
+        material.set_texture("normal_map", GpuTexture::default());
+        material.set_texture("surface_diffuse", second);
+
+        assert!(!uses_texture(&material, first));
+        assert!(uses_texture(&material, second));
+    }
+
+    #[test]
+    fn texcoord_locations() {
+        let locations: Vec<u32> = (0..MAX_TEXCOORD_CHANNELS).map(texcoord_location).collect();
+        assert_eq!(vec![2, 4, 5, 6], locations);
+
+        // Texcoords don't overlap position, normal, or tangent.
+        for &reserved in &[0, 1, 3] {
+            assert!(!locations.contains(&reserved));
+        }
+    }
+
+    #[test]
+    fn light_passes() {
+        // 12 lights take two passes, with the remaining 4 in the second.
+        let lights: Vec<usize> = (0..12).collect();
+        let batches = light_batches(&lights, 8);
+        assert_eq!(vec![&lights[..8], &lights[8..]], batches);
+
+        // Every light is rendered exactly once.
+        let rendered: Vec<usize> = batches.iter().flat_map(|batch| batch.iter().cloned()).collect();
+        assert_eq!(lights, rendered);
+
+        assert_eq!(3, light_batches(&lights, 5).len());
+        assert_eq!(1, light_batches(&lights[..8], 8).len());
+
+        // Meshes are still drawn once when there are no lights.
+        let no_lights: &[usize] = &[];
+        assert_eq!(vec![no_lights], light_batches(no_lights, 8));
+    }
+
+    #[test]
+    fn unlit_programs_draw_once() {
+        let lights: Vec<usize> = (0..20).collect();
+        let batches = light_batches(&lights, 8);
+        assert_eq!(3, batches.len());
+
+        // A program without light uniforms only gets the first pass, so it isn't blended in again.
+        let unlit = lighting_passes(&batches, |name| name == "model_view_projection");
+        assert_eq!(&batches[..1], unlit);
+
+        // Using any of the light uniforms gets every pass.
+        assert_eq!(&batches[..], lighting_passes(&batches, |name| name == "light_color"));
+        assert_eq!(&batches[..], lighting_passes(&batches, |name| name == "light_direction_view"));
+    }
+
+    #[test]
+    fn queued_texture_upload_completes() {
+        // A 1024x1024 RGBA texture is 4 MB, so it should take exactly 4 frames to upload.
+        let row_bytes = 1024 * 4;
+        let height = 1024;
+
+        let mut rows_uploaded = 0;
+        let mut frames = 0;
+        while rows_uploaded < height {
+            let rows = rows_to_upload(row_bytes, height - rows_uploaded, TEXTURE_UPLOAD_BUDGET);
+            assert!(rows > 0, "Upload didn't make progress");
+            assert!(rows * row_bytes <= TEXTURE_UPLOAD_BUDGET);
+
+            rows_uploaded += rows;
+            frames += 1;
+        }
+
+        assert_eq!(height, rows_uploaded);
+        assert_eq!(4, frames);
+
+        // Rows larger than the budget are still uploaded one at a time.
+        assert_eq!(1, rows_to_upload(TEXTURE_UPLOAD_BUDGET * 2, 10, TEXTURE_UPLOAD_BUDGET));
+
+        // Empty textures are finished immediately.
+        assert_eq!(0, rows_to_upload(row_bytes, 0, TEXTURE_UPLOAD_BUDGET));
+        assert_eq!(3, rows_to_upload(0, 3, TEXTURE_UPLOAD_BUDGET));
+    }
+}
@@ -28,6 +28,8 @@ use light::*;
 use material::*;
 use math::Color;
 use mesh_instance::*;
+use std::error::Error;
+use std::fmt;
 use texture::*;
 
 /// Identifies mesh data that has been sent to the GPU.
@@ -53,11 +55,41 @@ pub trait Renderer: 'static + Send {
     fn get_material(&self, material_id: MaterialId) -> Option<&Material>;
 
     /// Registers mesh data with the renderer, returning a unique id for the mesh.
-    fn register_mesh(&mut self, mesh: &Mesh) -> GpuMesh;
+    ///
+    /// Fails if the GPU resources for the mesh couldn't be allocated, e.g. because the GPU is out
+    /// of memory.
+    fn register_mesh(&mut self, mesh: &Mesh) -> Result<GpuMesh, RegisterMeshError>;
 
     /// Registers texture data with the renderer, returning a unique id for the texture.
     fn register_texture(&mut self, texture: &Texture2d) -> GpuTexture;
 
+    /// Queues texture data to be uploaded to the GPU, returning a unique id for the texture.
+    ///
+    /// Unlike `register_texture()`, which uploads all of the texture data immediately, queued
+    /// textures are uploaded a few rows at a time over the following frames so that streaming in
+    /// large textures doesn't cause frame hitches. The returned id can be used in materials right
+    /// away, but the texture will render as if no texture were set until it has finished
+    /// uploading. Use `texture_ready()` to check if the upload has finished.
+    fn queue_texture(&mut self, texture: Texture2d) -> GpuTexture;
+
+    /// Checks if a texture has been fully uploaded to the GPU.
+    ///
+    /// Textures registered with `register_texture()` are always ready.
+    fn texture_ready(&self, texture: GpuTexture) -> bool;
+
+    /// Unregisters mesh data, freeing the GPU resources used by the mesh.
+    ///
+    /// Mesh instances that still reference the mesh are skipped when rendering. In debug builds a
+    /// warning is printed if any mesh instances still reference the mesh.
+    fn unregister_mesh(&mut self, mesh: GpuMesh);
+
+    /// Unregisters a texture, freeing the GPU resources used by the texture.
+    ///
+    /// Textures that are still being uploaded are cancelled. Materials that still reference the
+    /// texture render as if no texture were set. In debug builds a warning is printed if any
+    /// mesh instance's material still references the texture.
+    fn unregister_texture(&mut self, texture: GpuTexture);
+
     /// Registers a mesh instance with the renderer, returning a unique id for that mesh instance.
     fn register_mesh_instance(&mut self, mesh_instance: MeshInstance) -> MeshInstanceId;
 
@@ -85,6 +117,19 @@ pub trait Renderer: 'static + Send {
     /// Gets a mutable reference to a registered camera.
     fn get_camera_mut(&mut self, camera_id: CameraId) -> Option<&mut Camera>;
 
+    /// Sets the camera used to render the scene.
+    ///
+    /// The active camera renders first, and any other cameras render on top of it based on their
+    /// order. If the active camera isn't registered, the renderer falls back to the registered
+    /// camera with the lowest id.
+    fn set_active_camera(&mut self, camera_id: CameraId);
+
+    /// Enables or disables depth testing for mesh instances on the specified render layer.
+    ///
+    /// Depth testing is enabled for all layers by default. Disabling it for a layer makes its mesh
+    /// instances draw over everything on lower layers, regardless of depth.
+    fn set_layer_depth_test(&mut self, layer: i32, enabled: bool);
+
     /// Registers a light with the renderer, returning a unique id for the light.
     fn register_light(&mut self, light: Light) -> LightId;
 
@@ -95,6 +140,14 @@ pub trait Renderer: 'static + Send {
     fn get_light_mut(&mut self, light_id: LightId) -> Option<&mut Light>;
 
     fn set_ambient_light(&mut self, color: Color);
+
+    /// Sets the color the screen is cleared to at the start of each frame.
+    ///
+    /// The clear color is black by default.
+    fn set_clear_color(&mut self, color: Color);
+
+    /// Gets the color the screen is cleared to at the start of each frame.
+    fn clear_color(&self) -> Color;
 }
 
 /// A helper struct for selecting and initializing the most suitable renderer for the client's
@@ -136,5 +189,81 @@ trait Counter {
     fn next(&mut self) -> Self;
 }
 
-#[derive(Debug)]
-pub struct BuildMaterialError;
+/// An error that occurred while building a material from its source.
+#[derive(Debug, Clone)]
+pub enum BuildMaterialError {
+    /// Indicates that the material source doesn't have a fragment program.
+    MissingFragmentProgram,
+
+    /// Indicates that the generated vertex shader failed to compile.
+    ///
+    /// The inner string is the compile log reported by the driver.
+    VertexCompile(String),
+
+    /// Indicates that the generated fragment shader failed to compile.
+    ///
+    /// The inner string is the compile log reported by the driver.
+    FragmentCompile(String),
+
+    /// Indicates that the compiled shaders failed to link.
+    ///
+    /// The inner string is the link log reported by the driver.
+    Link(String),
+}
+
+impl fmt::Display for BuildMaterialError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildMaterialError::MissingFragmentProgram => {
+                write!(formatter, "Material source has no fragment program")
+            },
+            BuildMaterialError::VertexCompile(ref log) => {
+                write!(formatter, "Failed to compile vertex shader:\n{}", log)
+            },
+            BuildMaterialError::FragmentCompile(ref log) => {
+                write!(formatter, "Failed to compile fragment shader:\n{}", log)
+            },
+            BuildMaterialError::Link(ref log) => {
+                write!(formatter, "Failed to link shader program:\n{}", log)
+            },
+        }
+    }
+}
+
+impl Error for BuildMaterialError {
+    fn description(&self) -> &str {
+        match *self {
+            BuildMaterialError::MissingFragmentProgram => "material source has no fragment program",
+            BuildMaterialError::VertexCompile(_) => "failed to compile vertex shader",
+            BuildMaterialError::FragmentCompile(_) => "failed to compile fragment shader",
+            BuildMaterialError::Link(_) => "failed to link shader program",
+        }
+    }
+}
+
+/// An error that occurred while uploading mesh data to the GPU.
+#[derive(Debug, Clone)]
+pub enum RegisterMeshError {
+    /// Indicates that the GPU resources for the mesh couldn't be allocated.
+    ///
+    /// The inner string describes the error reported by the driver.
+    Allocation(String),
+}
+
+impl fmt::Display for RegisterMeshError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RegisterMeshError::Allocation(ref message) => {
+                write!(formatter, "Failed to allocate mesh data: {}", message)
+            },
+        }
+    }
+}
+
+impl Error for RegisterMeshError {
+    fn description(&self) -> &str {
+        match *self {
+            RegisterMeshError::Allocation(_) => "failed to allocate mesh data",
+        }
+    }
+}
@@ -66,6 +66,60 @@
 //!   for directional lights).
 //! - `light_direction_view` - The normalized direction in view space of the current light (only
 //!   for directional lights).
+//!
+//! ## Normal Mapping
+//!
+//! Meshes built with tangent data (see `MeshBuilder::set_tangent_data()`) expose the tangent to
+//! materials as `@vertex.tangent` in model space and `@vertex.view_tangent` in view space. Fragment
+//! programs can use the built-in `apply_normal_map()` function to perturb the surface normal with
+//! a tangent-space normal map:
+//!
+//! ```text
+//! vec3 n = apply_normal_map(normal_map, @vertex.uv0, @vertex.view_normal, @vertex.view_tangent);
+//! ```
+//!
+//! If the mesh has no tangents or the normal map texture hasn't been set, `apply_normal_map()`
+//! returns the normalized geometric normal instead. The default material declares a `normal_map`
+//! texture property and uses it this way.
+//!
+//! ## Texture Coordinates
+//!
+//! Meshes can have multiple channels of texture coordinates (see
+//! `MeshBuilder::set_texcoord_channel()`), e.g. a second set of UVs for lightmapping. The first
+//! four channels are exposed to materials as `@vertex.uv0` through `@vertex.uv3`:
+//!
+//! ```text
+//! vec4 lighting = texture(lightmap, @vertex.uv1);
+//! ```
+//!
+//! Channels that the mesh doesn't have read as `vec2(0.0)`.
+//!
+//! ## Alpha Testing
+//!
+//! Materials for cutout surfaces like foliage and fences can discard fragments based on their
+//! alpha value by declaring an `alpha_cutoff` property:
+//!
+//! ```text
+//! property alpha_cutoff: f32;
+//! ```
+//!
+//! Any fragment whose final `@color` has an alpha below `alpha_cutoff` is discarded. Unlike
+//! blending, alpha tested materials still write depth and are rendered along with all other opaque
+//! geometry. The cutoff defaults to `0.0`, which doesn't discard anything, so set it with
+//! `Material::set_f32("alpha_cutoff", value)` to enable the test.
+//!
+//! ## Transparency
+//!
+//! Materials for transparent surfaces like glass declare how they blend with the geometry behind
+//! them using a `blend` item:
+//!
+//! ```text
+//! blend alpha;
+//! ```
+//!
+//! The supported blend modes are `alpha`, `premultiplied`, and `additive`. Transparent materials
+//! are rendered after all opaque geometry on the same render layer, sorted back-to-front by the
+//! position of their anchor, and don't write depth.
 
 use math::*;
 use shader::Shader;
@@ -73,9 +127,14 @@ use std::collections::HashMap;
 use std::collections::hash_map::Iter as HashMapIter;
 use texture::GpuTexture;
 
-pub use polygon_material::material_source::{Error as MaterialSourceError, MaterialSource};
+pub use polygon_material::material_source::{BlendMode, Error as MaterialSourceError, MaterialSource};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The name of the material property that enables alpha testing.
+///
+/// See the module documentation for more information on alpha testing.
+pub static ALPHA_CUTOFF_PROPERTY: &'static str = "alpha_cutoff";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MaterialId(usize);
 derive_Counter!(MaterialId);
 
@@ -84,6 +143,7 @@ derive_Counter!(MaterialId);
 pub struct Material {
     shader: Shader,
     properties: HashMap<String, MaterialProperty>,
+    blend: Option<BlendMode>,
 }
 
 impl Material {
@@ -92,6 +152,7 @@ impl Material {
         Material {
             shader: shader,
             properties: HashMap::new(),
+            blend: None,
         }
     }
 
@@ -100,6 +161,23 @@ impl Material {
         &self.shader
     }
 
+    /// Gets the blend mode used by the material, or `None` if the material is opaque.
+    pub fn blend(&self) -> Option<BlendMode> {
+        self.blend
+    }
+
+    /// Sets the blend mode used by the material.
+    ///
+    /// Materials built from a material source use the blend mode declared in the source.
+    pub fn set_blend(&mut self, blend: Option<BlendMode>) {
+        self.blend = blend;
+    }
+
+    /// Checks if the material is transparent, i.e. if it has a blend mode.
+    pub fn is_transparent(&self) -> bool {
+        self.blend.is_some()
+    }
+
     /// Gets an iterator yielding the the current material properties.
     pub fn properties(&self) -> HashMapIter<String, MaterialProperty> {
         self.properties.iter()
@@ -154,6 +232,13 @@ impl Material {
         self.properties.insert(name.into(), MaterialProperty::Texture(texture));
     }
 
+    /// Checks if the material is alpha tested.
+    ///
+    /// See the module documentation for more information on alpha testing.
+    pub fn is_alpha_tested(&self) -> bool {
+        self.get_f32(ALPHA_CUTOFF_PROPERTY).is_some()
+    }
+
     /// Removes a property from the material.
     ///
     /// The existing property is returned if any.
@@ -6,6 +6,20 @@
 //!
 //! * Allowing meshes to be displayed numerous times in the same scene.
 //! * Associating materials with meshes in the scene.
+//!
+//! # Render Layers
+//!
+//! Each mesh instance has a render layer, which controls the order in which it is drawn relative
+//! to other mesh instances. Instances on lower layers are always drawn before instances on higher
+//! layers, so an instance on a higher layer will draw over lower layers if depth testing is
+//! disabled for its layer (see `Renderer::set_layer_depth_test()`). This is useful for things
+//! like always-on-top weapon models or UI overlays. Instances are on layer 0 by default.
+//!
+//! # Render Modes
+//!
+//! Mesh instances are drawn as filled triangles by default, but can instead be drawn as a
+//! wireframe or as a cloud of vertices by setting their `RenderMode`. This is mostly useful for
+//! debugging geometry, e.g. visualizing collision hulls.
 
 use {GpuMesh};
 use anchor::AnchorId;
@@ -20,7 +34,9 @@ use material::*;
 pub struct MeshInstance {
     mesh: GpuMesh,
     material: MaterialType,
-    anchor: Option<AnchorId>
+    anchor: Option<AnchorId>,
+    render_layer: i32,
+    render_mode: RenderMode,
 }
 
 impl MeshInstance {
@@ -30,6 +46,8 @@ impl MeshInstance {
             mesh: mesh,
             material: MaterialType::Shared(material),
             anchor: None,
+            render_layer: 0,
+            render_mode: RenderMode::Filled,
         }
     }
 
@@ -39,6 +57,8 @@ impl MeshInstance {
             mesh: mesh,
             material: MaterialType::Owned(material),
             anchor: None,
+            render_layer: 0,
+            render_mode: RenderMode::Filled,
         }
     }
 
@@ -90,8 +110,51 @@ impl MeshInstance {
     pub fn anchor(&self) -> Option<AnchorId> {
         self.anchor
     }
+
+    /// Sets the render layer for the mesh instance.
+    ///
+    /// See the module documentation for more information on render layers.
+    pub fn set_render_layer(&mut self, layer: i32) {
+        self.render_layer = layer;
+    }
+
+    /// Gets the render layer for the mesh instance.
+    pub fn render_layer(&self) -> i32 {
+        self.render_layer
+    }
+
+    /// Sets how the mesh instance's triangles are drawn.
+    ///
+    /// See the module documentation for more information on render modes.
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) {
+        self.render_mode = render_mode;
+    }
+
+    /// Gets how the mesh instance's triangles are drawn.
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+}
+
+/// Specifies how a mesh instance's triangles are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    /// Triangles are filled in, which is the default.
+    Filled,
+
+    /// Only the edges of each triangle are drawn.
+    Wireframe,
+
+    /// Only the vertices of each triangle are drawn.
+    Points,
+}
+
+impl Default for RenderMode {
+    fn default() -> RenderMode {
+        RenderMode::Filled
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct MeshInstanceId(usize);
 derive_Counter!(MeshInstanceId);
@@ -1,12 +1,28 @@
+use Counter;
 use parse_bmp::{
     Bitmap,
     BitmapData,
 };
 
 /// Represents texture data that has been sent to the GPU.
+///
+/// `GpuTexture::default()` never refers to a registered texture. Materials use it for texture
+/// properties that haven't been set, which sample as black.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct GpuTexture(usize);
-derive_Counter!(GpuTexture);
+
+impl Counter for GpuTexture {
+    /// Starts after the default id, which is reserved for unset texture properties.
+    fn initial() -> GpuTexture {
+        GpuTexture(1)
+    }
+
+    fn next(&mut self) -> GpuTexture {
+        let next = *self;
+        self.0 += 1;
+        next
+    }
+}
 
 /// Represents a texture loaded into memory and ready to be sent to the GPU.
 ///
@@ -25,27 +41,67 @@ pub struct Texture2d {
 
 impl Texture2d {
     /// Loads a new `Texture` from a bitmap file.
+    ///
+    /// Compressed bitmaps are decompressed when they're parsed, so any successfully parsed bitmap
+    /// can be converted. `Bitmap` also implements `Into<Texture2d>`, so a bitmap can be registered
+    /// directly with `renderer.register_texture(&bitmap.into())`.
     pub fn from_bitmap(bitmap: Bitmap) -> Texture2d {
-        let texture = match bitmap.data() {
-            &BitmapData::Bgr(ref data) => {
+        let width = bitmap.width();
+        let height = bitmap.height();
+
+        match bitmap.into_data() {
+            BitmapData::Bgr(data) => {
                 Texture2d {
-                    width: bitmap.width(),
-                    height: bitmap.height(),
+                    width: width,
+                    height: height,
                     format: DataFormat::Bgr,
-                    data: TextureData::u8x3(data.clone()), // TODO: Don't clone the data.
+                    data: TextureData::u8x3(data),
                 }
             },
-            &BitmapData::Bgra(ref data) => {
+            BitmapData::Bgra(data) => {
                 Texture2d {
-                    width: bitmap.width(),
-                    height: bitmap.height(),
+                    width: width,
+                    height: height,
                     format: DataFormat::Bgra,
-                    data: TextureData::u8x4(data.clone()), // TODO: Don't clone the data.
+                    data: TextureData::u8x4(data),
                 }
             },
+        }
+    }
+
+    /// Creates a new `Texture2d` from raw texture data.
+    ///
+    /// # Panics
+    ///
+    /// - If the length of `data` doesn't match the `width`, `height`, and `format` of the texture.
+    pub fn from_data(width: usize, height: usize, format: DataFormat, data: TextureData) -> Texture2d {
+        let elements_per_pixel = match format {
+            DataFormat::Rgb | DataFormat::Bgr => 3,
+            DataFormat::Rgba | DataFormat::Bgra => 4,
+        };
+
+        let elements = match data {
+            TextureData::f32(ref data) => data.len(),
+            TextureData::u8(ref data) => data.len(),
+            TextureData::u8x3(ref data) => data.len() * 3,
+            TextureData::u8x4(ref data) => data.len() * 4,
         };
 
-        texture
+        assert!(
+            elements == width * height * elements_per_pixel,
+            "Wrong amount of data for texture, width: {}, height: {}, format: {:?}, expected elements: {}, actual elements: {}",
+            width,
+            height,
+            format,
+            width * height * elements_per_pixel,
+            elements);
+
+        Texture2d {
+            width: width,
+            height: height,
+            format: format,
+            data: data,
+        }
     }
 
     /// Returns the width of the texture.
@@ -69,6 +125,12 @@ impl Texture2d {
     }
 }
 
+impl From<Bitmap> for Texture2d {
+    fn from(bitmap: Bitmap) -> Texture2d {
+        Texture2d::from_bitmap(bitmap)
+    }
+}
+
 /// An enum representing the supported data formats for a texture.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DataFormat {
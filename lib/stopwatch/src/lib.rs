@@ -15,8 +15,9 @@ use fiber::FiberId;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
+use std::io::Write;
 use std::mem;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 use std::time::Duration;
 
 #[cfg(target_os="windows")]
@@ -30,13 +31,28 @@ thread_local! {
 
 lazy_static! {
     static ref CONTEXT_MAP: Mutex<HashMap<FiberId, Context>> = Mutex::new(HashMap::with_capacity(1024));
-    static ref EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+    static ref EVENTS: Mutex<Events> = Mutex::new(Events {
+        buffer: Vec::new(),
+        sink: None,
+    });
+    static ref CLOCK: RwLock<fn() -> i64> = RwLock::new(platform::timestamp);
+}
+
+/// Sets the function used to get the timestamp for stopwatch events.
+///
+/// `clock` must return a monotonically increasing timestamp in microseconds. By default
+/// `platform::timestamp()` is used, and passing it to `set_clock()` restores the default clock.
+/// The clock is shared by all threads, so it should only be changed before any stopwatches have
+/// been started.
+pub fn set_clock(clock: fn() -> i64) {
+    let mut current = CLOCK.write().expect("Clock lock got poisoned");
+    *current = clock;
 }
 
 /// Swaps the currently tracked execution context with the specified context.
 pub fn switch_context(old: FiberId, new: FiberId) {
     with_context(|stack| {
-        let timestamp = platform::timestamp();
+        let timestamp = timestamp();
 
         // Push an end event for each of the time slices.
         for stopwatch in stack.iter().rev() {
@@ -47,6 +63,9 @@ pub fn switch_context(old: FiberId, new: FiberId) {
                 ts: timestamp,
                 tid: platform::thread_id(),
                 pid: 0,
+                overrun: None,
+                scope: None,
+                args: None,
             });
         }
     });
@@ -63,7 +82,7 @@ pub fn switch_context(old: FiberId, new: FiberId) {
     context_map.insert(old, old_context);
 
     with_context(|stack| {
-        let timestamp = platform::timestamp();
+        let timestamp = timestamp();
 
         // Push an end event for each of the time slices.
         for stopwatch in stack.iter() {
@@ -74,30 +93,99 @@ pub fn switch_context(old: FiberId, new: FiberId) {
                 ts: timestamp,
                 tid: platform::thread_id(),
                 pid: 0,
+                overrun: None,
+                scope: None,
+                args: None,
             });
         }
     });
 }
 
+/// Records the current value of a counter.
+///
+/// Counter events show up as a graph in the trace viewer, which makes them useful for tracking
+/// values that change over time such as the number of live entities.
+pub fn counter(name: &'static str, value: f64) {
+    let mut args = HashMap::with_capacity(1);
+    args.insert(name, value);
+
+    push_event(Event {
+        name: name,
+        cat: String::new(),
+        ph: "C",
+        ts: timestamp(),
+        tid: platform::thread_id(),
+        pid: 0,
+        overrun: None,
+        scope: None,
+        args: Some(args),
+    });
+}
+
+/// Records an instant event, marking a single point in time on the current thread.
+pub fn mark(name: &'static str) {
+    push_event(Event {
+        name: name,
+        cat: String::new(),
+        ph: "i",
+        ts: timestamp(),
+        tid: platform::thread_id(),
+        pid: 0,
+        overrun: None,
+        scope: Some("t"),
+        args: None,
+    });
+}
+
 /// Writes the events history to a string.
+///
+/// Once an event sink has been set with `set_event_sink()` events are no longer buffered, so only
+/// the events recorded before then that haven't been flushed to the sink are written.
 pub fn write_events_to_string() -> String {
     let events = EVENTS.lock().expect("Events mutex got poisoned");
-    serde_json::to_string(&*events).unwrap()
+    serde_json::to_string(&events.buffer).unwrap()
+}
+
+/// Streams events to `writer` as they're recorded, rather than buffering them in memory.
+///
+/// Events are written in the Chrome trace format, one event per line. Since the trace viewer
+/// accepts a JSON array that's missing its closing bracket, the output can be loaded as-is even if
+/// the program exits without cleaning up. Any events that were already buffered are written to
+/// `writer` immediately and the buffer is cleared.
+///
+/// By default events are buffered until they're retrieved with `write_events_to_string()`, which
+/// is fine for short captures but grows without bound over a long profiling session.
+pub fn set_event_sink<W: Write + Send + 'static>(writer: W) {
+    let mut events = EVENTS.lock().expect("Events mutex got poisoned");
+
+    let mut writer: Box<Write + Send> = Box::new(writer);
+    let _ = writer.write_all(b"[\n");
+    for event in events.buffer.drain(..) {
+        write_event(&mut *writer, &event);
+    }
+
+    events.sink = Some(writer);
 }
 
 pub struct Stopwatch {
     name: &'static str,
+    start: i64,
+    budget: Option<Duration>,
 }
 
 impl Stopwatch {
     pub fn new(name: &'static str) -> Stopwatch {
+        let start = timestamp();
         push_event(Event {
             name: name,
             cat: String::new(),
             ph: "B",
-            ts: platform::timestamp(),
+            ts: start,
             tid: platform::thread_id(),
             pid: 0, // TODO: Do we care about tracking process ID?
+            overrun: None,
+            scope: None,
+            args: None,
         });
 
         with_context(|stack| {
@@ -106,29 +194,55 @@ impl Stopwatch {
 
         Stopwatch {
             name: name,
+            start: start,
+            budget: None,
         }
     }
 
-    pub fn with_budget(name: &'static str, _budget: Duration) -> Stopwatch {
-        // TODO: We should actually do something with the budget, right?
-        Stopwatch::new(name)
+    /// Creates a new stopwatch that is expected to finish within `budget`.
+    ///
+    /// When the stopwatch is dropped, a warning is logged if more time than `budget` has elapsed
+    /// since it was created, and its end event is marked with `"overrun": true`. Note that time
+    /// spent while the stopwatch's fiber is suspended counts against the budget.
+    pub fn with_budget(name: &'static str, budget: Duration) -> Stopwatch {
+        let mut stopwatch = Stopwatch::new(name);
+        stopwatch.budget = Some(budget);
+        stopwatch
     }
 }
 
 impl Drop for Stopwatch {
     fn drop(&mut self) {
-        with_context(|stack| {
-            let stopwatch = stack.pop().expect("No stopwatch popped, stack is corrupted");
-            assert_eq!(self.name, stopwatch.name, "Stack got corrupted I guess");
+        with_context(|stack| pop_stopwatch(stack, self.name));
+
+        let end = timestamp();
+        let elapsed = micros_to_duration(end - self.start);
+        stats::record(self.name, elapsed);
+
+        let overrun = self.budget.map(|budget| {
+            if elapsed > budget {
+                println!(
+                    "WARNING: {} took {}, exceeding its budget of {}",
+                    self.name,
+                    PrettyDuration(elapsed),
+                    PrettyDuration(budget),
+                );
+                true
+            } else {
+                false
+            }
         });
 
         push_event(Event {
             name: self.name,
             cat: String::new(),
             ph: "E",
-            ts: platform::timestamp(),
+            ts: end,
             tid: platform::thread_id(),
             pid: 0, // TODO: Do we care about tracking process ID?
+            overrun: overrun,
+            scope: None,
+            args: None,
         });
     }
 }
@@ -152,11 +266,60 @@ struct Event {
 
     /// Thread ID for the event.
     tid: usize,
+
+    /// Whether the stopwatch exceeded its budget, only set on end events for stopwatches created
+    /// with `Stopwatch::with_budget()`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    overrun: Option<bool>,
+
+    /// Scope of an instant event, `"t"` for thread, `"p"` for process, or `"g"` for global.
+    #[serde(rename = "s", skip_serializing_if = "Option::is_none")]
+    scope: Option<&'static str>,
+
+    /// Extra data for the event, e.g. the values of a counter event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<HashMap<&'static str, f64>>,
+}
+
+fn timestamp() -> i64 {
+    let clock = CLOCK.read().expect("Clock lock got poisoned");
+    clock()
+}
+
+/// Converts a difference between two timestamps into a `Duration`.
+///
+/// Negative differences, which can only happen if the clock is changed while a stopwatch is
+/// running, are treated as zero.
+fn micros_to_duration(micros: i64) -> Duration {
+    if micros <= 0 {
+        Duration::new(0, 0)
+    } else {
+        let micros = micros as u64;
+        Duration::new(micros / 1_000_000, (micros % 1_000_000) as u32 * 1_000)
+    }
 }
 
 fn push_event(event: Event) {
     let mut events = EVENTS.lock().expect("Events mutex got poisoned");
-    events.push(event);
+    match events.sink {
+        Some(ref mut sink) => write_event(&mut **sink, &event),
+        None => events.buffer.push(event),
+    }
+}
+
+/// Writes a single event to an event sink as a line of JSON.
+fn write_event(sink: &mut Write, event: &Event) {
+    // A failure to write profiling data shouldn't take down the whole program, so write errors
+    // are ignored and the event is dropped.
+    if serde_json::to_writer(&mut *sink, event).is_ok() {
+        let _ = sink.write_all(b",\n");
+    }
+}
+
+/// The recorded events, either buffered in memory or streamed to a sink.
+struct Events {
+    buffer: Vec<Event>,
+    sink: Option<Box<Write + Send>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -166,6 +329,38 @@ struct StopwatchData {
 
 type Context = Vec<StopwatchData>;
 
+/// Pops the stopwatch named `name` off of the context stack.
+///
+/// If the stopwatch isn't on top of the stack, i.e. stopwatches were dropped out of order, a
+/// warning is logged and the stack is re-synced by also popping any stopwatches above it. If the
+/// stopwatch isn't on the stack at all the stack is left as-is. Profiling shouldn't bring down
+/// the program it's measuring, so this only panics if the "strict" feature is enabled.
+fn pop_stopwatch(stack: &mut Context, name: &'static str) {
+    if stack.last().map(|stopwatch| stopwatch.name) == Some(name) {
+        stack.pop();
+        return;
+    }
+
+    if cfg!(feature = "strict") {
+        panic!("Stopwatch stack is corrupted, expected {} on top of stack {:?}", name, stack);
+    }
+
+    match stack.iter().rposition(|stopwatch| stopwatch.name == name) {
+        Some(index) => {
+            println!(
+                "WARNING: Stopwatch {} was dropped before {:?}, discarding them",
+                name,
+                &stack[index + 1 ..],
+            );
+            stack.truncate(index);
+        }
+
+        None => {
+            println!("WARNING: Stopwatch {} was dropped but isn't on the stopwatch stack", name);
+        }
+    }
+}
+
 fn with_context<F, T>(func: F) -> T
     where F: FnOnce(&mut Context) -> T
 {
@@ -1,5 +1,61 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::time::Duration;
 
+lazy_static! {
+    static ref SPANS: Mutex<HashMap<&'static str, SpanStats>> = Mutex::new(HashMap::new());
+}
+
+// Aggregate stats for stopwatch spans.
+// ============================================================================================
+/// Aggregate timing for all stopwatches with the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanStats {
+    pub count: usize,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+/// Returns the aggregate stats for each stopwatch name recorded since the last `reset()`.
+///
+/// Stats are recorded when a stopwatch is dropped, so stopwatches that are still running aren't
+/// included.
+pub fn summary() -> HashMap<&'static str, SpanStats> {
+    let spans = SPANS.lock().expect("Span stats mutex got poisoned");
+    spans.clone()
+}
+
+/// Clears all recorded span stats.
+///
+/// Call this at the start of each frame to get per-frame stats from `summary()`.
+pub fn reset() {
+    let mut spans = SPANS.lock().expect("Span stats mutex got poisoned");
+    spans.clear();
+}
+
+/// Adds a completed span to the stats for `name`.
+///
+/// Called by `Stopwatch` when it's dropped. Not intended to be called directly.
+#[doc(hidden)]
+pub fn record(name: &'static str, duration: Duration) {
+    let mut spans = SPANS.lock().expect("Span stats mutex got poisoned");
+    let stats = spans.entry(name).or_insert(SpanStats {
+        count: 0,
+        total: Duration::new(0, 0),
+        min: duration,
+        max: duration,
+        mean: Duration::new(0, 0),
+    });
+
+    stats.count += 1;
+    stats.total += duration;
+    if duration < stats.min { stats.min = duration; }
+    if duration > stats.max { stats.max = duration; }
+    stats.mean = stats.total / stats.count as u32;
+}
+
 // Calculate performance statistics.
 // ============================================================================================
 fn as_nanos(duration: Duration) -> u64 {
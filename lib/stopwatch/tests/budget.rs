@@ -0,0 +1,45 @@
+extern crate serde_json;
+extern crate stopwatch;
+
+mod common;
+
+use serde_json::Value;
+use std::time::Duration;
+use stopwatch::Stopwatch;
+
+#[test]
+fn budget_overrun() {
+    stopwatch::set_clock(common::fake_clock);
+
+    {
+        let _over = Stopwatch::with_budget("over", Duration::new(0, 5_000));
+    }
+    {
+        let _under = Stopwatch::with_budget("under", Duration::from_millis(1));
+    }
+    {
+        let _unbudgeted = Stopwatch::new("unbudgeted");
+    }
+
+    let events: Value = serde_json::from_str(&stopwatch::write_events_to_string()).unwrap();
+    let events = events.as_array().unwrap();
+
+    let expected = [
+        ("over", "B", None),
+        ("over", "E", Some(true)),
+        ("under", "B", None),
+        ("under", "E", Some(false)),
+        ("unbudgeted", "B", None),
+        ("unbudgeted", "E", None),
+    ];
+    assert_eq!(expected.len(), events.len());
+
+    for (event, &(name, phase, overrun)) in events.iter().zip(expected.iter()) {
+        let event = event.as_object().unwrap();
+        assert_eq!(Some(name), event["name"].as_str());
+        assert_eq!(Some(phase), event["ph"].as_str());
+        assert_eq!(overrun, event.get("overrun").and_then(Value::as_bool));
+    }
+
+    stopwatch::set_clock(stopwatch::platform::timestamp);
+}
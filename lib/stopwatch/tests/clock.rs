@@ -0,0 +1,44 @@
+extern crate serde_json;
+extern crate stopwatch;
+
+mod common;
+
+use serde_json::Value;
+use stopwatch::Stopwatch;
+
+#[test]
+fn custom_clock() {
+    stopwatch::set_clock(common::fake_clock);
+
+    {
+        let _outer = Stopwatch::new("outer");
+        {
+            let _inner = Stopwatch::new("inner");
+        }
+    }
+
+    let events: Value = serde_json::from_str(&stopwatch::write_events_to_string()).unwrap();
+    let events = events.as_array().unwrap();
+
+    let expected = [
+        ("outer", "B", 0),
+        ("inner", "B", 10),
+        ("inner", "E", 20),
+        ("outer", "E", 30),
+    ];
+    assert_eq!(expected.len(), events.len());
+
+    for (event, &(name, phase, timestamp)) in events.iter().zip(expected.iter()) {
+        let event = event.as_object().unwrap();
+        assert_eq!(Some(name), event["name"].as_str());
+        assert_eq!(Some(phase), event["ph"].as_str());
+        assert_eq!(Some(timestamp), event["ts"].as_i64());
+    }
+
+    // The inner stopwatch's duration is exactly one tick of the fake clock.
+    let inner_duration = events[2].as_object().unwrap()["ts"].as_i64().unwrap()
+                       - events[1].as_object().unwrap()["ts"].as_i64().unwrap();
+    assert_eq!(10, inner_duration);
+
+    stopwatch::set_clock(stopwatch::platform::timestamp);
+}
@@ -0,0 +1,8 @@
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+static FAKE_TIME: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Fake clock that advances by 10 microseconds every time it's queried.
+pub fn fake_clock() -> i64 {
+    FAKE_TIME.fetch_add(10, Ordering::SeqCst) as i64
+}
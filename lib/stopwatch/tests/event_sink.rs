@@ -0,0 +1,61 @@
+extern crate serde_json;
+extern crate stopwatch;
+
+use serde_json::Value;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use stopwatch::Stopwatch;
+
+/// Writer that appends to a shared buffer so the test can inspect what was written.
+#[derive(Clone)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn event_sink() {
+    // Record an event before the sink is set, it should be flushed to the sink.
+    {
+        let _before = Stopwatch::new("before");
+    }
+
+    let buffer = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+    stopwatch::set_event_sink(buffer.clone());
+
+    {
+        let _after = Stopwatch::new("after");
+    }
+
+    // Nothing is left in the in-memory buffer.
+    assert_eq!("[]", stopwatch::write_events_to_string());
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let mut lines = output.lines();
+    assert_eq!(Some("["), lines.next());
+
+    let expected = [
+        ("before", "B"),
+        ("before", "E"),
+        ("after", "B"),
+        ("after", "E"),
+    ];
+    let lines = lines.collect::<Vec<_>>();
+    assert_eq!(expected.len(), lines.len());
+
+    for (line, &(name, phase)) in lines.iter().zip(expected.iter()) {
+        assert!(line.ends_with(","), "Event line {:?} should end with a comma", line);
+
+        let event: Value = serde_json::from_str(&line[.. line.len() - 1]).unwrap();
+        let event = event.as_object().unwrap();
+        assert_eq!(Some(name), event["name"].as_str());
+        assert_eq!(Some(phase), event["ph"].as_str());
+    }
+}
@@ -0,0 +1,25 @@
+extern crate serde_json;
+extern crate stopwatch;
+
+use serde_json::Value;
+
+#[test]
+fn counter_and_mark() {
+    stopwatch::counter("entities", 42.0);
+    stopwatch::mark("level loaded");
+
+    let events: Value = serde_json::from_str(&stopwatch::write_events_to_string()).unwrap();
+    let events = events.as_array().unwrap();
+    assert_eq!(2, events.len());
+
+    let counter = events[0].as_object().unwrap();
+    assert_eq!(Some("entities"), counter["name"].as_str());
+    assert_eq!(Some("C"), counter["ph"].as_str());
+    assert_eq!(Some(42.0), counter["args"].as_object().unwrap()["entities"].as_f64());
+
+    let mark = events[1].as_object().unwrap();
+    assert_eq!(Some("level loaded"), mark["name"].as_str());
+    assert_eq!(Some("i"), mark["ph"].as_str());
+    assert_eq!(Some("t"), mark["s"].as_str());
+    assert!(mark.get("args").is_none());
+}
@@ -0,0 +1,41 @@
+#![cfg(not(feature = "strict"))]
+
+extern crate serde_json;
+extern crate stopwatch;
+
+use serde_json::Value;
+use stopwatch::Stopwatch;
+
+#[test]
+fn misnested_stopwatches() {
+    let outer = Stopwatch::new("outer");
+    let inner = Stopwatch::new("inner");
+
+    // Dropping the stopwatches out of order shouldn't panic, and the stack should recover so that
+    // later stopwatches work normally.
+    drop(outer);
+    drop(inner);
+
+    {
+        let _after = Stopwatch::new("after");
+    }
+
+    let events: Value = serde_json::from_str(&stopwatch::write_events_to_string()).unwrap();
+    let events = events.as_array().unwrap();
+
+    let expected = [
+        ("outer", "B"),
+        ("inner", "B"),
+        ("outer", "E"),
+        ("inner", "E"),
+        ("after", "B"),
+        ("after", "E"),
+    ];
+    assert_eq!(expected.len(), events.len());
+
+    for (event, &(name, phase)) in events.iter().zip(expected.iter()) {
+        let event = event.as_object().unwrap();
+        assert_eq!(Some(name), event["name"].as_str());
+        assert_eq!(Some(phase), event["ph"].as_str());
+    }
+}
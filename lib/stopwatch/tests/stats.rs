@@ -0,0 +1,39 @@
+extern crate stopwatch;
+
+mod common;
+
+use std::time::Duration;
+use stopwatch::Stopwatch;
+use stopwatch::stats;
+
+#[test]
+fn span_summary() {
+    stopwatch::set_clock(common::fake_clock);
+
+    {
+        let _outer = Stopwatch::new("outer");
+        for _ in 0..3 {
+            let _inner = Stopwatch::new("inner");
+        }
+    }
+
+    let summary = stats::summary();
+    assert_eq!(2, summary.len());
+
+    let inner = summary["inner"];
+    assert_eq!(3, inner.count);
+    assert_eq!(Duration::new(0, 30_000), inner.total);
+    assert_eq!(Duration::new(0, 10_000), inner.min);
+    assert_eq!(Duration::new(0, 10_000), inner.max);
+    assert_eq!(Duration::new(0, 10_000), inner.mean);
+
+    // The outer stopwatch spans its own start and end plus two ticks for each inner stopwatch.
+    let outer = summary["outer"];
+    assert_eq!(1, outer.count);
+    assert_eq!(Duration::new(0, 70_000), outer.total);
+
+    stats::reset();
+    assert!(stats::summary().is_empty());
+
+    stopwatch::set_clock(stopwatch::platform::timestamp);
+}
@@ -2,6 +2,7 @@ use camera::CameraData;
 use mesh_renderer::MeshRendererData;
 use resource::{MaterialId, MeshId};
 use scheduler::{self, WorkId};
+use time;
 use transform::{TransformInnerHandle, TransformGraph};
 use bootstrap::window::{Message, Window};
 use cell_extras::{AtomicInitCell, InitCell};
@@ -124,6 +125,7 @@ impl EngineBuilder {
             channel: receiever,
 
             mesh_map: HashMap::new(),
+            mesh_renderers: Vec::new(),
 
             scene_graph: TransformGraph::new(),
             lights: Vec::new(),
@@ -170,6 +172,10 @@ pub struct Engine {
 
     mesh_map: HashMap<MeshId, GpuMesh>,
 
+    // Mesh renderers hold a handle to their mesh, so keeping them here keeps the mesh registered
+    // with the renderer for as long as it's being drawn.
+    mesh_renderers: Vec<Box<MeshRendererData>>,
+
     scene_graph: TransformGraph,
     lights: Vec<LightInner>,
     camera: Option<(Box<CameraData>, CameraId)>,
@@ -227,6 +233,7 @@ pub enum EngineMessage {
     Light(LightInner),
     Material(MaterialId, ::polygon::material::MaterialSource),
     Mesh(MeshId, ::polygon::geometry::mesh::Mesh),
+    DropMesh(MeshId),
     MeshInstance(Box<MeshRendererData>, TransformInnerHandle),
     Behavior(Box<FnMut() + Send>),
 }
@@ -263,6 +270,7 @@ fn main_loop(mut engine: Box<Engine>) {
 
     let start_time = Instant::now();
     let mut frame_start = Instant::now();
+    let mut last_frame_start = frame_start;
 
     'main: loop {
         {
@@ -361,10 +369,19 @@ fn main_loop(mut engine: Box<Engine>) {
                         },
                         EngineMessage::Mesh(mesh_id, mesh_data) => {
                             let _s = Stopwatch::new("Mesh message");
-                            let gpu_mesh = engine.renderer.register_mesh(&mesh_data);
+                            let gpu_mesh = engine.renderer
+                                .register_mesh(&mesh_data)
+                                .expect("TODO: Handle mesh allocation failure");
                             let last = engine.mesh_map.insert(mesh_id, gpu_mesh);
                             assert!(last.is_none(), "Duplicate mesh_id found: {:?}", mesh_id);
                         },
+                        EngineMessage::DropMesh(mesh_id) => {
+                            let _s = Stopwatch::new("Drop mesh message");
+                            let gpu_mesh = engine.mesh_map
+                                .remove(&mesh_id)
+                                .expect("No gpu mesh found for mesh id");
+                            engine.renderer.unregister_mesh(gpu_mesh);
+                        },
                         EngineMessage::MeshInstance(mesh_renderer_data, transform_inner) => {
                             let _s = Stopwatch::new("Mesh instance message");
                             let anchor_id = match transform_inner.anchor() {
@@ -385,6 +402,8 @@ fn main_loop(mut engine: Box<Engine>) {
                             mesh_instance.set_anchor(anchor_id);
 
                             let _ = engine.renderer.register_mesh_instance(mesh_instance);
+
+                            engine.mesh_renderers.push(mesh_renderer_data);
                         }
                         EngineMessage::Behavior(func) => {
                             let _s = Stopwatch::new("Behavior message");
@@ -455,6 +474,11 @@ fn main_loop(mut engine: Box<Engine>) {
         while Instant::now() < frame_start {
             thread::sleep(Duration::new(0, 0));
         }
+
+        // Record the measured frame time, including time spent waiting for the frame cadence.
+        let now = Instant::now();
+        time::record_frame(now - last_frame_start);
+        last_frame_start = now;
     }
 
     // Print performance statistics.
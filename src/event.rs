@@ -0,0 +1,158 @@
+//! Typed event queues for communication between systems.
+//!
+//! An `EventQueue<E>` lets one system publish events without knowing which systems consume them.
+//! Any number of systems can subscribe to a queue, and every subscriber receives its own copy of
+//! each event published after it subscribed. For example, the collision system can publish
+//! collision events that an audio system uses to play impact sounds, without the collision system
+//! knowing anything about audio:
+//!
+//! ```rust,ignore
+//! let collisions = EventQueue::new();
+//!
+//! let mut impacts = collisions.subscribe();
+//! engine::run_each_frame(move || {
+//!     for collision in impacts.drain() {
+//!         // Play a sound.
+//!     }
+//! });
+//!
+//! engine::run_each_frame(move || {
+//!     collisions.publish(Collision { ... });
+//! });
+//! ```
+//!
+//! Events are delivered as soon as they are published, so a subscriber that drains its events
+//! after the publishing system has run will see them in the same frame. Subscribers should drain
+//! their events every frame, otherwise events will accumulate until they do.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
+
+/// A queue that broadcasts published events to all of its subscribers.
+///
+/// Cloning an `EventQueue` creates a new handle to the same queue, so it can be shared between
+/// all of the systems that publish to it.
+#[derive(Debug)]
+pub struct EventQueue<E> {
+    subscribers: Arc<Mutex<Vec<Weak<Mutex<VecDeque<E>>>>>>,
+}
+
+impl<E: Clone> EventQueue<E> {
+    /// Creates a new event queue with no subscribers.
+    pub fn new() -> EventQueue<E> {
+        EventQueue {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Publishes an event to all current subscribers.
+    ///
+    /// If there are no subscribers the event is discarded.
+    pub fn publish(&self, event: E) {
+        let mut subscribers = self.subscribers.lock().expect("Event queue lock was poisoned");
+
+        // Deliver the event to all live subscribers, dropping any that have gone away.
+        subscribers.retain(|subscriber| {
+            match subscriber.upgrade() {
+                Some(events) => {
+                    events.lock().expect("Subscriber lock was poisoned").push_back(event.clone());
+                    true
+                },
+                None => false,
+            }
+        });
+    }
+
+    /// Creates a new subscriber to the queue.
+    ///
+    /// The subscriber will receive all events published after it was created.
+    pub fn subscribe(&self) -> Subscriber<E> {
+        let events = Arc::new(Mutex::new(VecDeque::new()));
+
+        let mut subscribers = self.subscribers.lock().expect("Event queue lock was poisoned");
+        subscribers.push(Arc::downgrade(&events));
+
+        Subscriber {
+            events: events,
+        }
+    }
+}
+
+impl<E> Clone for EventQueue<E> {
+    fn clone(&self) -> EventQueue<E> {
+        EventQueue {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+/// A handle for receiving the events published to an `EventQueue`.
+///
+/// Dropping the subscriber unsubscribes it from the queue.
+#[derive(Debug)]
+pub struct Subscriber<E> {
+    events: Arc<Mutex<VecDeque<E>>>,
+}
+
+impl<E> Subscriber<E> {
+    /// Removes and returns all pending events, in the order they were published.
+    pub fn drain(&mut self) -> ::std::vec::IntoIter<E> {
+        let mut events = self.events.lock().expect("Subscriber lock was poisoned");
+        events.drain(..).collect::<Vec<_>>().into_iter()
+    }
+
+    /// Returns `true` if there are no pending events.
+    pub fn is_empty(&self) -> bool {
+        self.events.lock().expect("Subscriber lock was poisoned").is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Collision(u32);
+
+    #[test]
+    fn publish_observed_same_frame() {
+        let collisions = EventQueue::new();
+        let mut audio = collisions.subscribe();
+        let mut gameplay = collisions.subscribe();
+
+        // Simulate one frame: The collision system runs its update, then the audio and gameplay
+        // systems run theirs.
+        let collision_system = {
+            let collisions = collisions.clone();
+            move || {
+                collisions.publish(Collision(1));
+                collisions.publish(Collision(2));
+            }
+        };
+
+        collision_system();
+        assert_eq!(vec![Collision(1), Collision(2)], audio.drain().collect::<Vec<_>>());
+        assert_eq!(vec![Collision(1), Collision(2)], gameplay.drain().collect::<Vec<_>>());
+
+        // Events are only delivered once.
+        assert!(audio.is_empty());
+        assert!(gameplay.is_empty());
+    }
+
+    #[test]
+    fn late_and_dropped_subscribers() {
+        let queue = EventQueue::new();
+        queue.publish(Collision(0));
+
+        // Subscribers don't see events published before they subscribed.
+        let mut subscriber = queue.subscribe();
+        assert!(subscriber.is_empty());
+
+        // Dropped subscribers are removed on the next publish.
+        let dropped = queue.subscribe();
+        ::std::mem::drop(dropped);
+        queue.publish(Collision(1));
+        assert_eq!(1, queue.subscribers.lock().unwrap().len());
+        assert_eq!(vec![Collision(1)], subscriber.drain().collect::<Vec<_>>());
+    }
+}
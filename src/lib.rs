@@ -33,6 +33,7 @@ pub mod macros;
 pub mod camera;
 pub mod collections;
 pub mod engine;
+pub mod event;
 pub mod input;
 pub mod light;
 pub mod mesh_renderer;
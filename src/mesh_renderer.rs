@@ -12,9 +12,7 @@ pub struct MeshRenderer {
 
 impl MeshRenderer {
     pub fn new(mesh: &Mesh, transform: &Transform) -> MeshRenderer {
-        let mut data = Box::new(MeshRendererData {
-            mesh_id: mesh.id(),
-        });
+        let mut data = Box::new(MeshRendererData::new(mesh));
 
         let ptr = &mut *data as *mut _;
 
@@ -33,11 +31,21 @@ impl MeshRenderer {
 
 unsafe impl Send for MeshRenderer {}
 
+/// The engine-side data for a mesh renderer.
+///
+/// Holds a handle to the mesh, so the mesh stays registered with the renderer for as long as the
+/// mesh renderer exists, even if all other handles to the mesh have been dropped.
 #[derive(Debug)]
 pub struct MeshRendererData {
-    mesh_id: MeshId
+    mesh: Mesh,
 }
 
 impl MeshRendererData {
-    pub fn mesh_id(&self) -> MeshId { self.mesh_id }
+    pub fn new(mesh: &Mesh) -> MeshRendererData {
+        MeshRendererData {
+            mesh: mesh.clone(),
+        }
+    }
+
+    pub fn mesh_id(&self) -> MeshId { self.mesh.id() }
 }
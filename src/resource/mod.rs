@@ -3,11 +3,13 @@ use scheduler::{self, Async};
 use polygon::geometry::mesh::{BuildMeshError, MeshBuilder};
 use polygon::math::Vector2;
 use obj::{self, Obj};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::string::FromUtf8Error;
+use std::sync::{Arc, Mutex, Weak};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use stopwatch::Stopwatch;
 
@@ -16,6 +18,15 @@ pub mod collada;
 static MESH_ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
 static MATERIAL_ID_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
+lazy_static! {
+    /// Cache of the meshes that are currently loaded, keyed on the path they were loaded from.
+    ///
+    /// The cache only holds weak references so that it doesn't keep meshes alive on its own. Once
+    /// the last `Mesh` handle for an asset is dropped the entry is removed and the mesh data is
+    /// released by the engine.
+    static ref MESH_CACHE: Mutex<HashMap<PathBuf, Weak<MeshInner>>> = Mutex::new(HashMap::new());
+}
+
 /// Load all data from the specified file as an array of bytes.
 pub fn load_file_bytes<'a, P>(path: P) -> Async<'a, Result<Vec<u8>, io::Error>>
     where
@@ -74,6 +85,10 @@ impl From<FromUtf8Error> for LoadTextError {
 ///
 /// Loads a mesh data from the specified path and performs any necessary processing to prepare it
 /// to be used in rendering.
+///
+/// Meshes are cached based on the path they were loaded from, so loading the same path again
+/// while a `Mesh` handle for it is still alive returns a handle to the already-loaded mesh rather
+/// than sending the mesh data to the renderer a second time.
 pub fn load_mesh<'a, P>(path: P) -> Async<'a, Result<Mesh, LoadMeshError>>
     where
     P: 'a,
@@ -81,6 +96,15 @@ pub fn load_mesh<'a, P>(path: P) -> Async<'a, Result<Mesh, LoadMeshError>>
 {
     scheduler::start(move || {
         let _s = Stopwatch::new("Load mesh");
+        let cache_key = path.as_ref().to_path_buf();
+
+        // Reuse the existing mesh if one has already been loaded from the same path.
+        // TODO: Two loads of the same path that are in flight at the same time will both load
+        // the mesh. Should we track pending loads in the cache too?
+        if let Some(mesh) = cached_mesh(&cache_key) {
+            return Ok(mesh);
+        }
+
         let extension: Option<String> = path.as_ref().extension().map(|ext| ext.to_string_lossy().into_owned());
 
         // Load mesh source and parse mesh data based on file type.
@@ -140,25 +164,61 @@ pub fn load_mesh<'a, P>(path: P) -> Async<'a, Result<Mesh, LoadMeshError>>
 
         engine::send_message(EngineMessage::Mesh(mesh_id, mesh_data));
 
-        Ok(Mesh(mesh_id))
+        Ok(cache_mesh(cache_key, mesh_id))
     })
 }
 
+/// Returns a handle to the mesh loaded from `path`, if it is still alive.
+fn cached_mesh(path: &Path) -> Option<Mesh> {
+    MESH_CACHE.lock().unwrap().get(path).and_then(Weak::upgrade).map(Mesh)
+}
+
+/// Creates the handle for a newly-registered mesh and adds it to the mesh cache.
+fn cache_mesh(path: PathBuf, mesh_id: MeshId) -> Mesh {
+    let inner = Arc::new(MeshInner {
+        id: mesh_id,
+        path: path.clone(),
+    });
+    MESH_CACHE.lock().unwrap().insert(path, Arc::downgrade(&inner));
+
+    Mesh(inner)
+}
+
 pub type MeshId = usize;
 
-#[derive(Debug)]
-pub struct Mesh(MeshId);
+/// A shared handle to a mesh that has been loaded and registered with the renderer.
+///
+/// Cloning a `Mesh` is cheap and produces another handle to the same mesh data. The mesh data is
+/// released once all handles to it have been dropped.
+#[derive(Debug, Clone)]
+pub struct Mesh(Arc<MeshInner>);
 
 impl Mesh {
     // TODO: Make this private to the crate.
     pub fn id(&self) -> MeshId {
-        self.0
+        self.0.id
     }
 }
 
-impl Drop for Mesh {
+#[derive(Debug)]
+struct MeshInner {
+    id: MeshId,
+    path: PathBuf,
+}
+
+impl Drop for MeshInner {
     fn drop(&mut self) {
-        // TODO: How do we cleanup a mesh?
+        // Remove the cache entry, unless the mesh has already been reloaded and the entry now
+        // refers to the new mesh.
+        {
+            let mut cache = MESH_CACHE.lock().unwrap();
+            let is_stale = cache.get(&self.path).map_or(false, |weak| weak.upgrade().is_none());
+            if is_stale {
+                cache.remove(&self.path);
+            }
+        }
+
+        engine::send_message(EngineMessage::DropMesh(self.id));
     }
 }
 
@@ -243,3 +303,60 @@ impl From<::polygon::BuildMaterialError> for LoadMaterialError {
         LoadMaterialError::BuildMaterialError(from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::RENDER_MESSAGE_CHANNEL;
+    use mesh_renderer::MeshRendererData;
+    use std::sync::mpsc;
+
+    #[test]
+    fn shared_mesh_dropped_once() {
+        let (sender, receiver) = mpsc::channel();
+        RENDER_MESSAGE_CHANNEL.with(move |channel| { channel.init(sender); });
+
+        let path = PathBuf::from("test/shared_mesh_dropped_once.obj");
+        let mesh_id = MESH_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        // Loading the same path again while the first handle is alive shares the mesh.
+        let first = cache_mesh(path.clone(), mesh_id);
+        let second = cached_mesh(&path).expect("Mesh wasn't in the cache");
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+
+        // The mesh is only dropped once the last handle goes away.
+        ::std::mem::drop(first);
+        assert!(receiver.try_recv().is_err());
+
+        ::std::mem::drop(second);
+        match receiver.try_recv() {
+            Ok(EngineMessage::DropMesh(id)) => assert_eq!(mesh_id, id),
+            _ => panic!("Expected a DropMesh message for {}", mesh_id),
+        }
+        assert!(receiver.try_recv().is_err());
+        assert!(cached_mesh(&path).is_none());
+    }
+
+    #[test]
+    fn mesh_renderer_keeps_mesh_alive() {
+        let (sender, receiver) = mpsc::channel();
+        RENDER_MESSAGE_CHANNEL.with(move |channel| { channel.init(sender); });
+
+        let path = PathBuf::from("test/mesh_renderer_keeps_mesh_alive.obj");
+        let mesh_id = MESH_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        let mesh = cache_mesh(path.clone(), mesh_id);
+        let renderer_data = MeshRendererData::new(&mesh);
+
+        // Dropping the user's handle leaves the mesh registered while a renderer still draws it.
+        ::std::mem::drop(mesh);
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(mesh_id, renderer_data.mesh_id());
+
+        ::std::mem::drop(renderer_data);
+        match receiver.try_recv() {
+            Ok(EngineMessage::DropMesh(id)) => assert_eq!(mesh_id, id),
+            _ => panic!("Expected a DropMesh message for {}", mesh_id),
+        }
+    }
+}
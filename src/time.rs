@@ -7,7 +7,13 @@
 //! rather it gives the current locked framerate for the game. Therefore, game code can be
 //! written with the assumption of a fixed time step (i.e. the delta will be the same
 //! frame-to-frame) even if the exact time step may occaisonally change in practice.
+//!
+//! For diagnostics such as an FPS counter the module also tracks the measured duration of recent
+//! frames. `fps()` and `frame_time_ms()` report on the last frame, and `smoothed_fps()` averages
+//! over a rolling window of frames (see `set_smoothing_window()`).
 
+use std::collections::VecDeque;
+use std::sync::Mutex;
 use std::time::Duration;
 
 /// Returns the exact time between frames.
@@ -23,3 +29,178 @@ pub fn delta() -> Duration {
 pub fn delta_f32() -> f32 {
     1.0 / 60.0
 }
+
+/// The default number of frames averaged by `smoothed_fps()`.
+pub const DEFAULT_SMOOTHING_WINDOW: usize = 60;
+
+lazy_static! {
+    static ref FRAME_STATS: Mutex<FrameStats> = Mutex::new(FrameStats::new(DEFAULT_SMOOTHING_WINDOW));
+}
+
+/// Returns the instantaneous framerate, based on the measured duration of the last frame.
+///
+/// Unlike `delta()`, this is the actual time the last frame took and is intended for diagnostics
+/// (e.g. an FPS counter), not for game logic. Returns `0.0` if no frames have completed yet.
+pub fn fps() -> f32 {
+    FRAME_STATS.lock().expect("Frame stats mutex was poisoned").fps()
+}
+
+/// Returns the measured duration of the last frame in milliseconds.
+///
+/// Returns `0.0` if no frames have completed yet.
+pub fn frame_time_ms() -> f32 {
+    FRAME_STATS.lock().expect("Frame stats mutex was poisoned").frame_time_ms()
+}
+
+/// Returns the framerate averaged over the last several frames.
+///
+/// The number of frames averaged is controlled with `set_smoothing_window()` and defaults to
+/// `DEFAULT_SMOOTHING_WINDOW`. Returns `0.0` if no frames have completed yet.
+pub fn smoothed_fps() -> f32 {
+    FRAME_STATS.lock().expect("Frame stats mutex was poisoned").smoothed_fps()
+}
+
+/// Sets the number of frames averaged by `smoothed_fps()`.
+///
+/// # Panics
+///
+/// Panics if `window` is 0.
+pub fn set_smoothing_window(window: usize) {
+    FRAME_STATS.lock().expect("Frame stats mutex was poisoned").set_window(window);
+}
+
+/// Records the measured duration of a completed frame. Called by the engine once per frame.
+///
+/// Not intended to be called directly.
+#[doc(hidden)]
+pub fn record_frame(frame_time: Duration) {
+    FRAME_STATS.lock().expect("Frame stats mutex was poisoned").push(frame_time);
+}
+
+/// Rolling record of recent frame times.
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    frame_times: VecDeque<Duration>,
+    window: usize,
+    total: Duration,
+}
+
+impl FrameStats {
+    /// Creates an empty `FrameStats` that averages over the last `window` frames.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn new(window: usize) -> FrameStats {
+        assert!(window > 0, "Smoothing window must contain at least one frame");
+
+        FrameStats {
+            frame_times: VecDeque::with_capacity(window),
+            window: window,
+            total: Duration::new(0, 0),
+        }
+    }
+
+    /// Adds the duration of a completed frame, discarding the oldest frame if the window is full.
+    pub fn push(&mut self, frame_time: Duration) {
+        self.frame_times.push_back(frame_time);
+        self.total += frame_time;
+
+        self.trim();
+    }
+
+    /// Changes the number of frames averaged, discarding the oldest frames if necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn set_window(&mut self, window: usize) {
+        assert!(window > 0, "Smoothing window must contain at least one frame");
+
+        self.window = window;
+        self.trim();
+    }
+
+    /// Returns the duration of the most recent frame, if any.
+    pub fn last_frame_time(&self) -> Option<Duration> {
+        self.frame_times.back().cloned()
+    }
+
+    /// Returns the average duration of the frames in the window, if any.
+    pub fn average_frame_time(&self) -> Option<Duration> {
+        if self.frame_times.is_empty() {
+            None
+        } else {
+            Some(self.total / self.frame_times.len() as u32)
+        }
+    }
+
+    /// Returns the framerate based on the most recent frame.
+    pub fn fps(&self) -> f32 {
+        self.last_frame_time().map(fps_from_duration).unwrap_or(0.0)
+    }
+
+    /// Returns the duration of the most recent frame in milliseconds.
+    pub fn frame_time_ms(&self) -> f32 {
+        self.last_frame_time().map(|time| duration_secs(time) * 1000.0).unwrap_or(0.0)
+    }
+
+    /// Returns the framerate based on the average frame time over the window.
+    pub fn smoothed_fps(&self) -> f32 {
+        self.average_frame_time().map(fps_from_duration).unwrap_or(0.0)
+    }
+
+    fn trim(&mut self) {
+        while self.frame_times.len() > self.window {
+            let oldest = self.frame_times.pop_front().unwrap();
+            self.total -= oldest;
+        }
+    }
+}
+
+fn duration_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+fn fps_from_duration(duration: Duration) -> f32 {
+    let secs = duration_secs(duration);
+    if secs > 0.0 { 1.0 / secs } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn empty_stats() {
+        let stats = FrameStats::new(4);
+        assert_eq!(0.0, stats.fps());
+        assert_eq!(0.0, stats.frame_time_ms());
+        assert_eq!(0.0, stats.smoothed_fps());
+    }
+
+    #[test]
+    fn rolling_average() {
+        let mut stats = FrameStats::new(4);
+        for &ms in &[10, 20, 30, 40] {
+            stats.push(millis(ms));
+        }
+
+        assert_eq!(Some(millis(25)), stats.average_frame_time());
+        assert!((stats.smoothed_fps() - 40.0).abs() < 0.001);
+        assert!((stats.frame_time_ms() - 40.0).abs() < 0.001);
+        assert!((stats.fps() - 25.0).abs() < 0.001);
+
+        // Pushing past the window drops the oldest frame.
+        stats.push(millis(50));
+        assert_eq!(Some(millis(35)), stats.average_frame_time());
+
+        // Shrinking the window drops the oldest frames.
+        stats.set_window(2);
+        assert_eq!(Some(millis(45)), stats.average_frame_time());
+    }
+}